@@ -0,0 +1,76 @@
+# functions available to tankgrid player programs
+
+def turn_cw() -> None:
+    """turn 90 degrees clockwise. takes 16 game time, acts after 8"""
+
+def turn_right() -> None:
+    """turn 90 degrees clockwise. takes 16 game time, acts after 8. same as turn_cw"""
+
+def turn_r() -> None:
+    """turn 90 degrees clockwise. takes 16 game time, acts after 8. same as turn_cw"""
+
+def turn_ccw() -> None:
+    """turn 90 degrees counterclockwise. takes 16 game time, acts after 8"""
+
+def turn_left() -> None:
+    """turn 90 degrees counterclockwise. takes 16 game time, acts after 8. same as turn_ccw"""
+
+def turn_l() -> None:
+    """turn 90 degrees counterclockwise. takes 16 game time, acts after 8. same as turn_ccw"""
+
+def move_forward() -> None:
+    """move one cell forward, tank stays in place if the cell is taken or impassable. takes 10 game time, acts after 5"""
+
+def move_fwd() -> None:
+    """move one cell forward, tank stays in place if the cell is taken or impassable. takes 10 game time, acts after 5. same as move_forward"""
+
+def move_backward() -> None:
+    """move one cell back without turning, tank stays in place if the cell is taken or impassable. takes 14 game time, acts after 7"""
+
+def move_backwards() -> None:
+    """move one cell back without turning, tank stays in place if the cell is taken or impassable. takes 14 game time, acts after 7. same as move_backward"""
+
+def move_back() -> None:
+    """move one cell back without turning, tank stays in place if the cell is taken or impassable. takes 14 game time, acts after 7. same as move_backward"""
+
+def shoot() -> None:
+    """shoot forward if there is ammo left, the gun then needs to cool down. takes 10 game time, acts after 5"""
+
+def fire() -> None:
+    """shoot forward if there is ammo left, the gun then needs to cool down. takes 10 game time, acts after 5. same as shoot"""
+
+def wait() -> None:
+    """do nothing for a while. takes 5 game time"""
+
+def print(*args: object) -> None:
+    """write arguments separated by spaces to the battle log, long lines are cut. takes 0 game time"""
+
+def look(direction: str) -> list[tuple[str, str | None]]:
+    """cells seen in direction "forward", "right", "back" or "left" of the tank, nearest first, as (tile, object) pairs. object is like "player[front]", "ally[left-side]" or "ammocrate[back]", telling which side of it faces the tank. takes 4 game time"""
+
+def listen() -> list[str]:
+    """directions to recent sounds of moving, turning and shooting tanks, nearest first, like "front-left-side" or "back-right-along". takes 15 game time, acts after 3"""
+
+def check_ammo() -> int:
+    """shots left. takes 2 game time"""
+
+def check_health() -> int:
+    """health left. takes 2 game time"""
+
+def check_hit() -> str | None:
+    """direction relative to the tank the last hit came from, None if not hit since last check. takes 2 game time"""
+
+def reset_hit() -> None:
+    """forget the last hit, faster than check_hit. takes 1 game time"""
+
+def send_message(text: str) -> None:
+    """radio text to living allies, it arrives after a short delay. takes 3 game time"""
+
+def receive_messages() -> list[tuple[str, str]]:
+    """(sender, text) radio messages arrived since the last call. takes 1 game time"""
+
+def time() -> int:
+    """current game time. takes 0 game time"""
+
+def rand() -> float:
+    """random number from 0 to 1, the same sequence for the same battle seed. takes no game time"""
@@ -4,31 +4,101 @@ use std::collections::VecDeque;
 use std::hash::{DefaultHasher, Hash, Hasher};
 use std::marker::PhantomData;
 use std::mem;
-use std::sync::mpsc::TryRecvError;
 use std::thread;
 use std::time::{self, Duration, Instant};
-use std::{cell::RefCell, rc::Rc, sync::mpsc};
+use std::{cell::{Cell, RefCell}, rc::Rc, sync::mpsc};
 
 use crate::battle_state_info::BattleStateInfo;
 
 use super::battle_logic::BattleLogic;
 use super::command_and_reply::CommandReplyStat;
 use super::gametime::GameTime;
-use super::log_data::{LogRepresentable, LogWriter, MaybeLogRepresentable};
+use super::log_data::{BattleLogAction, LogRepresentable, LogWriter, ToLogCommand};
 
 use super::player_state::PlayerControl;
+use super::program_stopper::{ProgramStopReceiver, ProgramStopper};
 use super::script_repr::ToScriptRepr;
+use super::think_budget::{
+    compile_with_steps, StepBudget, ThinkLimit, STEP_FUNCTION_NAME, UNCOUNTED_CODE_BUILTINS,
+};
 
 use rand::distributions::Uniform;
 use rand::prelude::*;
 
 use rustpython_vm::Settings;
+use rustpython_vm::builtins::{PyStr, PyStrRef, PyType};
+use rustpython_vm::function::PySetterValue;
+use rustpython_vm::types::{PyTypeFlags, SetAttr};
 use rustpython_vm::{
-    compiler,
-    signal::{user_signal_channel, UserSignalReceiver},
-    Interpreter, PyResult,
+    compiler, Interpreter, Py, PyObject, PyObjectRef, PyResult, VirtualMachine,
 };
 
+/// how a player program ended
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProgramExit {
+    /// program ran to its end
+    Finished,
+    /// program raised an exception on its own, with traceback
+    Errored(String),
+    /// program failed to compile
+    CompileError(String),
+    /// program took too long or too many think steps without issuing a command
+    ThinkTimeout,
+    /// program was stopped by the battle: game ended or player died
+    Stopped,
+}
+
+// error messages in the log are cut to this many characters
+const MAX_EXIT_MESSAGE_LENGTH: usize = 160;
+
+impl ProgramExit {
+    /// exit reason as logged: finished, error, compile-error, think-timeout or stopped
+    pub fn log_reason(&self) -> &'static str {
+        match self {
+            ProgramExit::Finished => "finished",
+            ProgramExit::Errored(_) => "error",
+            ProgramExit::CompileError(_) => "compile-error",
+            ProgramExit::ThinkTimeout => "think-timeout",
+            ProgramExit::Stopped => "stopped",
+        }
+    }
+
+    /// last line of the error as logged, cut and without control characters
+    pub fn log_message(&self) -> Option<String> {
+        let message = match self {
+            ProgramExit::Errored(e) | ProgramExit::CompileError(e) => e,
+            _ => return None,
+        };
+        let line = message.lines().rev().find(|x| !x.trim().is_empty())?;
+        Some(
+            line.trim()
+                .chars()
+                .take(MAX_EXIT_MESSAGE_LENGTH)
+                .map(|c| if c.is_control() { '_' } else { c })
+                .collect(),
+        )
+    }
+}
+
+impl LogRepresentable for ProgramExit {
+    /// "exit[reason]", or "exit[reason,message]" with the last line of the error
+    fn log_repr(&self) -> String {
+        match self.log_message() {
+            Some(line) => format!("exit[{},{}]", self.log_reason(), line),
+            None => format!("exit[{}]", self.log_reason()),
+        }
+    }
+}
+
+/// how a battle ended
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BattleResult {
+    /// indices of winner players, None if simulation ended in an error
+    pub winners: Option<Vec<usize>>,
+    /// how each player program ended, None if its thread panicked
+    pub program_exits: Vec<Option<ProgramExit>>,
+}
+
 #[derive(Clone, Copy, PartialEq)]
 pub enum PlayerCommandState<PC, PR> {
     None,
@@ -39,16 +109,52 @@ pub enum PlayerCommandState<PC, PR> {
     Finish,
 }
 
+/// called with player index, command, its reply, game time the command was issued at
+/// and game time the reply is delivered at
+pub type CommandEcho<PCom, PComRep> =
+    Box<dyn FnMut(usize, &PCom, &PComRep, GameTime, GameTime) + Send>;
+
+/// player index and its command with think steps taken before it,
+/// or Err if player thread is done and will not send anything more,
+/// with how its program ended, None if the thread panicked
+type PlayerMessage<PCom> = (usize, Result<(PCom, u64), Option<ProgramExit>>);
+
+/// all players send their commands into one channel, so main loop can block on it
+/// instead of polling every player.
+/// dropping the sender tells main loop that the player is done
+struct PlayerCommandSender<PCom> {
+    player_i: usize,
+    sender: mpsc::Sender<PlayerMessage<PCom>>,
+    program_exit: Option<ProgramExit>,
+}
+
+impl<PCom> PlayerCommandSender<PCom> {
+    fn send(&self, command: (PCom, u64)) -> Result<(), mpsc::SendError<PlayerMessage<PCom>>> {
+        self.sender.send((self.player_i, Ok(command)))
+    }
+}
+
+impl<PCom> Drop for PlayerCommandSender<PCom> {
+    fn drop(&mut self) {
+        let _ = self
+            .sender
+            .send((self.player_i, Err(self.program_exit.take())));
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct GameEventItem<GameEvent> {
     time: GameTime,
+    // events of the same time are processed in the order they were scheduled,
+    // so the log does not depend on heap internals however many events pile up
+    seq: u64,
     event: GameEvent,
 }
 
-// note: all comparisons are ONLY based on time, this is for the heap
+// note: all comparisons are ONLY based on time and seq, this is for the heap
 impl<GameEvent> PartialEq for GameEventItem<GameEvent> {
     fn eq(&self, other: &Self) -> bool {
-        self.time == other.time
+        self.time == other.time && self.seq == other.seq
     }
 }
 
@@ -64,7 +170,10 @@ impl<GameEvent> Ord for GameEventItem<GameEvent> {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
         // reverse comparison to make max-heap into a min-heap
         // so now event item comparison sorta shows their relative priority
-        other.time.cmp(&self.time)
+        other
+            .time
+            .cmp(&self.time)
+            .then_with(|| other.seq.cmp(&self.seq))
     }
 }
 
@@ -82,50 +191,117 @@ impl<PC, PR> PlayerCommandState<PC, PR> {
     }
 }
 
-pub struct Battle<P, BLogic, PCom, PComRep, GameEvent, LW>
+pub struct Battle<P, BLogic, PCom, PComRep, GameEvent, LW, LA>
 where
     P: PlayerControl + LogRepresentable,
-    PCom: MaybeLogRepresentable,
-    BLogic: BattleLogic<P, PCom, PComRep, GameEvent, String, String>,
-    LW: LogWriter<String, String>,
+    PCom: ToLogCommand,
+    BLogic: BattleLogic<P, PCom, PComRep, GameEvent, String, LA>,
+    LW: LogWriter<String, LA>,
+    LA: BattleLogAction,
 {
     player_states: Vec<P>,
     player_programs: Vec<String>,
+    program_names: Vec<String>,
     battle_logic: BLogic,
     time: GameTime,
     log_writer: LW,
     player_death_logged: Vec<bool>,
     next_command_id: usize, // each player command will get a unique id for logging
-    _marker: PhantomData<(PCom, PComRep, GameEvent)>,
+    think_limit: ThinkLimit,
+    seed: Option<u64>,
+    program_exits: Vec<Option<ProgramExit>>,
+    command_echo: Option<CommandEcho<PCom, PComRep>>,
+    _marker: PhantomData<(PCom, PComRep, GameEvent, LA)>,
 }
 
 pub const DEFAULT_COMMAND_DURATION: GameTime = 10;
 pub const VM_THINK_TIMEOUT: time::Duration = time::Duration::from_secs(5);
+// how often a program that ignores being stopped is reminded of it
+const STOP_NUDGE_INTERVAL: Duration = Duration::from_millis(1);
+// file name player program errors refer to, unless set with set_program_names
+const DEFAULT_PROGRAM_NAME: &str = "<embedded>";
+
+/// think step counter of the player program running in current thread
+struct StepCounter {
+    think_steps: Rc<Cell<u64>>,
+    max_think_steps: u64,
+    program_stopper: ProgramStopper,
+}
+
+thread_local! {
+    static STEP_COUNTER: RefCell<Option<StepCounter>> = const { RefCell::new(None) };
+}
+
+/// getter of None.STEP_FUNCTION_NAME, instrumented program reads it on every step
+fn count_step(_: PyObjectRef, vm: &VirtualMachine) -> PyResult<bool> {
+    STEP_COUNTER.with_borrow(|counter| {
+        let Some(counter) = counter else {
+            return PyResult::Ok(true);
+        };
+        if counter.program_stopper.is_stop_requested() {
+            return PyResult::Err(ProgramStopper::stop_error(vm));
+        }
+        let steps = counter.think_steps.get() + 1;
+        counter.think_steps.set(steps);
+        if steps > counter.max_think_steps {
+            return PyResult::Err(vm.new_runtime_error("think step budget exhausted".to_owned()));
+        }
+        PyResult::Ok(true)
+    })
+}
 
-impl<P, BLogic, PCom, PComRep, GameEvent, LW> Battle<P, BLogic, PCom, PComRep, GameEvent, LW>
+/// setattr of types that refuses to change builtin types, as cpython does, but rustpython does not.
+/// step counter lives on a builtin type, programs must not be able to replace it
+fn strict_type_setattro(
+    obj: &PyObject,
+    name: &Py<PyStr>,
+    value: PySetterValue,
+    vm: &VirtualMachine,
+) -> PyResult<()> {
+    let Some(typ) = obj.downcast_ref::<PyType>() else {
+        return PyResult::Err(vm.new_type_error("unexpected payload for __setattr__".to_owned()));
+    };
+    if !typ.slots.flags.has_feature(PyTypeFlags::HEAPTYPE) {
+        return PyResult::Err(vm.new_type_error(format!(
+            "cannot set '{}' attribute of immutable type '{}'",
+            name,
+            typ.name()
+        )));
+    }
+    PyType::setattro(typ, name, value, vm)
+}
+
+impl<P, BLogic, PCom, PComRep, GameEvent, LW, LA>
+    Battle<P, BLogic, PCom, PComRep, GameEvent, LW, LA>
 where
     P: PlayerControl + ToScriptRepr + LogRepresentable,
-    BLogic: BattleLogic<P, PCom, PComRep, GameEvent, String, String>,
-    PCom: MaybeLogRepresentable + Hash + Clone + PartialEq + Eq + Send + 'static,
+    BLogic: BattleLogic<P, PCom, PComRep, GameEvent, String, LA>,
+    PCom: ToLogCommand + Hash + Clone + PartialEq + Eq + Send + 'static,
     PComRep: CommandReplyStat + Clone + PartialEq + Send + 'static,
-    LW: LogWriter<String, String>,
+    LW: LogWriter<String, LA>,
+    LA: BattleLogAction,
     GameEvent: Clone + PartialEq,
 {
     pub fn new(
         battle_logic: BLogic,
         player_initial_states_and_programs: Vec<(P, String)>,
         log_writer: LW,
-    ) -> Battle<P, BLogic, PCom, PComRep, GameEvent, LW> {
+    ) -> Battle<P, BLogic, PCom, PComRep, GameEvent, LW, LA> {
         let (player_states, player_programs): (Vec<P>, Vec<String>) =
             player_initial_states_and_programs.into_iter().unzip();
         Battle {
             player_death_logged: vec![false; player_states.len()],
+            program_names: vec![DEFAULT_PROGRAM_NAME.to_owned(); player_programs.len()],
             player_states,
             player_programs,
             battle_logic,
             log_writer,
             time: 0,
             next_command_id: 0,
+            think_limit: ThinkLimit::WallClock(VM_THINK_TIMEOUT),
+            seed: None,
+            program_exits: Vec::new(),
+            command_echo: None,
             _marker: PhantomData,
         }
     }
@@ -146,54 +322,112 @@ where
         &self.log_writer
     }
 
-    /// returns indices of winner players
-    /// if sim ended in an error - there are no winners, so None is returned
-    pub fn run_simulation(&mut self) -> Option<Vec<usize>> {
+    /// by default players are limited by wall clock think time (VM_THINK_TIMEOUT),
+    /// use ThinkLimit::Steps for reproducible battles
+    pub fn set_think_limit(&mut self, think_limit: ThinkLimit) {
+        self.think_limit = think_limit;
+    }
+
+    /// rand() of player programs is seeded by program text only by default,
+    /// setting a seed mixes it in, so same programs roll differently in different battles
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+    }
+
+    /// file names program errors and tracebacks refer to, in player order.
+    /// players without a name keep "<embedded>"
+    pub fn set_program_names(&mut self, names: Vec<String>) {
+        for (program_name, name) in self.program_names.iter_mut().zip(names) {
+            *program_name = name;
+        }
+    }
+
+    /// call echo every time a command issued by a player program gets its reply
+    pub fn set_command_echo(&mut self, echo: CommandEcho<PCom, PComRep>) {
+        self.command_echo = Some(echo);
+    }
+
+    /// how each player program ended in the last simulation,
+    /// None if its thread panicked
+    pub fn program_exits(&self) -> &[Option<ProgramExit>] {
+        &self.program_exits
+    }
+
+    /// returns indices of winner players and how every player program ended.
+    /// if sim ended in an error - there are no winners, so winners are None.
+    /// program ends are also logged as "exit[reason]" at the time player was done
+    pub fn run_simulation(&mut self) -> BattleResult {
         self.run_simulation_with_time_limit(None)
     }
 
     pub fn run_simulation_with_time_limit(
         &mut self,
         game_time_limit: Option<GameTime>,
-    ) -> Option<Vec<usize>> {
+    ) -> BattleResult {
         self.time = 0;
         let player_count = self.player_programs.len();
         let mut winner_ids = None;
 
         thread::scope(|scope| {
             let mut handles = Vec::with_capacity(player_count);
-            let mut channels = Vec::with_capacity(player_count);
-            let mut thread_stop_signal_senders = Vec::with_capacity(player_count);
+            let mut reply_channels = Vec::with_capacity(player_count);
+            let (command_sender, command_receiver) = mpsc::channel();
+            let mut program_stoppers = Vec::with_capacity(player_count);
             let mut player_extra_commands_queues = vec![VecDeque::new(); player_count];
             let mut thread_ready_chans = Vec::with_capacity(player_count);
+            let step_budget = if let ThinkLimit::Steps(budget) = self.think_limit {
+                Some(budget)
+            } else {
+                None
+            };
+            let think_timeout = match self.think_limit {
+                ThinkLimit::WallClock(timeout) => Some(timeout),
+                ThinkLimit::Steps(budget) => budget.hard_timeout,
+            };
 
-            for program in self.player_programs.iter() {
-                let (command_sender, command_receiver) = mpsc::channel();
+            for (player_i, (program, program_name)) in self
+                .player_programs
+                .iter()
+                .zip(self.program_names.iter())
+                .enumerate()
+            {
+                let command_sender = PlayerCommandSender {
+                    player_i,
+                    sender: command_sender.clone(),
+                    program_exit: None,
+                };
                 let (result_sender, result_receiver) = mpsc::channel();
-                let (thread_stop_sender, thread_stop_receiver) = user_signal_channel();
+                let (program_stopper, program_stop_receiver) = ProgramStopper::channel();
                 let (thead_ready_tx, thread_ready_rx) = mpsc::channel();
 
                 let handle = scope.spawn({
                     let program = program.clone();
+                    let program_name = program_name.clone();
                     let mut program_hasher = DefaultHasher::new();
+                    if let Some(seed) = self.seed {
+                        program_hasher.write_u64(seed);
+                    }
                     program_hasher.write(program.as_bytes());
                     let program_hash = program_hasher.finish();
                     move || {
                         Self::program_runner(
-                            program,
+                            (program, program_name),
                             command_sender,
                             result_receiver,
-                            thread_stop_receiver,
+                            program_stop_receiver,
                             thead_ready_tx,
                             program_hash, //88284664
+                            step_budget,
                         )
                     }
                 });
                 handles.push(Some(handle));
-                channels.push(Some((command_receiver, result_sender)));
-                thread_stop_signal_senders.push(Some(thread_stop_sender));
+                reply_channels.push(Some(result_sender));
+                program_stoppers.push(program_stopper);
                 thread_ready_chans.push(thread_ready_rx);
             }
+            drop(command_sender); // only player threads hold senders now
+
             // wait for all threads to initialize
             for thread_ready_rx in thread_ready_chans {
                 if let Err(_) = thread_ready_rx.recv() {
@@ -205,6 +439,21 @@ where
                 vec![PlayerCommandState::None; player_count];
 
             let mut next_events_queue: BinaryHeap<GameEventItem<GameEvent>> = BinaryHeap::new();
+            let mut next_event_seq: u64 = 0;
+
+            // think steps taken by player programs that are not yet paid for with game time
+            let mut unpaid_think_steps = vec![0_u64; player_count];
+            // program commands held back till think overrun penalty is served
+            let mut held_program_commands: Vec<Option<PCom>> = vec![None; player_count];
+            let mut think_overruns_to_log: Vec<Option<u64>> = vec![None; player_count];
+            // commands received from player programs, but not yet taken by the main loop
+            let mut received_commands: Vec<Option<(PCom, u64)>> = vec![None; player_count];
+            let mut players_disconnected = vec![false; player_count];
+            // game time current command of each player was issued at
+            let mut command_issue_times = vec![0; player_count];
+            // how each player program ended, decided here when player is marked Finish,
+            // so it does not depend on thread timing
+            let mut program_exits: Vec<Option<ProgramExit>> = vec![None; player_count];
 
             // initial logic setup
             self.battle_logic
@@ -229,9 +478,12 @@ where
                     if self.battle_logic.is_player_dead(player) && !*death_logged {
                         // TODO: remove is_dead from player - game logic is responsible for that info
                         next_commands[i] = PlayerCommandState::Finish;
+                        if !players_disconnected[i] {
+                            program_exits[i] = Some(ProgramExit::Stopped);
+                        }
                         self.log_writer.add_log_data(
                             self.player_states[i].log_repr(),
-                            "die".to_owned(),
+                            LA::die(),
                             self.time,
                             0,
                         );
@@ -247,7 +499,7 @@ where
                         for winner_id in winner_ids.as_ref().unwrap() {
                             self.log_writer.add_log_data(
                                 self.player_states[*winner_id].log_repr(),
-                                "win".to_owned(),
+                                LA::win(),
                                 self.time,
                                 0,
                             );
@@ -258,30 +510,19 @@ where
                         }
                     }
                 }
-                // if game is ended - we allow pending commands to finalize and enforce Finish state
-                if let Some(_) = winner_ids {
-                    for next_command in next_commands.iter_mut() {
-                        // just finalize all players
-                        // this will force their stopping and loop safe exit
-                        if let PlayerCommandState::None = next_command {
-                            *next_command = PlayerCommandState::Finish;
-                        }
-                        // we should let pending commands finalize
-                    }
-                }
+                // if game is ended - we allow pending commands to finalize,
+                // players waiting for their program are finalized below right away
 
-                for (i, (channels_maybe, extra_commands_queue)) in channels
+                for (i, (reply_channel_maybe, extra_commands_queue)) in reply_channels
                     .iter()
                     .zip(player_extra_commands_queues.iter_mut())
                     .enumerate()
                 {
-                    let (command_receiver, _) = if let Some(x) = channels_maybe {
-                        x
-                    } else {
+                    if reply_channel_maybe.is_none() {
                         next_commands[i] = PlayerCommandState::Finish;
                         players_that_have_commands += 1;
                         continue;
-                    };
+                    }
                     // ignore finished ones and set ones. If their channels are not closed yet - without this check they might get a new command.
                     if let PlayerCommandState::None = next_commands[i] {
                     } else {
@@ -290,6 +531,15 @@ where
                         continue;
                     }
 
+                    if winner_ids.is_some() {
+                        // game is over, whatever the program is doing - it is stopped.
+                        // a program that ended in the same tick is stopped too,
+                        // as whether its thread got to report that depends on timing
+                        next_commands[i] = PlayerCommandState::Finish;
+                        program_exits[i] = Some(ProgramExit::Stopped);
+                        players_that_have_commands += 1;
+                        continue;
+                    }
                     // first check if there are extra commands queues
                     if extra_commands_queue.len() > 0 {
                         let com = extra_commands_queue.pop_front().unwrap();
@@ -298,70 +548,99 @@ where
                         players_that_have_commands += 1;
                         continue;
                     }
+                    // then command held back by think overrun penalty
+                    if let Some(com) = held_program_commands[i].take() {
+                        next_commands[i] = PlayerCommandState::GotCommandQueued(com, true, self.time);
+                        players_that_have_commands += 1;
+                        continue;
+                    }
                     // then get new command from the program
-                    match command_receiver.try_recv() {
-                        Ok(com) => {
+                    match received_commands[i].take() {
+                        Some((com, think_steps)) => {
+                            players_that_have_commands += 1;
+                            if let Some(budget) = step_budget {
+                                unpaid_think_steps[i] += think_steps;
+                                if unpaid_think_steps[i] > budget.max_steps {
+                                    next_commands[i] = PlayerCommandState::Finish;
+                                    program_exits[i] = Some(ProgramExit::ThinkTimeout);
+                                    continue;
+                                }
+                                // commands that take no game time do not pay for thinking,
+                                // otherwise free commands could be used to think for free
+                                let player_state = &self.player_states[i];
+                                if self.battle_logic.get_command_duration(player_state, &com)
+                                    + self.battle_logic.get_command_reply_delay(player_state, &com)
+                                    > 0
+                                {
+                                    let steps = mem::take(&mut unpaid_think_steps[i]);
+                                    let penalty_units = budget.penalty_units(steps);
+                                    if penalty_units > 0 {
+                                        if let Some(penalty) = self
+                                            .battle_logic
+                                            .think_overrun_penalty(player_state, penalty_units)
+                                        {
+                                            extra_commands_queue.extend(penalty);
+                                        }
+                                        think_overruns_to_log[i] = Some(steps);
+                                    }
+                                }
+                                if let Some(penalty_com) = extra_commands_queue.pop_front() {
+                                    // program's command waits till penalty is served
+                                    held_program_commands[i] = Some(com);
+                                    next_commands[i] = PlayerCommandState::GotCommandQueued(
+                                        penalty_com,
+                                        false,
+                                        self.time,
+                                    );
+                                    continue;
+                                }
+                            }
                             next_commands[i] =
                                 PlayerCommandState::GotCommandQueued(com, true, self.time);
-                            players_that_have_commands += 1;
                             continue;
                         }
-                        Err(TryRecvError::Disconnected) => {
+                        None if players_disconnected[i] => {
+                            // program_exits already has what the program reported
                             next_commands[i] = PlayerCommandState::Finish;
                             players_that_have_commands += 1;
                             continue;
                         }
-                        _ => (),
+                        None => (),
                     }
                     // so we are still waiting for a command
                     // check for timeout
-                    if time::Instant::now() - start_timestamps[i] > VM_THINK_TIMEOUT {
-                        next_commands[i] = PlayerCommandState::Finish;
-                        players_that_have_commands += 1;
+                    if let Some(timeout) = think_timeout {
+                        if time::Instant::now() - start_timestamps[i] > timeout {
+                            next_commands[i] = PlayerCommandState::Finish;
+                            program_exits[i] = Some(ProgramExit::ThinkTimeout);
+                            players_that_have_commands += 1;
+                        }
                     }
-                    thread::sleep(Duration::from_micros(1));
                 }
                 let players_that_have_commands = players_that_have_commands; // remove mut
 
-                // ensure closed channels for Finished players
-                for (i, ((next_command, channel), thread_stop_signal_sender)) in next_commands
+                // ensure closed channels and stopped programs for Finished players
+                for (i, (next_command, channel)) in next_commands
                     .iter()
-                    .zip(channels.iter_mut())
-                    .zip(thread_stop_signal_senders.iter_mut())
+                    .zip(reply_channels.iter_mut())
                     .enumerate()
                 {
-                    match (next_command, &channel) {
-                        (PlayerCommandState::Finish, Some(_)) => {
-                            channel.take();
-                            // need to kill thread...
-                            if let Some(chan) = thread_stop_signal_sender.take() {
-                                // spray and pray
-                                // in case there are many generic try-except clauses - we just send a shit ton of exceptions to except from exception handlers
-                                for k in 0..999999 {
-                                    match chan.send(Box::new(|vm| {
-                                        Err(vm.new_runtime_error("program stopped".to_owned()))
-                                    })) {
-                                        Ok(_) => {
-                                            if k % 10 == 0 {
-                                                println!(
-                                                    "(attempt:{}) trying to stop the vm {} ...",
-                                                    k, i
-                                                )
-                                            };
-                                            thread::sleep(Duration::from_nanos(1));
-                                        }
-                                        Err(_) => {
-                                            println!(
-                                                "closing vm failed: probably vm {} already stopped",
-                                                i
-                                            );
-                                            break;
-                                        }
-                                    };
-                                }
-                            }
+                    if let (PlayerCommandState::Finish, Some(_)) = (next_command, &channel) {
+                        channel.take();
+                        program_stoppers[i].request_stop();
+                        if let Some(program_exit) = &program_exits[i] {
+                            self.log_writer.add_log_data(
+                                self.player_states[i].log_repr(),
+                                LA::exit(program_exit),
+                                self.time,
+                                0,
+                            );
                         }
-                        _ => {}
+                    }
+                }
+                for (program_stopper, handle) in program_stoppers.iter().zip(handles.iter()) {
+                    if handle.as_ref().is_some_and(|x| !x.is_finished()) {
+                        program_stopper.nudge();
                     }
                 }
 
@@ -394,8 +673,18 @@ where
                             if let PlayerCommandState::GotCommandQueued(com, need_to_reply, time) =
                                 taken_command
                             {
+                                if let Some(steps) = think_overruns_to_log[player_i].take() {
+                                    self.log_writer.add_log_data(
+                                        player_state.log_repr(),
+                                        LA::think_overrun(steps),
+                                        time,
+                                        0,
+                                    );
+                                }
+
                                 let command_id = self.next_command_id;
                                 self.next_command_id += 1;
+                                command_issue_times[player_i] = time;
 
                                 let duration =
                                     self.battle_logic.get_command_duration(player_state, &com);
@@ -403,10 +692,10 @@ where
                                     .battle_logic
                                     .get_command_reply_delay(player_state, &com);
 
-                                if let Some(log_repr) = com.try_log_repr() {
+                                if let Some(log_command) = com.to_log_command() {
                                     self.log_writer.add_log_data(
                                         player_state.log_repr(),
-                                        format!("-{}({})", log_repr, command_id),
+                                        LA::command_start(log_command, command_id),
                                         time,
                                         duration + reply_delay_duration, // log full command time
                                     );
@@ -489,9 +778,13 @@ where
                                 },
                             ) {
                                 next_events_queue.extend(extra_events.into_iter().map(
-                                    |(time_delta, event)| GameEventItem {
-                                        time: self.time + time_delta,
-                                        event,
+                                    |(time_delta, event)| {
+                                        next_event_seq += 1;
+                                        GameEventItem {
+                                            time: self.time + time_delta,
+                                            seq: next_event_seq,
+                                            event,
+                                        }
                                     },
                                 ));
                             };
@@ -530,9 +823,13 @@ where
                                     }
                                     if let Some(extra_events) = extra_events_maybe {
                                         next_events_queue.extend(extra_events.into_iter().map(
-                                            |(time_delta, event)| GameEventItem {
-                                                time: self.time + time_delta,
-                                                event,
+                                            |(time_delta, event)| {
+                                                next_event_seq += 1;
+                                                GameEventItem {
+                                                    time: self.time + time_delta,
+                                                    seq: next_event_seq,
+                                                    event,
+                                                }
                                             },
                                         ));
                                     }
@@ -574,15 +871,25 @@ where
                                     //
                                     
                                     // now the actual replying
-                                    let reply_channel = &channels[player_i].as_ref().unwrap().1;
+                                    let reply_channel = reply_channels[player_i].as_ref().unwrap();
 
                                     let command_succeeded = reply.command_succeeded();
                                     // send reply
                                     if need_to_reply {
+                                        if let Some(echo) = self.command_echo.as_mut() {
+                                            echo(
+                                                player_i,
+                                                &com,
+                                                &reply,
+                                                command_issue_times[player_i],
+                                                self.time,
+                                            );
+                                        }
                                         if let Err(_) = reply_channel.send(reply) {
-                                            println!("failed to send reply to the player");
-                                            // consider player broken
+                                            // player thread is gone, consider player broken,
+                                            // this is recorded as its exit instead of printed
                                             *next_command = PlayerCommandState::Finish;
+                                            program_exits[player_i] = Some(ProgramExit::Stopped);
                                             continue;
                                         }
                                     }
@@ -590,14 +897,13 @@ where
                                     start_timestamps[player_i] = Instant::now(); // update timeout counter
 
                                     // log operation finish
-                                    if let Some(log_repr) = com.try_log_repr() {
+                                    if let Some(log_command) = com.to_log_command() {
                                         self.log_writer.add_log_data(
                                             self.player_states[player_i].log_repr(),
-                                            format!(
-                                                "{}{}({})",
-                                                if command_succeeded { "+" } else { "!" },
-                                                log_repr,
+                                            LA::command_finish(
+                                                log_command,
                                                 command_id, // at this point command_id is guaranteed not to be None
+                                                command_succeeded,
                                             ),
                                             self.time,
                                             0,
@@ -611,30 +917,57 @@ where
                         // no min - means all commands are Finish, but that must have been checked before, so
                         unreachable!("should not be reached");
                     };
+                } else {
+                    // someone is still thinking - sleep till a player sends something
+                    // or till the earliest think timeout of those we wait for
+                    let deadline = think_timeout.and_then(|timeout| {
+                        next_commands
+                            .iter()
+                            .zip(start_timestamps.iter())
+                            .filter(|(x, _)| matches!(x, PlayerCommandState::None))
+                            .map(|(_, start)| *start + timeout)
+                            .min()
+                    });
+                    let message = if let Some(deadline) = deadline {
+                        command_receiver
+                            .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                            .ok()
+                    } else {
+                        command_receiver.recv().ok()
+                    };
+                    for (player_i, message) in message.into_iter().chain(command_receiver.try_iter()) {
+                        match message {
+                            Ok(command) => received_commands[player_i] = Some(command),
+                            Err(program_exit) => {
+                                players_disconnected[player_i] = true;
+                                if !matches!(next_commands[player_i], PlayerCommandState::Finish) {
+                                    program_exits[player_i] = program_exit;
+                                }
+                            }
+                        }
+                    }
                 }
             } // inf loop end
 
-            for handle in handles.into_iter() {
-                let handle = if let Some(h) = handle {
-                    h
-                } else {
-                    continue;
-                };
-
-                match handle.join() {
-                    Ok(Ok(_)) => {
-                        println!("program finished fine");
-                    }
-                    Ok(Err(e)) => {
-                        println!("program errored out: {}", e);
-                    }
-                    Err(_) => {
-                        println!("something went funky with the thread!");
+            // battle is over, but stopped programs may still be unwinding
+            for program_stopper in program_stoppers.iter() {
+                program_stopper.request_stop();
+            }
+            for (handle, program_stopper) in handles.into_iter().zip(program_stoppers.iter()) {
+                if let Some(handle) = handle {
+                    while !handle.is_finished() {
+                        program_stopper.nudge();
+                        thread::sleep(STOP_NUDGE_INTERVAL);
                     }
+                    let _ = handle.join();
                 }
             }
+            self.program_exits = program_exits;
         });
-        winner_ids
+        BattleResult {
+            winners: winner_ids,
+            program_exits: self.program_exits.clone(),
+        }
     }
 
     ///
@@ -642,17 +975,29 @@ where
     /// this represents a single tank AI,
     /// and runs a python interpreter with player ai code
     ///
-    /// returns success or error if code produced an exception
+    /// returns how the program ended
     fn program_runner(
-        program: String,
-        command_channel: mpsc::Sender<PCom>,
+        (program, program_name): (String, String), // source and file name errors refer to
+        command_channel: PlayerCommandSender<PCom>, // command and think steps taken before it
         reply_channel: mpsc::Receiver<PComRep>, // PlayerCommandReply<(String, Option<String>)>
-        vm_signal_receiver: UserSignalReceiver,
+        program_stop_receiver: ProgramStopReceiver,
         thread_ready_signal: mpsc::Sender<()>,
         seed: u64,
-    ) -> Result<(), String> {
+        step_budget: Option<StepBudget>,
+    ) -> ProgramExit {
         macro_rules! send_command {
-            ($vm:ident, $command_channel:ident, $reply_channel:ident, $cmd:expr) => {{
+            (
+                $vm:ident,
+                $program_stopper:ident,
+                $command_channel:ident,
+                $reply_channel:ident,
+                $think_steps:ident,
+                $max_think_steps:ident,
+                $cmd:expr
+            ) => {{
+                if $program_stopper.is_stop_requested() {
+                    return Err(())
+                }
                 let command_channel = if let Some(x) = $command_channel.upgrade() {
                     x
                 } else {
@@ -666,10 +1011,16 @@ where
                     //return PyResult::Err($vm.new_runtime_error("game is closed!".to_owned()));
                 };
 
-                if let Err(_) = command_channel.borrow().send($cmd) {
+                let think_steps = $think_steps.get();
+                if think_steps > $max_think_steps {
+                    // program is out of budget, it must not be able to continue
+                    return Err(())
+                }
+                if let Err(_) = command_channel.borrow().send(($cmd, think_steps)) {
                     return Err(())
                     //return PyResult::Err($vm.new_runtime_error("game is closed!".to_owned()));
                 };
+                $think_steps.set(0);
 
                 let ret = match reply_channel.borrow().recv() {
                     Ok(x) => Ok(x),
@@ -684,6 +1035,13 @@ where
 
         let reply_channel = Rc::new(RefCell::new(reply_channel));
         let command_channel = Rc::new(RefCell::new(command_channel));
+        let think_steps = Rc::new(Cell::new(0_u64));
+        let max_think_steps = step_budget.map_or(u64::MAX, |x| x.max_steps);
+
+        let ProgramStopReceiver {
+            stopper: program_stopper,
+            signal_receiver: vm_signal_receiver,
+        } = program_stop_receiver;
 
         let mut vm_settings: Settings = Default::default();
         vm_settings.install_signal_handlers = false;
@@ -708,11 +1066,65 @@ where
                 )
                 .unwrap();
 
+            if step_budget.is_some() {
+                // instrumented program reads this on every step. it is read only,
+                // and types are per thread, each player program has its own thread
+                let type_type = vm.ctx.types.type_type;
+                type_type.slots.setattro.store(Some(strict_type_setattro));
+                type_type.set_attr(
+                    vm.ctx.intern_str("__setattr__"),
+                    vm.new_function(
+                        "__setattr__",
+                        |obj: PyObjectRef,
+                         name: PyStrRef,
+                         value: PyObjectRef,
+                         vm: &VirtualMachine| {
+                            strict_type_setattro(&obj, &name, PySetterValue::Assign(value), vm)
+                        },
+                    )
+                    .into(),
+                );
+                type_type.set_attr(
+                    vm.ctx.intern_str("__delattr__"),
+                    vm.new_function(
+                        "__delattr__",
+                        |obj: PyObjectRef, name: PyStrRef, vm: &VirtualMachine| {
+                            strict_type_setattro(&obj, &name, PySetterValue::Delete, vm)
+                        },
+                    )
+                    .into(),
+                );
+                STEP_COUNTER.set(Some(StepCounter {
+                    think_steps: think_steps.clone(),
+                    max_think_steps,
+                    program_stopper: program_stopper.clone(),
+                }));
+                vm.ctx.types.none_type.set_attr(
+                    vm.ctx.intern_str(STEP_FUNCTION_NAME),
+                    vm.ctx
+                        .new_readonly_getset(STEP_FUNCTION_NAME, vm.ctx.types.none_type, count_step)
+                        .into(),
+                );
+                for name in UNCOUNTED_CODE_BUILTINS {
+                    vm.builtins.dict().del_item(name, vm).unwrap();
+                }
+            }
+
             BLogic::initialize_scope(vm, &scope, {
                 let reply_channel = Rc::downgrade(&reply_channel);
                 let command_channel = Rc::downgrade(&command_channel);
+                let think_steps = think_steps.clone();
+                let program_stopper = program_stopper.clone();
                 move |com: PCom| -> Result<PComRep, ()> {
-                    send_command!(vm, command_channel, reply_channel, com)
+                    send_command!(
+                        vm,
+                        program_stopper,
+                        command_channel,
+                        reply_channel,
+                        think_steps,
+                        max_think_steps,
+                        com
+                    )
                 }
             });
 
@@ -721,27 +1133,39 @@ where
             thread_ready_signal.send(()).unwrap();
             drop(thread_ready_signal);
 
-            let code_obj = match vm.compile(&program, compiler::Mode::Exec, "<embedded>".to_owned())
-            {
+            let code_obj = if step_budget.is_some() {
+                compile_with_steps(&program, program_name.clone()).map(|x| vm.ctx.new_code(x))
+            } else {
+                vm.compile(&program, compiler::Mode::Exec, program_name.clone())
+            };
+            let code_obj = match code_obj {
                 Ok(x) => x,
                 Err(e) => {
-                    return Err(e.to_string());
+                    return ProgramExit::CompileError(format!("{}: {}", program_name, e));
                 }
             };
 
             // run player code
             if let PyResult::Err(e) = vm.run_code_obj(code_obj, scope) {
+                if think_steps.get() > max_think_steps {
+                    return ProgramExit::ThinkTimeout;
+                }
+                if program_stopper.is_stop_requested() {
+                    return ProgramExit::Stopped;
+                }
                 let mut exc_str = String::new();
                 vm.write_exception(&mut exc_str, &e).unwrap_or_else(|_| {
                     exc_str.push_str("unknown error");
                 });
-                return Err(exc_str);
+                return ProgramExit::Errored(exc_str);
             }
 
-            Ok(())
+            ProgramExit::Finished
         });
         interpreter.finalize(None);
-        println!("program runner completed");
+        STEP_COUNTER.set(None);
+        // main loop learns how the program ended when the sender is dropped
+        command_channel.borrow_mut().program_exit = Some(ret.clone());
         ret
     }
 }
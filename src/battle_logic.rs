@@ -32,7 +32,9 @@ where
         players_states: &mut [P],
         battle_info: &BattleStateInfo,
         logger: &mut LWF,
-    ) -> Option<Vec<(GameTime, GameEvent)>>; // time offset till event
+    ) -> Option<Vec<(GameTime, GameEvent)>> // time offset till event
+    where
+        LWF: FnMut(LO, LA);
 
     /// called when command is received from player,
     /// but is not yet to be processed
@@ -87,6 +89,16 @@ where
         None
     }
 
+    /// called when player program took more think steps than its budget allows
+    /// before issuing a command.
+    /// returned commands are forced onto the player before the issued command
+    fn think_overrun_penalty(&self, player_state: &P, penalty_units: u64) -> Option<Vec<PCom>> {
+        // avoid unused var warning
+        let _ = player_state;
+        let _ = penalty_units;
+        None
+    }
+
     fn get_command_duration(&self, player_state: &P, com: &PCom) -> GameTime;
     fn get_command_reply_delay(&self, player_state: &P, com: &PCom) -> GameTime;
 
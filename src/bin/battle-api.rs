@@ -0,0 +1,97 @@
+use battle_sim::r#impl::script_api::{api_to_json, api_to_markdown, api_to_pyi};
+use battle_sim::r#impl::standard_battle::describe_standard_api;
+
+use std::env::args;
+use std::fs;
+use std::io::{stdout, Error, ErrorKind, Result, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+enum ApiFormat {
+    Json,
+    Pyi,
+    Markdown,
+}
+
+struct Config {
+    format: ApiFormat,
+    out_path: Option<PathBuf>,
+}
+
+fn main() -> ExitCode {
+    let config = match parse_args() {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!("Error parsing arguments: {e}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let api = describe_standard_api();
+    let text = match config.format {
+        ApiFormat::Json => api_to_json(&api),
+        ApiFormat::Pyi => api_to_pyi(&api),
+        ApiFormat::Markdown => api_to_markdown(&api),
+    };
+
+    let result = match &config.out_path {
+        Some(path) => fs::write(path, text),
+        None => stdout().write_all(text.as_bytes()),
+    };
+    if let Err(e) = result {
+        eprintln!("failed to write api description: {}", e);
+        return ExitCode::from(1);
+    }
+
+    ExitCode::SUCCESS
+}
+
+enum ArgsState {
+    FlagOrOut,
+    Format,
+    Nothing,
+}
+
+fn parse_args() -> Result<Config> {
+    let mut config = Config {
+        format: ApiFormat::Json,
+        out_path: None,
+    };
+
+    let mut state = ArgsState::FlagOrOut;
+    for arg in args().skip(1) {
+        match state {
+            ArgsState::FlagOrOut => match arg.as_str() {
+                "-f" | "--format" => {
+                    state = ArgsState::Format;
+                }
+                path => {
+                    config.out_path = Some(PathBuf::from(path));
+                    state = ArgsState::Nothing;
+                }
+            },
+            ArgsState::Format => {
+                config.format = match arg.as_str() {
+                    "json" => ApiFormat::Json,
+                    "pyi" => ApiFormat::Pyi,
+                    "md" | "markdown" => ApiFormat::Markdown,
+                    _ => {
+                        return Err(Error::new(
+                            ErrorKind::InvalidData,
+                            "format must be one of: json, pyi, markdown",
+                        ))
+                    }
+                };
+                state = ArgsState::FlagOrOut;
+            }
+            ArgsState::Nothing => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "not expecting any more arguments",
+                ));
+            }
+        }
+    }
+
+    Ok(config)
+}
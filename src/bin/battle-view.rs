@@ -0,0 +1,283 @@
+use battle_sim::gametime::GameTime;
+use battle_sim::r#impl::ascii_render::{render_ascii, render_status};
+use battle_sim::r#impl::buf_battle_log_reader::read_log_events;
+use battle_sim::r#impl::gridmap_text_file::load_map_file;
+use battle_sim::r#impl::log_replay::LogReplay;
+use battle_sim::r#impl::replay_export::ReplayExporter;
+
+use std::env::args;
+use std::fs::File;
+use std::io::{self, BufRead, Error, ErrorKind, Result, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const HELP: &str = "\
+enter, n   next tick with events
+p          previous tick with events
++N, -N     step N ticks forward or back
+g T        go to time T
+e          go to the end
+q          quit";
+
+enum Mode {
+    Interactive,
+    At(GameTime),
+    All,
+    Export(PathBuf),
+}
+
+struct Config {
+    map_path: PathBuf,
+    log_path: PathBuf,
+    mode: Mode,
+    ticks_per_frame: GameTime,
+    cell_size: u32,
+    frame_delay_ms: u32,
+}
+
+fn main() -> ExitCode {
+    let config = match parse_args() {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!("Error parsing arguments: {e}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let map = match load_map_file(&config.map_path) {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!(
+                "failed to load map at '{}': {}",
+                config.map_path.to_string_lossy(),
+                e
+            );
+            return ExitCode::from(1);
+        }
+    };
+    let events = match File::open(&config.log_path)
+        .and_then(|file| read_log_events(io::BufReader::new(file)))
+    {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!(
+                "failed to read battle log at '{}': {}",
+                config.log_path.to_string_lossy(),
+                e
+            );
+            return ExitCode::from(1);
+        }
+    };
+    let replay = LogReplay::new(events);
+
+    let print_frame = |time: GameTime| {
+        let state = replay.state_at(time);
+        println!("time {}/{}", state.time, replay.end_time());
+        print!("{}", render_ascii(&map, &state));
+        print!("{}", render_status(&state));
+    };
+
+    let mut event_times: Vec<GameTime> = replay.events().iter().map(|x| x.time).collect();
+    event_times.dedup();
+
+    match config.mode {
+        Mode::At(time) => print_frame(time),
+        Mode::Export(path) => {
+            let mut exporter = ReplayExporter::new(&map, &replay);
+            exporter.set_ticks_per_frame(config.ticks_per_frame);
+            exporter.set_cell_size(config.cell_size);
+            exporter.set_frame_delay_ms(config.frame_delay_ms);
+            match exporter.save(&path) {
+                Ok(count) => println!("exported {} frames to '{}'", count, path.to_string_lossy()),
+                Err(e) => {
+                    eprintln!("failed to export replay: {}", e);
+                    return ExitCode::from(1);
+                }
+            }
+        }
+        Mode::All => {
+            for time in event_times {
+                print_frame(time);
+                println!();
+            }
+        }
+        Mode::Interactive => {
+            let end_time = replay.end_time();
+            let mut time = 0;
+            let mut lines = io::stdin().lock().lines();
+            loop {
+                print_frame(time);
+                print!("[h for help] > ");
+                let _ = io::stdout().flush();
+                let line = match lines.next() {
+                    Some(Ok(x)) => x,
+                    _ => break,
+                };
+                let line = line.trim();
+                time = match line {
+                    "" | "n" => event_times
+                        .iter()
+                        .copied()
+                        .find(|&x| x > time)
+                        .unwrap_or(end_time),
+                    "p" => event_times
+                        .iter()
+                        .copied()
+                        .rev()
+                        .find(|&x| x < time)
+                        .unwrap_or(0),
+                    "e" => end_time,
+                    "q" => break,
+                    "h" => {
+                        println!("{}", HELP);
+                        time
+                    }
+                    _ => {
+                        if let Some(Ok(x)) = line.strip_prefix('+').map(|x| x.parse::<GameTime>()) {
+                            (time + x).min(end_time)
+                        } else if let Some(Ok(x)) =
+                            line.strip_prefix('-').map(|x| x.parse::<GameTime>())
+                        {
+                            time.saturating_sub(x)
+                        } else if let Some(Ok(x)) =
+                            line.strip_prefix('g').map(|x| x.trim().parse::<GameTime>())
+                        {
+                            x.min(end_time)
+                        } else {
+                            println!("unknown command '{}'\n{}", line, HELP);
+                            time
+                        }
+                    }
+                };
+            }
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+enum ArgsState {
+    FlagOrMapPath,
+    Time,
+    ExportPath,
+    TicksPerFrame,
+    CellSize,
+    FrameDelay,
+    LogPath,
+    Done,
+}
+
+fn parse_args() -> Result<Config> {
+    let mut state = ArgsState::FlagOrMapPath;
+    let mut config = Config {
+        map_path: PathBuf::new(),
+        log_path: PathBuf::new(),
+        mode: Mode::Interactive,
+        ticks_per_frame: 1,
+        cell_size: 16,
+        frame_delay_ms: 100,
+    };
+
+    let args = args().skip(1);
+    for arg in args {
+        match state {
+            ArgsState::FlagOrMapPath => match arg.as_str() {
+                "-t" | "--at" => {
+                    state = ArgsState::Time;
+                    continue;
+                }
+                "-a" | "--all" => {
+                    config.mode = Mode::All;
+                    continue;
+                }
+                "-e" | "--export" => {
+                    state = ArgsState::ExportPath;
+                    continue;
+                }
+                "--ticks-per-frame" => {
+                    state = ArgsState::TicksPerFrame;
+                    continue;
+                }
+                "--cell" => {
+                    state = ArgsState::CellSize;
+                    continue;
+                }
+                "--delay" => {
+                    state = ArgsState::FrameDelay;
+                    continue;
+                }
+                arg => {
+                    config.map_path = PathBuf::from(arg);
+                    state = ArgsState::LogPath;
+                }
+            },
+            ArgsState::Time => {
+                config.mode = Mode::At(if let Ok(x) = arg.parse::<GameTime>() {
+                    x
+                } else {
+                    return Err(Error::new(ErrorKind::InvalidData, "invalid data for time"));
+                });
+                state = ArgsState::FlagOrMapPath;
+            }
+            ArgsState::ExportPath => {
+                config.mode = Mode::Export(PathBuf::from(arg));
+                state = ArgsState::FlagOrMapPath;
+            }
+            ArgsState::TicksPerFrame => {
+                config.ticks_per_frame = match arg.parse::<GameTime>() {
+                    Ok(x) if x > 0 => x,
+                    _ => {
+                        return Err(Error::new(
+                            ErrorKind::InvalidData,
+                            "ticks per frame must be a positive number",
+                        ))
+                    }
+                };
+                state = ArgsState::FlagOrMapPath;
+            }
+            ArgsState::CellSize => {
+                config.cell_size = match arg.parse::<u32>() {
+                    Ok(x) if x > 0 => x,
+                    _ => {
+                        return Err(Error::new(
+                            ErrorKind::InvalidData,
+                            "cell size must be a positive number of pixels",
+                        ))
+                    }
+                };
+                state = ArgsState::FlagOrMapPath;
+            }
+            ArgsState::FrameDelay => {
+                config.frame_delay_ms = match arg.parse::<u32>() {
+                    Ok(x) => x,
+                    Err(_) => {
+                        return Err(Error::new(
+                            ErrorKind::InvalidData,
+                            "frame delay must be a number of milliseconds",
+                        ))
+                    }
+                };
+                state = ArgsState::FlagOrMapPath;
+            }
+            ArgsState::LogPath => {
+                config.log_path = PathBuf::from(arg);
+                state = ArgsState::Done;
+            }
+            ArgsState::Done => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "not expecting any more arguments",
+                ));
+            }
+        }
+    }
+
+    if let ArgsState::Done = state {
+        Ok(config)
+    } else {
+        Err(Error::new(
+            ErrorKind::InvalidData,
+            "not all arguments provided",
+        ))
+    }
+}
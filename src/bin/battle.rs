@@ -1,75 +1,46 @@
+use battle_sim::battle::ProgramExit;
 use battle_sim::gametime::GameTime;
-use battle_sim::map::MapReadAccess;
-use battle_sim::maptile_logic::MaptileLogic;
-use battle_sim::object_layer::ObjectLayer;
+use battle_sim::log_data::LogWriter;
+use battle_sim::r#impl::buf_battle_log_reader::read_log_records;
 use battle_sim::r#impl::buf_battle_logger::BufferLogWriter;
-use battle_sim::r#impl::grid_battle::{new_player, GridBattle};
+use battle_sim::r#impl::dry_run::{
+    command_echo_line, empty_dry_run_map, DRY_RUN_TIME_LIMIT, DUMMY_PROGRAM,
+};
 use battle_sim::r#impl::grid_map::GridBattleMap;
-use battle_sim::r#impl::grid_map_prober::GridMapProber;
-use battle_sim::r#impl::grid_orientation::GridOrientation;
-use battle_sim::r#impl::simple_battle_logic::PlayerCommand;
-use battle_sim::r#impl::simple_battle_logic::{CommandTimer, SimpleBattleLogic};
-use battle_sim::r#impl::simple_battle_object_layer::SimpleBattleObjectLayer;
-use battle_sim::r#impl::simple_object::{ObjectCacheType, SimpleObject};
-use battle_sim::r#impl::tile_types_logic::TileTypeLogic;
-use battle_sim::serialization::FromFile;
+use battle_sim::r#impl::gridmap_text_file::load_map_file;
+use battle_sim::r#impl::jsonl_battle_logger::JsonLinesLogWriter;
+use battle_sim::r#impl::log_events::LogAction;
+use battle_sim::r#impl::standard_battle::{
+    analyze_standard_map, new_standard_battle, new_standard_team_battle, step_think_limit,
+    StandardBattle, TeamSetup, DEFAULT_OBJECTS_SEED,
+};
+use battle_sim::r#impl::tile_types::TileType;
+use battle_sim::r#impl::verify_log_writer::VerifyLogWriter;
 
-use rand::prelude::*;
 use std::env::args;
 use std::fs::File;
 use std::io::{self, stdout, Error, ErrorKind, Read, Result, Write};
 use std::path::PathBuf;
 use std::process::ExitCode;
 
-struct CommandTimings {}
-
-impl CommandTimer<PlayerCommand<GridOrientation>> for CommandTimings {
-    fn get_base_duration(
-        &self,
-        command: &PlayerCommand<GridOrientation>,
-    ) -> battle_sim::gametime::GameTime {
-        match command {
-            PlayerCommand::MoveFwd => 5, // half, half after
-            PlayerCommand::MoveBack => 7, // half, half after
-            PlayerCommand::TurnCW => 8,  // half, half after
-            PlayerCommand::TurnCCW => 8, // half, half after
-            PlayerCommand::Shoot => 5,
-            PlayerCommand::AfterShootCooldown => 20,
-            PlayerCommand::ShotHitSound => 30,
-            PlayerCommand::Look(_) => 4,
-            PlayerCommand::Listen => 3, // start listening fast, delay reply by long
-            PlayerCommand::Wait => 5,
-            PlayerCommand::AddAmmo(_) => 2,
-            PlayerCommand::AddHealth(_) => 2,
-            PlayerCommand::CheckAmmo => 2,
-            PlayerCommand::CheckHealth => 2,
-            PlayerCommand::CheckHit => 2,
-            PlayerCommand::ResetHit => 1,
-            PlayerCommand::Print(_) => 0,
-            PlayerCommand::Time => 0,
-        }
-    }
-    fn get_reply_delay(
-        &self,
-        command: &PlayerCommand<GridOrientation>,
-    ) -> battle_sim::gametime::GameTime {
-        match command {
-            PlayerCommand::MoveFwd => 5,
-            PlayerCommand::MoveBack => 7,
-            PlayerCommand::TurnCW => 8,
-            PlayerCommand::TurnCCW => 8,
-            PlayerCommand::Shoot => 5,
-            PlayerCommand::Listen => 12,
-            _ => 0,
-        }
-    }
+enum LogFormat {
+    Tsv,
+    JsonLines,
 }
 
 struct Config {
     map_path: PathBuf,
     player_programs: Vec<PathBuf>,
     log_path: Option<PathBuf>,
+    log_format: LogFormat,
     time_limit: Option<GameTime>,
+    think_steps: Option<u64>,
+    seed: Option<u64>,
+    teams: Option<Vec<usize>>,
+    friendly_fire: bool,
+    allow_invalid_map: bool,
+    verify_path: Option<PathBuf>,
+    dry_run_program: Option<PathBuf>,
 }
 
 fn main() -> ExitCode {
@@ -81,44 +52,49 @@ fn main() -> ExitCode {
         }
     };
 
-    let map = match GridBattleMap::load_from_file(&config.map_path) {
-        Ok(x) => x,
-        Err(e) => {
-            eprintln!(
-                "failed to load map at '{}': {}",
-                config.map_path.to_string_lossy(),
-                e
-            );
-            return ExitCode::from(1);
-        }
+    // dry run bot goes first, its opponent is the optional second program
+    let program_paths: Vec<&PathBuf> = config
+        .dry_run_program
+        .iter()
+        .chain(config.player_programs.iter())
+        .collect();
+    let player_count = if config.dry_run_program.is_some() {
+        2
+    } else {
+        program_paths.len()
     };
-    let map_logic = TileTypeLogic::new();
 
-    let logger = BufferLogWriter::new(io::BufWriter::new({
-        if let Some(path) = config.log_path {
-            if let Ok(file) = File::create(path) {
-                Box::new(file) as Box<dyn Write>
-            } else {
-                eprintln!("failed to create battle log file");
+    let map = if config.dry_run_program.is_some() && config.map_path.as_os_str().is_empty() {
+        empty_dry_run_map()
+    } else {
+        match load_map_file(&config.map_path) {
+            Ok(x) => x,
+            Err(e) => {
+                eprintln!(
+                    "failed to load map at '{}': {}",
+                    config.map_path.to_string_lossy(),
+                    e
+                );
                 return ExitCode::from(1);
             }
-        } else {
-            Box::new(stdout()) as Box<dyn Write>
         }
-    }));
-
-    let mut player_initial_data = Vec::with_capacity(config.player_programs.len());
-    let player_initial_placements = match map.get_spawn_locations(config.player_programs.len()) {
-        Ok(x) => x,
-        Err(_) => {
-            eprintln!("failed to generate spawn locations for all playes on the given map");
+    };
+    if let Some(analysis) = analyze_standard_map(
+        &map,
+        player_count,
+        config.seed.unwrap_or(DEFAULT_OBJECTS_SEED),
+    ) {
+        for problem in analysis.problems() {
+            eprintln!("map problem: {}", problem);
+        }
+        if !analysis.is_valid() && !config.allow_invalid_map {
+            eprintln!("refusing to play on invalid map, pass --allow-invalid-map to play anyway");
             return ExitCode::from(1);
         }
-    };
-    for (player_program_file, (x, y, ori)) in
-        config.player_programs.iter().zip(player_initial_placements)
-    {
-        let mut file = match std::fs::File::open(&player_program_file) {
+    }
+    let mut players = Vec::with_capacity(player_count);
+    for player_program_file in program_paths.iter() {
+        let mut file = match std::fs::File::open(player_program_file) {
             Ok(x) => x,
             Err(e) => {
                 eprintln!(
@@ -143,51 +119,126 @@ fn main() -> ExitCode {
             .file_stem()
             .map(|x| x.to_str().unwrap_or("player"))
             .unwrap_or("player");
-        player_initial_data.push((new_player(x, y, ori, 5, 5, name), player_program));
+        players.push((name.to_owned(), player_program));
     }
+    if players.len() < player_count {
+        players.push(("dummy".to_owned(), DUMMY_PROGRAM.to_owned()));
+    }
+    let program_names: Vec<String> = program_paths
+        .iter()
+        .map(|x| x.to_string_lossy().into_owned())
+        .collect();
 
-    let mut object_layer = SimpleBattleObjectLayer::new();
-    {
-        let mut rng = StdRng::seed_from_u64(1234567);
-        let ammocrates_count =
-            1.max(map.map_data().row_count() * map.map_data().row(0).len() / 100);
-        for _ in 0..ammocrates_count {
-            for _ in 0..100 {
-                let y = rng.gen_range(0..map.map_data().row_count());
-                let x = rng.gen_range(0..map.map_data().row(y).len());
-                let x = x as i64;
-                let y = y as i64;
-                if !map_logic.passable(map.get_tile_at(x, y))
-                    || object_layer.objects_at(x, y).len() > 0
-                {
-                    continue;
-                }
-                object_layer.add(SimpleObject::new(
-                    x,
-                    y,
-                    GridOrientation::North,
-                    ObjectCacheType::AmmoCrate(17),
-                    false,
-                    true,
-                    false,
-                ));
-                break;
+    if config.dry_run_program.is_some() {
+        return dry_run(&config, map, players, program_names);
+    }
+
+    let result = if let Some(verify_path) = &config.verify_path {
+        let expected_log = match File::open(verify_path)
+            .and_then(|file| read_log_records(io::BufReader::new(file)))
+        {
+            Ok(x) => x,
+            Err(e) => {
+                eprintln!(
+                    "failed to read battle log to verify at '{}': {}",
+                    verify_path.to_string_lossy(),
+                    e
+                );
+                return ExitCode::from(1);
             }
+        };
+        let mut battle = match new_battle(&config, map, players, VerifyLogWriter::new(expected_log)) {
+            Some(x) => x,
+            None => return ExitCode::from(1),
+        };
+        battle.set_program_names(program_names);
+        let result = battle.run_simulation_with_time_limit(config.time_limit);
+        report_program_exits(&result.program_exits);
+        if let Some(divergence) = battle.log_writer().divergence() {
+            println!("DIVERGED at {}", divergence);
+            return ExitCode::from(3);
         }
-    }
+        println!("VERIFIED");
+        result
+    } else {
+        let log_buffer = io::BufWriter::new({
+            if let Some(path) = &config.log_path {
+                if let Ok(file) = File::create(path) {
+                    Box::new(file) as Box<dyn Write>
+                } else {
+                    eprintln!("failed to create battle log file");
+                    return ExitCode::from(1);
+                }
+            } else {
+                Box::new(stdout()) as Box<dyn Write>
+            }
+        });
+        let logger = match config.log_format {
+            LogFormat::Tsv => {
+                Box::new(BufferLogWriter::new(log_buffer)) as Box<dyn LogWriter<String, LogAction>>
+            }
+            LogFormat::JsonLines => {
+                Box::new(JsonLinesLogWriter::new(log_buffer)) as Box<dyn LogWriter<String, LogAction>>
+            }
+        };
+        let mut battle = match new_battle(&config, map, players, logger) {
+            Some(x) => x,
+            None => return ExitCode::from(1),
+        };
+        battle.set_program_names(program_names);
+        let result = battle.run_simulation_with_time_limit(config.time_limit);
+        report_program_exits(&result.program_exits);
+        result
+    };
 
-    let game_logic = SimpleBattleLogic::new(
-        map,
-        map_logic,
-        GridMapProber::new(),
-        object_layer,
-        CommandTimings {},
-        1,
-        30,
-    );
-    let mut battle = GridBattle::new(game_logic, player_initial_data, logger);
-    let winners = battle.run_simulation_with_time_limit(config.time_limit);
+    report_winners(result.winners);
+    ExitCode::SUCCESS
+}
 
+///
+/// run the bot against the dummy, echoing every script call of the bot to stdout.
+/// battle log is only written if asked for with --output
+///
+fn dry_run(
+    config: &Config,
+    map: GridBattleMap<TileType>,
+    players: Vec<(String, String)>,
+    program_names: Vec<String>,
+) -> ExitCode {
+    let log_buffer = io::BufWriter::new({
+        if let Some(path) = &config.log_path {
+            if let Ok(file) = File::create(path) {
+                Box::new(file) as Box<dyn Write>
+            } else {
+                eprintln!("failed to create battle log file");
+                return ExitCode::from(1);
+            }
+        } else {
+            Box::new(io::sink()) as Box<dyn Write>
+        }
+    });
+    let mut battle = match new_battle(config, map, players, BufferLogWriter::new(log_buffer)) {
+        Some(x) => x,
+        None => return ExitCode::from(1),
+    };
+    battle.set_program_names(program_names);
+    battle.set_command_echo(Box::new(|player_i, command, reply, issued_at, replied_at| {
+        if player_i == 0 {
+            println!(
+                "{}",
+                command_echo_line(command, reply, issued_at, replied_at)
+            );
+        }
+    }));
+    let result =
+        battle.run_simulation_with_time_limit(config.time_limit.or(Some(DRY_RUN_TIME_LIMIT)));
+    println!("battle ended at game time {}", battle.time());
+    report_program_exits(&result.program_exits);
+    report_winners(result.winners);
+    ExitCode::SUCCESS
+}
+
+fn report_winners(winners: Option<Vec<usize>>) {
     match winners {
         Some(winner_ids) if winner_ids.len() > 0 => {
             println!(
@@ -203,16 +254,73 @@ fn main() -> ExitCode {
             println!("DRAW");
         }
     }
+}
 
-    ExitCode::SUCCESS
+/// how programs ended is reported to stderr, so it does not mix with the log
+fn report_program_exits(program_exits: &[Option<ProgramExit>]) {
+    for (i, program_exit) in program_exits.iter().enumerate() {
+        match program_exit {
+            Some(ProgramExit::Finished) => eprintln!("player {} program finished", i),
+            Some(ProgramExit::Stopped) => eprintln!("player {} program stopped by the battle", i),
+            Some(ProgramExit::ThinkTimeout) => {
+                eprintln!("player {} program ran out of think time", i)
+            }
+            Some(ProgramExit::Errored(e)) => eprintln!("player {} program errored out: {}", i, e),
+            Some(ProgramExit::CompileError(e)) => {
+                eprintln!("player {} program failed to compile: {}", i, e)
+            }
+            None => eprintln!("player {} program thread crashed", i),
+        }
+    }
+}
+
+fn new_battle<LW>(
+    config: &Config,
+    map: GridBattleMap<TileType>,
+    players: Vec<(String, String)>,
+    logger: LW,
+) -> Option<StandardBattle<LW>>
+where
+    LW: LogWriter<String, LogAction>,
+{
+    let objects_seed = config.seed.unwrap_or(DEFAULT_OBJECTS_SEED);
+    let battle = if let Some(teams) = &config.teams {
+        let team_setup = TeamSetup {
+            teams: teams.clone(),
+            friendly_fire: config.friendly_fire,
+        };
+        new_standard_team_battle(map, players, team_setup, objects_seed, logger)
+    } else {
+        new_standard_battle(map, players, objects_seed, logger)
+    };
+    let mut battle = match battle {
+        Some(x) => x,
+        None => {
+            eprintln!("failed to generate spawn locations for all playes on the given map");
+            return None;
+        }
+    };
+    if let Some(free_steps) = config.think_steps {
+        battle.set_think_limit(step_think_limit(free_steps));
+    }
+    if let Some(seed) = config.seed {
+        battle.set_seed(seed);
+    }
+    Some(battle)
 }
 
 enum ArgsState {
     FlagOrMapPath,
     PlayerProgram,
     GameTimeLimit,
+    ThinkSteps,
+    Seed,
+    Teams,
     PlayerProgramOrDone,
     BattleLogPath,
+    BattleLogFormat,
+    VerifyLogPath,
+    DryRunProgram,
 }
 
 fn parse_args() -> Result<Config> {
@@ -221,7 +329,15 @@ fn parse_args() -> Result<Config> {
         map_path: PathBuf::new(),
         player_programs: Vec::new(),
         log_path: None,
+        log_format: LogFormat::Tsv,
         time_limit: None,
+        think_steps: None,
+        seed: None,
+        teams: None,
+        friendly_fire: true,
+        allow_invalid_map: false,
+        verify_path: None,
+        dry_run_program: None,
     };
 
     let args = args().skip(1);
@@ -232,10 +348,42 @@ fn parse_args() -> Result<Config> {
                     state = ArgsState::BattleLogPath;
                     continue;
                 }
+                "-f" | "--log-format" => {
+                    state = ArgsState::BattleLogFormat;
+                    continue;
+                }
                 "-l" | "--time-limit" => {
                     state = ArgsState::GameTimeLimit;
                     continue;
                 }
+                "--think-steps" => {
+                    state = ArgsState::ThinkSteps;
+                    continue;
+                }
+                "--seed" => {
+                    state = ArgsState::Seed;
+                    continue;
+                }
+                "--verify" => {
+                    state = ArgsState::VerifyLogPath;
+                    continue;
+                }
+                "--teams" => {
+                    state = ArgsState::Teams;
+                    continue;
+                }
+                "--dry-run" => {
+                    state = ArgsState::DryRunProgram;
+                    continue;
+                }
+                "--no-friendly-fire" => {
+                    config.friendly_fire = false;
+                    continue;
+                }
+                "--allow-invalid-map" => {
+                    config.allow_invalid_map = true;
+                    continue;
+                }
                 arg => {
                     config.map_path = PathBuf::from(arg);
                     state = ArgsState::PlayerProgram;
@@ -249,6 +397,27 @@ fn parse_args() -> Result<Config> {
                 config.log_path = Some(PathBuf::from(arg));
                 state = ArgsState::FlagOrMapPath;
             }
+            ArgsState::BattleLogFormat => {
+                config.log_format = match arg.as_str() {
+                    "tsv" => LogFormat::Tsv,
+                    "jsonl" => LogFormat::JsonLines,
+                    _ => {
+                        return Err(Error::new(
+                            ErrorKind::InvalidData,
+                            "log format must be one of: tsv, jsonl",
+                        ));
+                    }
+                };
+                state = ArgsState::FlagOrMapPath;
+            }
+            ArgsState::VerifyLogPath => {
+                config.verify_path = Some(PathBuf::from(arg));
+                state = ArgsState::FlagOrMapPath;
+            }
+            ArgsState::DryRunProgram => {
+                config.dry_run_program = Some(PathBuf::from(arg));
+                state = ArgsState::FlagOrMapPath;
+            }
             ArgsState::GameTimeLimit => {
                 config.time_limit = Some(if let Ok(x) = u64::from_str_radix(&arg, 10) {
                     x
@@ -260,9 +429,88 @@ fn parse_args() -> Result<Config> {
                 });
                 state = ArgsState::FlagOrMapPath;
             }
+            ArgsState::Seed => {
+                config.seed = Some(if let Ok(x) = arg.parse::<u64>() {
+                    x
+                } else {
+                    return Err(Error::new(ErrorKind::InvalidData, "invalid data for seed"));
+                });
+                state = ArgsState::FlagOrMapPath;
+            }
+            ArgsState::Teams => {
+                config.teams = Some(
+                    arg.split(',')
+                        .map(|x| x.trim().parse::<usize>())
+                        .collect::<std::result::Result<Vec<_>, _>>()
+                        .map_err(|_| {
+                            Error::new(
+                                ErrorKind::InvalidData,
+                                "teams must be a comma separated list of team numbers, one per player",
+                            )
+                        })?,
+                );
+                state = ArgsState::FlagOrMapPath;
+            }
+            ArgsState::ThinkSteps => {
+                config.think_steps = Some(if let Ok(x) = arg.parse::<u64>() {
+                    x
+                } else {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        "invalid data for think steps",
+                    ));
+                });
+                state = ArgsState::FlagOrMapPath;
+            }
         }
     }
 
+    if config.log_path.is_some() && config.verify_path.is_some() {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "--output and --verify cannot be used together",
+        ));
+    }
+
+    if config.dry_run_program.is_some() {
+        // map and dummy program are optional
+        if config.verify_path.is_some() || config.teams.is_some() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "--dry-run cannot be used with --verify or --teams",
+            ));
+        }
+        if config.player_programs.len() > 1 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "--dry-run takes at most one opponent program",
+            ));
+        }
+        return match state {
+            ArgsState::FlagOrMapPath | ArgsState::PlayerProgram | ArgsState::PlayerProgramOrDone => {
+                Ok(config)
+            }
+            _ => Err(Error::new(
+                ErrorKind::InvalidData,
+                "not all arguments provided",
+            )),
+        };
+    }
+
+    if let Some(teams) = &config.teams {
+        if teams.len() != config.player_programs.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "--teams must list a team for every player",
+            ));
+        }
+    } else if !config.friendly_fire {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "--no-friendly-fire makes sense only with --teams",
+        ));
+    }
+
     if let ArgsState::PlayerProgramOrDone = state {
         Ok(config)
     } else {
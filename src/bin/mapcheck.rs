@@ -0,0 +1,163 @@
+use battle_sim::log_data::LogRepresentable;
+use battle_sim::r#impl::grid_map::GridBattleMap;
+use battle_sim::r#impl::gridmap_text_file::load_map_file;
+use battle_sim::r#impl::map_analysis::MapAnalysis;
+use battle_sim::r#impl::standard_battle::{analyze_standard_map, DEFAULT_OBJECTS_SEED};
+use battle_sim::r#impl::tile_types::TileType;
+
+use std::env::args;
+use std::io::{Error, ErrorKind, Result};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+struct Config {
+    map_paths: Vec<PathBuf>,
+    player_count: Option<usize>,
+    seed: u64,
+}
+
+fn main() -> ExitCode {
+    let config = match parse_args() {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!("Error parsing arguments: {e}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut all_valid = true;
+    for map_path in config.map_paths.iter() {
+        let map: GridBattleMap<TileType> = match load_map_file(map_path) {
+            Ok(x) => x,
+            Err(e) => {
+                eprintln!(
+                    "failed to load map at '{}': {}",
+                    map_path.to_string_lossy(),
+                    e
+                );
+                return ExitCode::from(1);
+            }
+        };
+        // maps know best how many players they are for
+        let player_count = config
+            .player_count
+            .or(map.map_data().recommended_players())
+            .unwrap_or(2);
+
+        print!("{}", map_path.to_string_lossy());
+        if let Some(name) = map.map_data().name() {
+            print!(": {}", name);
+        }
+        if let Some(author) = map.map_data().author() {
+            print!(" by {}", author);
+        }
+        println!();
+        match analyze_standard_map(&map, player_count, config.seed) {
+            Some(analysis) => {
+                print_analysis(&analysis);
+                all_valid = all_valid && analysis.is_valid();
+            }
+            None => {
+                println!("  INVALID: no spawn locations for {} players", player_count);
+                all_valid = false;
+            }
+        }
+    }
+
+    if all_valid {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(1)
+    }
+}
+
+fn print_analysis(analysis: &MapAnalysis) {
+    let distance = |x: Option<usize>| x.map_or("unreachable".to_owned(), |x| x.to_string());
+    println!("  size {}x{}", analysis.width, analysis.height);
+    for (i, spawn) in analysis.spawns.iter().enumerate() {
+        println!(
+            "  spawn {} at {},{} {}: exposure {}, nearest pickup {}, nearest enemy {}",
+            i,
+            spawn.x,
+            spawn.y,
+            spawn.orientation.log_repr(),
+            spawn.exposure,
+            distance(spawn.nearest_pickup),
+            distance(spawn.nearest_enemy),
+        );
+    }
+    for region in analysis.unreachable_regions.iter() {
+        println!(
+            "  unreachable region of {} cells at {},{}",
+            region.size, region.cell.0, region.cell.1
+        );
+    }
+    println!(
+        "  symmetry {:.2}, fairness {:.2}",
+        analysis.symmetry, analysis.fairness
+    );
+    let problems = analysis.problems();
+    for problem in problems.iter() {
+        println!("  PROBLEM: {}", problem);
+    }
+    println!("  {}", if problems.is_empty() { "OK" } else { "INVALID" });
+}
+
+enum ArgsState {
+    FlagOrMapPath,
+    PlayerCount,
+    Seed,
+}
+
+fn parse_args() -> Result<Config> {
+    let mut state = ArgsState::FlagOrMapPath;
+    let mut config = Config {
+        map_paths: Vec::new(),
+        player_count: None,
+        seed: DEFAULT_OBJECTS_SEED,
+    };
+
+    for arg in args().skip(1) {
+        match state {
+            ArgsState::FlagOrMapPath => match arg.as_str() {
+                "-p" | "--players" => {
+                    state = ArgsState::PlayerCount;
+                }
+                "--seed" => {
+                    state = ArgsState::Seed;
+                }
+                arg => {
+                    config.map_paths.push(PathBuf::from(arg));
+                }
+            },
+            ArgsState::PlayerCount => {
+                config.player_count = match arg.parse::<usize>() {
+                    Ok(x) if x > 0 => Some(x),
+                    _ => {
+                        return Err(Error::new(
+                            ErrorKind::InvalidData,
+                            "invalid data for player count",
+                        ))
+                    }
+                };
+                state = ArgsState::FlagOrMapPath;
+            }
+            ArgsState::Seed => {
+                config.seed = arg
+                    .parse::<u64>()
+                    .map_err(|_| Error::new(ErrorKind::InvalidData, "invalid data for seed"))?;
+                state = ArgsState::FlagOrMapPath;
+            }
+        }
+    }
+    if !matches!(state, ArgsState::FlagOrMapPath) {
+        return Err(Error::new(ErrorKind::InvalidData, "flag value is missing"));
+    }
+    if config.map_paths.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "usage: mapcheck [-p PLAYERS] [--seed N] map.json [map.json ...]",
+        ));
+    }
+    Ok(config)
+}
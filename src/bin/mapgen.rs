@@ -1,20 +1,31 @@
-use battle_sim::r#impl::grid_map::GridBattleMap;
-use battle_sim::r#impl::tile_types::TileType;
-use battle_sim::map::MapWriteAccess;
+use battle_sim::r#impl::buf_battle_log_reader::read_log_events;
+use battle_sim::r#impl::gridmap_text_file::{load_map_file, save_map_file, TextGridBattleMap};
+use battle_sim::r#impl::log_replay::LogReplay;
+use battle_sim::r#impl::map_generator::{MapGenMode, MapGenerator, MapSymmetry};
+use battle_sim::r#impl::map_image::{position_heatmap, MapRenderer};
 use battle_sim::serialization::ToFile;
 
 use std::env::args;
-use std::io::{Error, ErrorKind};
+use std::fs::File;
+use std::io::{self, Error, ErrorKind};
 use std::path::PathBuf;
 use std::process::ExitCode;
 use std::str::FromStr;
-use rand::prelude::*;
 
 struct Config {
     width: usize,
     height: usize,
     is_opened: bool,
     seed: u64,
+    mode: MapGenMode,
+    symmetry: MapSymmetry,
+    mud_percent: u64,
+    convert_path: Option<PathBuf>,
+    text_output: bool,
+    render_path: Option<PathBuf>,
+    cell_size: u32,
+    log_path: Option<PathBuf>,
+    heatmap: bool,
     out_path: Option<PathBuf>,
 }
 
@@ -27,33 +38,71 @@ fn main() -> ExitCode {
         }
     };
 
-    let mut map = GridBattleMap::new(
-        config.width,
-        config.height,
-        TileType::Ground,
-        if config.is_opened {
-            TileType::Ground
-        } else {
-            TileType::Wall
-        },
-    );
+    let map = if let Some(path) = config.convert_path {
+        match load_map_file(&path) {
+            Ok(x) => x,
+            Err(e) => {
+                eprintln!("failed to load map at '{}': {}", path.to_string_lossy(), e);
+                return ExitCode::from(1);
+            }
+        }
+    } else {
+        let mut generator = MapGenerator::new(config.width, config.height, config.seed);
+        generator.set_mode(config.mode);
+        generator.set_symmetry(config.symmetry);
+        generator.set_mud_share(config.mud_percent as f64 / 100.0);
+        generator.set_opened(config.is_opened);
+        generator.generate()
+    };
 
-    // some basic gen for now, nothing smart
-    let mut rng = StdRng::seed_from_u64(config.seed);
-    for _ in 0..(((config.height*config.width) as f64 * 0.07) as usize) {
-        let x: i64 = rng.gen_range(0..config.width).try_into().unwrap();
-        let y: i64 = rng.gen_range(0..config.height).try_into().unwrap();
+    if let Some(render_path) = &config.render_path {
+        let replay = match &config.log_path {
+            Some(path) => match File::open(path)
+                .and_then(|file| read_log_events(io::BufReader::new(file)))
+            {
+                Ok(x) => Some(LogReplay::new(x)),
+                Err(e) => {
+                    eprintln!(
+                        "failed to read battle log at '{}': {}",
+                        path.to_string_lossy(),
+                        e
+                    );
+                    return ExitCode::from(1);
+                }
+            },
+            None => None,
+        };
+        let state = replay.as_ref().map(|x| x.state_at(x.end_time()));
+        let heatmap = match (&replay, config.heatmap) {
+            (Some(replay), true) => Some(position_heatmap(replay)),
+            _ => None,
+        };
 
-        map.set_tile_at(x, y, TileType::Wall);
+        let mut renderer = MapRenderer::new(&map);
+        renderer.set_cell_size(config.cell_size);
+        renderer.set_state(state.as_ref());
+        renderer.set_heatmap(heatmap.as_ref());
+        if let Err(e) = renderer.render().save(render_path) {
+            eprintln!("failed to save map image: {}", e);
+            return ExitCode::from(1);
+        }
+        if config.out_path.is_none() {
+            return ExitCode::SUCCESS;
+        }
     }
 
     if let Some(path) = config.out_path {
-        if let Err(e) = map.save_to_file(&path) {
+        if let Err(e) = save_map_file(&map, &path) {
             eprintln!("failed to save map to file: {}", e);
             return ExitCode::from(1);
         }
     } else {
-        if let Err(e) = map.save_to_writer(std::io::stdout()) {
+        let result = if config.text_output {
+            TextGridBattleMap(map).save_to_writer(std::io::stdout())
+        } else {
+            map.save_to_writer(std::io::stdout())
+        };
+        if let Err(e) = result {
             eprintln!("failed to save map to stdout: {}", e);
             return ExitCode::from(1);
         }
@@ -66,6 +115,13 @@ fn main() -> ExitCode {
 enum ArgsState {
     FlagOrOut,
     Seed,
+    Mode,
+    Symmetry,
+    MudPercent,
+    ConvertPath,
+    RenderPath,
+    CellSize,
+    LogPath,
     MapWidth,
     MapHeight,
     Nothing,
@@ -77,6 +133,15 @@ fn parse_args() -> Result<Config, Error> {
         height: 16,
         is_opened: false,
         seed: 123456,
+        mode: MapGenMode::Random,
+        symmetry: MapSymmetry::None,
+        mud_percent: 0,
+        convert_path: None,
+        text_output: false,
+        render_path: None,
+        cell_size: 16,
+        log_path: None,
+        heatmap: false,
         out_path: None,
     };
 
@@ -96,10 +161,46 @@ fn parse_args() -> Result<Config, Error> {
                     state = ArgsState::Seed;
                     continue;
                 }
+                "-m" | "--mode" => {
+                    state = ArgsState::Mode;
+                    continue;
+                }
+                "--symmetry" => {
+                    state = ArgsState::Symmetry;
+                    continue;
+                }
+                "--mud" => {
+                    state = ArgsState::MudPercent;
+                    continue;
+                }
                 "--opened" => {
                     config.is_opened = true;
                     continue;
                 }
+                "-c" | "--convert" => {
+                    state = ArgsState::ConvertPath;
+                    continue;
+                }
+                "--text" => {
+                    config.text_output = true;
+                    continue;
+                }
+                "--render" => {
+                    state = ArgsState::RenderPath;
+                    continue;
+                }
+                "--cell" => {
+                    state = ArgsState::CellSize;
+                    continue;
+                }
+                "--log" => {
+                    state = ArgsState::LogPath;
+                    continue;
+                }
+                "--heatmap" => {
+                    config.heatmap = true;
+                    continue;
+                }
                 s => {
                     config.out_path = Some(PathBuf::from_str(s).unwrap());
                     state = ArgsState::Nothing;
@@ -117,6 +218,76 @@ fn parse_args() -> Result<Config, Error> {
                     Ok(x) => x,
                     Err(e) => return Err(Error::new(ErrorKind::InvalidData, e)),
                 };
+                if config.width == 0 || config.height == 0 {
+                    return Err(Error::new(ErrorKind::InvalidData, "map size can not be 0"));
+                }
+                state = ArgsState::FlagOrOut;
+            }
+            ArgsState::Mode => {
+                config.mode = match arg.as_str() {
+                    "random" => MapGenMode::Random,
+                    "caves" => MapGenMode::Caves,
+                    "maze" => MapGenMode::Maze,
+                    "rooms" => MapGenMode::Rooms,
+                    _ => {
+                        return Err(Error::new(
+                            ErrorKind::InvalidData,
+                            "mode must be one of: random, caves, maze, rooms",
+                        ))
+                    }
+                };
+                state = ArgsState::FlagOrOut;
+            }
+            ArgsState::Symmetry => {
+                config.symmetry = match arg.as_str() {
+                    "none" => MapSymmetry::None,
+                    "mirror" => MapSymmetry::Mirror,
+                    "rotational" => MapSymmetry::Rotational,
+                    "mirror4" => MapSymmetry::Mirror4,
+                    "rotational4" => MapSymmetry::Rotational4,
+                    _ => {
+                        return Err(Error::new(
+                            ErrorKind::InvalidData,
+                            "symmetry must be one of: none, mirror, rotational, mirror4, rotational4",
+                        ))
+                    }
+                };
+                state = ArgsState::FlagOrOut;
+            }
+            ArgsState::MudPercent => {
+                config.mud_percent = match arg.parse::<u64>() {
+                    Ok(x) if x <= 100 => x,
+                    _ => {
+                        return Err(Error::new(
+                            ErrorKind::InvalidData,
+                            "mud must be a percentage from 0 to 100",
+                        ))
+                    }
+                };
+                state = ArgsState::FlagOrOut;
+            }
+            ArgsState::ConvertPath => {
+                config.convert_path = Some(PathBuf::from_str(&arg).unwrap());
+                state = ArgsState::FlagOrOut;
+            }
+            ArgsState::RenderPath => {
+                config.render_path = Some(PathBuf::from_str(&arg).unwrap());
+                state = ArgsState::FlagOrOut;
+            }
+            ArgsState::CellSize => {
+                config.cell_size = match arg.parse::<u32>() {
+                    Ok(x) if x > 0 => x,
+                    _ => {
+                        return Err(Error::new(
+                            ErrorKind::InvalidData,
+                            "cell size must be a positive number of pixels",
+                        ))
+                    }
+                };
+                state = ArgsState::FlagOrOut;
+            }
+            ArgsState::LogPath => {
+                config.log_path = Some(PathBuf::from_str(&arg).unwrap());
                 state = ArgsState::FlagOrOut;
             }
             ArgsState::Seed => {
@@ -135,5 +306,12 @@ fn parse_args() -> Result<Config, Error> {
         }
     }
 
+    if config.heatmap && config.log_path.is_none() {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "--heatmap needs a battle log given with --log",
+        ));
+    }
+
     Ok(config)
 }
@@ -0,0 +1,336 @@
+use battle_sim::gametime::GameTime;
+use battle_sim::r#impl::battle_batch::{run_batch, BatchBattle};
+use battle_sim::r#impl::grid_map::GridBattleMap;
+use battle_sim::r#impl::gridmap_text_file::load_map_file;
+use battle_sim::r#impl::standard_battle::step_think_limit;
+use battle_sim::r#impl::tile_types::TileType;
+use battle_sim::r#impl::tournament::{round_robin_pairings, GameOutcome, Standings};
+
+use std::env::args;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+// a tournament cannot afford a standoff that never ends
+const DEFAULT_TIME_LIMIT: GameTime = 10000;
+
+enum Pairing {
+    RoundRobin,
+    Swiss(usize),
+}
+
+struct Config {
+    bots_dir: PathBuf,
+    map_paths: Vec<PathBuf>,
+    pairing: Pairing,
+    time_limit: GameTime,
+    think_steps: Option<u64>,
+    seed: Option<u64>,
+    json_path: Option<PathBuf>,
+    jobs: usize,
+}
+
+struct Games<'a> {
+    config: &'a Config,
+    maps: &'a [(String, GridBattleMap<TileType>)],
+    bots: &'a [(String, String)],
+}
+
+impl Games<'_> {
+    /// every pair plays every map twice with swapped spawns,
+    /// games run in parallel, but are recorded in a fixed order
+    fn play_round(&self, standings: &mut Standings, pairings: Vec<(usize, usize)>) {
+        let games: Vec<(usize, usize, usize)> = pairings
+            .into_iter()
+            .flat_map(|(first, second)| {
+                (0..self.maps.len())
+                    .flat_map(move |map_i| [(first, second, map_i), (second, first, map_i)])
+            })
+            .collect();
+        let battles = games
+            .iter()
+            .map(|&(a, b, map_i)| BatchBattle {
+                map: self.maps[map_i].1.clone(),
+                players: vec![self.bots[a].clone(), self.bots[b].clone()],
+                seed: self.config.seed,
+                time_limit: Some(self.config.time_limit),
+                think_limit: self.config.think_steps.map(step_think_limit),
+                keep_log: false,
+            })
+            .collect();
+
+        for ((a, b, map_i), result) in games.into_iter().zip(run_batch(battles, self.config.jobs)) {
+            let Some(result) = result else {
+                // game never happened, it must not count as a draw
+                eprintln!(
+                    "{} vs {} on {}: failed to set up the game, skipped",
+                    self.bots[a].0, self.bots[b].0, self.maps[map_i].0
+                );
+                continue;
+            };
+            let outcome = match result.winners.as_deref() {
+                Some([0]) => GameOutcome::FirstWon,
+                Some([1]) => GameOutcome::SecondWon,
+                _ => GameOutcome::Draw,
+            };
+            eprintln!(
+                "{} vs {} on {}: {}",
+                self.bots[a].0,
+                self.bots[b].0,
+                self.maps[map_i].0,
+                match outcome {
+                    GameOutcome::FirstWon => self.bots[a].0.as_str(),
+                    GameOutcome::SecondWon => self.bots[b].0.as_str(),
+                    GameOutcome::Draw => "draw",
+                }
+            );
+            standings.record_game(a, b, outcome);
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let config = match parse_args() {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!("Error parsing arguments: {e}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut maps = Vec::with_capacity(config.map_paths.len());
+    for map_path in config.map_paths.iter() {
+        let map: GridBattleMap<TileType> = match load_map_file(map_path) {
+            Ok(x) => x,
+            Err(e) => {
+                eprintln!(
+                    "failed to load map at '{}': {}",
+                    map_path.to_string_lossy(),
+                    e
+                );
+                return ExitCode::from(1);
+            }
+        };
+        if map.get_spawn_locations(2).is_err() {
+            eprintln!(
+                "map '{}' has no spawn locations for 2 players",
+                map_path.to_string_lossy()
+            );
+            return ExitCode::from(1);
+        }
+        let name = map_path
+            .file_stem()
+            .map(|x| x.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        maps.push((name, map));
+    }
+
+    let bots = match read_bots(&config.bots_dir) {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!(
+                "failed to read bot programs from '{}': {}",
+                config.bots_dir.to_string_lossy(),
+                e
+            );
+            return ExitCode::from(1);
+        }
+    };
+    if bots.len() < 2 {
+        eprintln!("need at least 2 bot programs for a tournament");
+        return ExitCode::from(1);
+    }
+
+    let mut standings = Standings::new(bots.iter().map(|(name, _)| name.clone()).collect());
+    let games = Games {
+        config: &config,
+        maps: &maps,
+        bots: &bots,
+    };
+    match config.pairing {
+        Pairing::RoundRobin => {
+            games.play_round(&mut standings, round_robin_pairings(bots.len()));
+        }
+        Pairing::Swiss(rounds) => {
+            for round in 0..rounds {
+                eprintln!("round {}", round + 1);
+                let pairings = standings.swiss_pairings();
+                if let Some(bye) = standings.swiss_bye() {
+                    eprintln!("{} sits the round out", bots[bye].0);
+                    standings.record_bye(bye);
+                }
+                games.play_round(&mut standings, pairings);
+            }
+        }
+    }
+
+    if let Some(json_path) = &config.json_path {
+        let json = match standings.to_json() {
+            Ok(x) => x,
+            Err(e) => {
+                eprintln!("failed to serialize standings: {}", e);
+                return ExitCode::from(1);
+            }
+        };
+        if let Err(e) = fs::write(json_path, json) {
+            eprintln!("failed to write standings: {}", e);
+            return ExitCode::from(1);
+        }
+    }
+    match config.seed {
+        Some(seed) => println!("seed: {}", seed),
+        None => println!("seed: none"),
+    }
+    print!("{}", standings.to_text());
+
+    ExitCode::SUCCESS
+}
+
+/// every .py file in the directory is a bot, named by file stem
+fn read_bots(dir: &PathBuf) -> Result<Vec<(String, String)>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|x| x.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|x| x == "py"))
+        .collect();
+    paths.sort();
+
+    let mut bots = Vec::with_capacity(paths.len());
+    for path in paths {
+        let program = fs::read_to_string(&path)?;
+        let name = path
+            .file_stem()
+            .map(|x| x.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        bots.push((name, program));
+    }
+    Ok(bots)
+}
+
+enum ArgsState {
+    FlagOrBotsDir,
+    SwissRounds,
+    GameTimeLimit,
+    ThinkSteps,
+    Seed,
+    JsonPath,
+    Jobs,
+    MapPath,
+    MapPathOrDone,
+}
+
+fn parse_args() -> Result<Config> {
+    let mut state = ArgsState::FlagOrBotsDir;
+    let mut config = Config {
+        bots_dir: PathBuf::new(),
+        map_paths: Vec::new(),
+        pairing: Pairing::RoundRobin,
+        time_limit: DEFAULT_TIME_LIMIT,
+        think_steps: None,
+        seed: None,
+        json_path: None,
+        jobs: 0,
+    };
+
+    let args = args().skip(1);
+    for arg in args {
+        match state {
+            ArgsState::FlagOrBotsDir => match arg.as_str() {
+                "--swiss" => {
+                    state = ArgsState::SwissRounds;
+                    continue;
+                }
+                "-l" | "--time-limit" => {
+                    state = ArgsState::GameTimeLimit;
+                    continue;
+                }
+                "--think-steps" => {
+                    state = ArgsState::ThinkSteps;
+                    continue;
+                }
+                "--seed" => {
+                    state = ArgsState::Seed;
+                    continue;
+                }
+                "-o" | "--output" => {
+                    state = ArgsState::JsonPath;
+                    continue;
+                }
+                "-j" | "--jobs" => {
+                    state = ArgsState::Jobs;
+                    continue;
+                }
+                arg => {
+                    config.bots_dir = PathBuf::from(arg);
+                    state = ArgsState::MapPath;
+                }
+            },
+            ArgsState::SwissRounds => {
+                config.pairing = Pairing::Swiss(if let Ok(x) = arg.parse::<usize>() {
+                    x
+                } else {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        "invalid data for swiss rounds",
+                    ));
+                });
+                state = ArgsState::FlagOrBotsDir;
+            }
+            ArgsState::GameTimeLimit => {
+                config.time_limit = if let Ok(x) = arg.parse::<GameTime>() {
+                    x
+                } else {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        "invalid data for time limit",
+                    ));
+                };
+                state = ArgsState::FlagOrBotsDir;
+            }
+            ArgsState::ThinkSteps => {
+                config.think_steps = Some(if let Ok(x) = arg.parse::<u64>() {
+                    x
+                } else {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        "invalid data for think steps",
+                    ));
+                });
+                state = ArgsState::FlagOrBotsDir;
+            }
+            ArgsState::Seed => {
+                config.seed = Some(if let Ok(x) = arg.parse::<u64>() {
+                    x
+                } else {
+                    return Err(Error::new(ErrorKind::InvalidData, "invalid data for seed"));
+                });
+                state = ArgsState::FlagOrBotsDir;
+            }
+            ArgsState::JsonPath => {
+                config.json_path = Some(PathBuf::from(arg));
+                state = ArgsState::FlagOrBotsDir;
+            }
+            ArgsState::Jobs => {
+                config.jobs = if let Ok(x) = arg.parse::<usize>() {
+                    x
+                } else {
+                    return Err(Error::new(ErrorKind::InvalidData, "invalid data for jobs"));
+                };
+                state = ArgsState::FlagOrBotsDir;
+            }
+            ArgsState::MapPath | ArgsState::MapPathOrDone => {
+                config.map_paths.push(PathBuf::from(arg));
+                state = ArgsState::MapPathOrDone;
+            }
+        }
+    }
+
+    if let ArgsState::MapPathOrDone = state {
+        Ok(config)
+    } else {
+        Err(Error::new(
+            ErrorKind::InvalidData,
+            "not all arguments provided",
+        ))
+    }
+}
@@ -0,0 +1,190 @@
+use super::grid_map::GridBattleMap;
+use super::log_replay::WorldState;
+use super::tile_types::TileType;
+use crate::map::MapReadAccess;
+
+use std::fmt::Write;
+
+// every cell is drawn with two characters, so map looks about square in a terminal
+type Cell = [char; 2];
+
+fn tile_cell(tile: TileType) -> Cell {
+    match tile {
+        TileType::Ground => ['.', ' '],
+        TileType::Mud => ['~', '~'],
+        TileType::Wall => ['#', '#'],
+        TileType::BrickWall(_) => ['[', ']'],
+        TileType::Grass => [',', ','],
+        TileType::Tracks => [':', ':'],
+        TileType::ThinIce => ['_', '_'],
+        TileType::Water => ['=', '='],
+    }
+}
+
+fn facing_char(orientation: &str) -> char {
+    match orientation {
+        "north" => '^',
+        "east" => '>',
+        "south" => 'v',
+        "west" => '<',
+        _ => '?',
+    }
+}
+
+// label of players that ran out of digits and letters
+const OVERFLOW_PLAYER_LABEL: char = '@';
+
+/// label players are drawn with: 1..9, then a..z, then '@' for everyone else
+pub fn player_label(player_i: usize) -> char {
+    u32::try_from(player_i + 1)
+        .ok()
+        .and_then(|x| char::from_digit(x, 36))
+        .unwrap_or(OVERFLOW_PLAYER_LABEL)
+}
+
+///
+/// render map with objects, tanks and shots of given world state as text.
+/// tanks are drawn as their label followed by facing arrow,
+/// dead tanks as label followed by 'x', shots as lines ending with '*'
+///
+pub fn render_ascii(map: &GridBattleMap<TileType>, state: &WorldState) -> String {
+    let height = map.map_data().row_count();
+    let width = if height > 0 {
+        map.map_data().row(0).len()
+    } else {
+        0
+    };
+
+    let mut cells: Vec<Vec<Cell>> = (0..height)
+        .map(|y| {
+            (0..width)
+                .map(|x| tile_cell(map.get_tile_at(x as i64, y as i64)))
+                .collect()
+        })
+        .collect();
+    for ((x, y), tile) in state.tiles.iter() {
+        let tile = tile.parse().ok().and_then(TileType::from_code);
+        if let (Some(tile), true) = (tile, map.is_within_bounds(*x, *y)) {
+            cells[*y as usize][*x as usize] = tile_cell(tile);
+        }
+    }
+    let mut put = |x: i64, y: i64, cell: Cell| {
+        if map.is_within_bounds(x, y) {
+            cells[y as usize][x as usize] = cell;
+        }
+    };
+
+    for object in state.objects.values().filter(|x| x.kind != "player") {
+        let label = object.kind.chars().next().unwrap_or('?');
+        put(object.x, object.y, [label, ' ']);
+    }
+    for shot in state.shots.iter() {
+        let (dx, dy) = (
+            (shot.hit_x - shot.x).signum(),
+            (shot.hit_y - shot.y).signum(),
+        );
+        let line = if dx != 0 { ['-', '-'] } else { ['|', ' '] };
+        let (mut x, mut y) = (shot.x + dx, shot.y + dy);
+        while (x, y) != (shot.hit_x, shot.hit_y) && (dx, dy) != (0, 0) {
+            put(x, y, line);
+            x += dx;
+            y += dy;
+        }
+    }
+    for (i, player) in state
+        .objects
+        .values()
+        .filter(|x| x.kind == "player")
+        .enumerate()
+    {
+        let facing = if player.dead {
+            'x'
+        } else {
+            facing_char(&player.orientation)
+        };
+        put(player.x, player.y, [player_label(i), facing]);
+    }
+    for shot in state.shots.iter() {
+        if map.is_within_bounds(shot.hit_x, shot.hit_y) {
+            cells[shot.hit_y as usize][shot.hit_x as usize][1] = '*';
+        }
+    }
+
+    let mut ret = String::with_capacity((width * 2 + 3) * (height + 2));
+    let border = format!("+{}+\n", "-".repeat(width * 2));
+    ret.push_str(&border);
+    for row in cells {
+        ret.push('|');
+        ret.extend(row.into_iter().flatten());
+        ret.push_str("|\n");
+    }
+    ret.push_str(&border);
+    ret
+}
+
+/// one status line per player, then messages, radio and winners of given world state
+pub fn render_status(state: &WorldState) -> String {
+    let mut ret = String::new();
+    for (i, (uid, player)) in state
+        .objects
+        .iter()
+        .filter(|(_, x)| x.kind == "player")
+        .enumerate()
+    {
+        let _ = write!(
+            ret,
+            "{} {}({}) at {},{} {}",
+            player_label(i),
+            player.name.as_deref().unwrap_or(""),
+            uid,
+            player.x,
+            player.y,
+            player.orientation,
+        );
+        if let Some(team) = player.team {
+            let _ = write!(ret, " team {}", team);
+        }
+        if player.dead {
+            ret.push_str(" DEAD");
+        } else if player.dying {
+            ret.push_str(" DYING");
+        }
+        if let Some(command) = &player.command {
+            let _ = write!(ret, " doing {}", command.command);
+            if let Some(argument) = &command.argument {
+                let _ = write!(ret, "[{}]", argument);
+            }
+            let _ = write!(ret, " till {}", command.start + command.duration);
+        }
+        ret.push('\n');
+    }
+    for (uid, message) in state.messages.iter() {
+        let name = state
+            .objects
+            .get(uid)
+            .and_then(|x| x.name.as_deref())
+            .unwrap_or("");
+        let _ = writeln!(ret, "{}({}): {}", name, uid, message);
+    }
+    for (uid, text) in state.radio.iter() {
+        let name = state
+            .objects
+            .get(uid)
+            .and_then(|x| x.name.as_deref())
+            .unwrap_or("");
+        let _ = writeln!(ret, "{}({}) radio: {}", name, uid, text);
+    }
+    if !state.winners.is_empty() {
+        let _ = writeln!(
+            ret,
+            "WINNERS: {}",
+            state
+                .winners
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+    }
+    ret
+}
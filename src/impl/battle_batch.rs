@@ -0,0 +1,129 @@
+use super::buf_battle_logger::BufferLogWriter;
+use super::grid_map::GridBattleMap;
+use super::log_events::LogAction;
+use super::standard_battle::{new_standard_battle, DEFAULT_OBJECTS_SEED};
+use super::tile_types::TileType;
+use crate::battle::{BattleResult, ProgramExit};
+use crate::gametime::GameTime;
+use crate::log_data::LogWriter;
+use crate::think_budget::ThinkLimit;
+
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// one battle of a batch, set up the same way new_standard_battle does
+#[derive(Clone)]
+pub struct BatchBattle {
+    pub map: GridBattleMap<TileType>,
+    /// (name, program source) pairs
+    pub players: Vec<(String, String)>,
+    /// seeds object placement and rand() of player programs,
+    /// None plays the same as battle without --seed
+    pub seed: Option<u64>,
+    pub time_limit: Option<GameTime>,
+    /// None keeps default wall clock limit
+    pub think_limit: Option<ThinkLimit>,
+    /// keep battle log in the result
+    pub keep_log: bool,
+}
+
+pub struct BatchBattleResult {
+    /// same as run_simulation result
+    pub winners: Option<Vec<usize>>,
+    pub program_exits: Vec<Option<ProgramExit>>,
+    pub end_time: GameTime,
+    /// log as written by BufferLogWriter, if keep_log was set
+    pub log: Option<Vec<u8>>,
+}
+
+///
+/// run jobs on at most `workers` threads, results are in the order of jobs.
+/// 0 workers means as many as there are cpus
+///
+pub fn run_in_pool<J, R>(jobs: Vec<J>, workers: usize) -> Vec<R>
+where
+    J: FnOnce() -> R + Send,
+    R: Send,
+{
+    let workers = if workers == 0 {
+        thread::available_parallelism().map_or(1, |x| x.get())
+    } else {
+        workers
+    };
+    let job_count = jobs.len();
+    let jobs: Vec<Mutex<Option<J>>> = jobs.into_iter().map(|x| Mutex::new(Some(x))).collect();
+    let results: Vec<Mutex<Option<R>>> = (0..job_count).map(|_| Mutex::new(None)).collect();
+    let next_job = AtomicUsize::new(0);
+
+    thread::scope(|scope| {
+        for _ in 0..workers.min(job_count) {
+            scope.spawn(|| loop {
+                let i = next_job.fetch_add(1, Ordering::Relaxed);
+                if i >= job_count {
+                    break;
+                }
+                let job = jobs[i].lock().unwrap().take().unwrap();
+                let result = job();
+                *results[i].lock().unwrap() = Some(result);
+            });
+        }
+    });
+
+    results
+        .into_iter()
+        .map(|x| x.into_inner().unwrap().unwrap())
+        .collect()
+}
+
+/// run a single batch battle in current thread.
+/// None if map has no spawn locations for that many players
+pub fn run_batch_battle(battle: BatchBattle) -> Option<BatchBattleResult> {
+    if battle.keep_log {
+        let mut log = Vec::new();
+        let (result, end_time) = simulate(battle, BufferLogWriter::new(&mut log))?;
+        Some(BatchBattleResult {
+            winners: result.winners,
+            program_exits: result.program_exits,
+            end_time,
+            log: Some(log),
+        })
+    } else {
+        let (result, end_time) = simulate(battle, BufferLogWriter::new(io::sink()))?;
+        Some(BatchBattleResult {
+            winners: result.winners,
+            program_exits: result.program_exits,
+            end_time,
+            log: None,
+        })
+    }
+}
+
+fn simulate<LW>(battle: BatchBattle, logger: LW) -> Option<(BattleResult, GameTime)>
+where
+    LW: LogWriter<String, LogAction>,
+{
+    let objects_seed = battle.seed.unwrap_or(DEFAULT_OBJECTS_SEED);
+    let mut b = new_standard_battle(battle.map, battle.players, objects_seed, logger)?;
+    if let Some(seed) = battle.seed {
+        b.set_seed(seed);
+    }
+    if let Some(think_limit) = battle.think_limit {
+        b.set_think_limit(think_limit);
+    }
+    let result = b.run_simulation_with_time_limit(battle.time_limit);
+    Some((result, b.time()))
+}
+
+/// run independent battles concurrently on at most `workers` threads (0 - one per cpu),
+/// results are in the order of battles
+pub fn run_batch(battles: Vec<BatchBattle>, workers: usize) -> Vec<Option<BatchBattleResult>> {
+    run_in_pool(
+        battles
+            .into_iter()
+            .map(|battle| move || run_batch_battle(battle))
+            .collect(),
+        workers,
+    )
+}
@@ -1,20 +1,27 @@
 use super::tile_types::TileType;
+use super::tile_types_logic::TileTypeLogic;
 use crate::maptile_logic::MaptileLogic;
 
 pub struct ConfigurableMaptileLogic {}
 
 impl MaptileLogic<TileType> for ConfigurableMaptileLogic {
+    // tiles change under tanks the same way in every battle
     fn move_from(&self, tile: TileType) -> TileType {
-        tile
+        TileTypeLogic::new().move_from(tile)
     }
     fn move_onto(&self, tile: TileType) -> TileType {
-        tile
+        TileTypeLogic::new().move_onto(tile)
     }
     fn pass_speed_percentage(&self, tile: TileType) -> u32 {
         match tile {
             TileType::Ground => 100,
             TileType::Mud => 50,
             TileType::Wall => 0,
+            TileType::BrickWall(_) => 0,
+            TileType::Grass => 100,
+            TileType::Tracks => 100,
+            TileType::ThinIce => 100,
+            TileType::Water => 0,
         }
     }
     fn turn_speed_percentage(&self, tile: TileType) -> u32 {
@@ -22,6 +29,11 @@ impl MaptileLogic<TileType> for ConfigurableMaptileLogic {
             TileType::Ground => 100,
             TileType::Mud => 80,
             TileType::Wall => 0,
+            TileType::BrickWall(_) => 0,
+            TileType::Grass => 100,
+            TileType::Tracks => 100,
+            TileType::ThinIce => 50,
+            TileType::Water => 0,
         }
     }
     fn passable(&self, tile: TileType) -> bool {
@@ -29,6 +41,11 @@ impl MaptileLogic<TileType> for ConfigurableMaptileLogic {
             TileType::Ground => true,
             TileType::Mud => true,
             TileType::Wall => false,
+            TileType::BrickWall(_) => false,
+            TileType::Grass => true,
+            TileType::Tracks => true,
+            TileType::ThinIce => true,
+            TileType::Water => false,
         }
     }
     fn seethroughable(&self, tile: TileType) -> bool {
@@ -36,9 +53,14 @@ impl MaptileLogic<TileType> for ConfigurableMaptileLogic {
             TileType::Ground => true,
             TileType::Mud => true,
             TileType::Wall => false,
+            TileType::BrickWall(_) => false,
+            TileType::Grass => true,
+            TileType::Tracks => true,
+            TileType::ThinIce => true,
+            TileType::Water => true,
         }
     }
     fn shoot(&self, tile: TileType) -> TileType {
-        tile
+        TileTypeLogic::new().shoot(tile)
     }
 }
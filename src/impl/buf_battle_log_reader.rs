@@ -0,0 +1,82 @@
+use super::log_events::LogEvent;
+use crate::gametime::GameTime;
+
+use std::fmt;
+use std::io::{self, BufRead};
+
+/// single line of a battle log, as written by BufferLogWriter
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogRecord {
+    pub object: String,
+    pub action: String,
+    pub time: GameTime,
+    pub duration: GameTime,
+}
+
+impl LogRecord {
+    /// parse a single "object\taction\ttime\tduration" line
+    pub fn parse(line: &str) -> Option<LogRecord> {
+        let mut parts = line.split('\t');
+        let object = parts.next()?;
+        let action = parts.next()?;
+        let time = parts.next()?.parse().ok()?;
+        let duration = parts.next()?.parse().ok()?;
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(LogRecord {
+            object: object.to_owned(),
+            action: action.to_owned(),
+            time,
+            duration,
+        })
+    }
+}
+
+impl From<&LogRecord> for LogEvent {
+    fn from(record: &LogRecord) -> Self {
+        LogEvent::from_parts(&record.object, &record.action, record.time, record.duration)
+    }
+}
+
+impl fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}\t{}\t{}\t{}",
+            self.object, self.action, self.time, self.duration
+        )
+    }
+}
+
+/// read all records of a battle log, empty lines are skipped
+pub fn read_log_records<R>(r: R) -> io::Result<Vec<LogRecord>>
+where
+    R: BufRead,
+{
+    let mut records = Vec::new();
+    for (i, line) in r.lines().enumerate() {
+        let line = line?;
+        if line.is_empty() {
+            continue;
+        }
+        match LogRecord::parse(&line) {
+            Some(record) => records.push(record),
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("bad battle log line {}: '{}'", i + 1, line),
+                ));
+            }
+        }
+    }
+    Ok(records)
+}
+
+/// read all lines of a battle log as typed events
+pub fn read_log_events<R>(r: R) -> io::Result<Vec<LogEvent>>
+where
+    R: BufRead,
+{
+    Ok(read_log_records(r)?.iter().map(LogEvent::from).collect())
+}
@@ -0,0 +1,80 @@
+use super::grid_map::GridBattleMap;
+use super::script_api::script_function_name;
+use super::simple_battle_logic::{PlayerCommand, PlayerCommandReply};
+use super::tile_types::TileType;
+use crate::gametime::GameTime;
+use crate::script_repr::ToScriptRepr;
+
+/// opponent of a dry run bot when none is given, it stands still till the time limit
+pub const DUMMY_PROGRAM: &str = "while True:\n    wait()\n";
+
+/// game time a dry run ends at unless given otherwise, the dummy never ends the battle itself
+pub const DRY_RUN_TIME_LIMIT: GameTime = 1000;
+
+/// map a dry run bot plays on when none is given: open ground surrounded by walls
+pub fn empty_dry_run_map() -> GridBattleMap<TileType> {
+    GridBattleMap::new(9, 9, TileType::Ground, TileType::Wall)
+}
+
+///
+/// one line describing a script call made by a player program: game time it was issued at,
+/// the call as the program wrote it, the value it returned and its game time cost,
+/// like `[    10] look('left') -> [('ground', None), ('wall', None)]  cost 4`
+///
+pub fn command_echo_line<R>(
+    command: &PlayerCommand<R>,
+    reply: &PlayerCommandReply<R>,
+    issued_at: GameTime,
+    replied_at: GameTime,
+) -> String
+where
+    R: From<u64> + ToScriptRepr,
+{
+    let args = match command {
+        PlayerCommand::Look(direction) => py_str(&direction.to_script_repr()),
+        PlayerCommand::Print(text) | PlayerCommand::SendMessage(text) => py_str(text),
+        _ => String::new(),
+    };
+    let reply = match reply {
+        PlayerCommandReply::Failed => "None (failed)".to_owned(),
+        PlayerCommandReply::Ok => "None".to_owned(),
+        PlayerCommandReply::Bool(x) => if *x { "True" } else { "False" }.to_owned(),
+        PlayerCommandReply::Int(x) => x.to_string(),
+        PlayerCommandReply::Uint(x) => x.to_string(),
+        PlayerCommandReply::HitDirection(x) => py_option(x.as_ref().map(|x| x.to_script_repr())),
+        PlayerCommandReply::LookResult(cells) => py_list(
+            cells
+                .iter()
+                .map(|(tile, object)| format!("({}, {})", py_str(tile), py_option(object.clone()))),
+        ),
+        PlayerCommandReply::ListenResult(sounds) => py_list(sounds.iter().map(|x| py_str(x))),
+        PlayerCommandReply::Messages(messages) => py_list(
+            messages
+                .iter()
+                .map(|(sender, text)| format!("({}, {})", py_str(sender), py_str(text))),
+        ),
+    };
+    format!(
+        "[{:>6}] {}({}) -> {}  cost {}",
+        issued_at,
+        script_function_name(command).unwrap_or("?"),
+        args,
+        reply,
+        replied_at - issued_at,
+    )
+}
+
+fn py_str(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn py_option(s: Option<String>) -> String {
+    s.map_or("None".to_owned(), |x| py_str(&x))
+}
+
+fn py_list<I>(items: I) -> String
+where
+    I: Iterator<Item = String>,
+{
+    format!("[{}]", items.collect::<Vec<_>>().join(", "))
+}
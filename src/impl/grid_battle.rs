@@ -1,6 +1,8 @@
 use super::grid_orientation::GridOrientation;
+use super::log_events::LogAction;
 use super::simple_battle_logic::{
-    PlayerCommand, PlayerCommandReply, SimpleGameEvent, AMMO_RES, HEALTH_RES, MAX_FREE_PRINTS, PRINT_COUNTER_RES,
+    PlayerCommand, PlayerCommandReply, SimpleGameEvent, AMMO_RES, HEALTH_RES, MAX_FREE_PRINTS,
+    MAX_HEALTH_RES, PRINT_COUNTER_RES,
 };
 use crate::battle::Battle;
 
@@ -14,14 +16,30 @@ pub fn new_player(
     health: u64,
     name: &str,
 ) -> GridPlayerState {
-    let mut res = vec![0 as u64; 4];
+    let mut res = vec![0 as u64; MAX_HEALTH_RES + 1];
     res[HEALTH_RES] = health;
+    res[MAX_HEALTH_RES] = health;
     res[AMMO_RES] = ammo;
     res[PRINT_COUNTER_RES] = MAX_FREE_PRINTS;
     
     GridPlayerState::new(col, row, orientation, res, name)
 }
 
+/// same as new_player, but player is in a team. players of the same team are allies
+pub fn new_team_player(
+    col: i64,
+    row: i64,
+    orientation: GridOrientation,
+    ammo: u64,
+    health: u64,
+    name: &str,
+    team: usize,
+) -> GridPlayerState {
+    let mut player = new_player(col, row, orientation, ammo, health, name);
+    player.team = Some(team);
+    player
+}
+
 pub type GridBattle<GameLogic, LW> = Battle<
     GridPlayerState,
     GameLogic,
@@ -29,4 +47,5 @@ pub type GridBattle<GameLogic, LW> = Battle<
     PlayerCommandReply<GridOrientation>,
     SimpleGameEvent,
     LW,
+    LogAction,
 >;
@@ -1,9 +1,14 @@
-use crate::map::{MapReadAccess, MapWriteAccess};
-use crate::map_data::MapData;
+use crate::map::{MapReadAccess, MapWriteAccess, ObjectPlacementTile};
+use crate::map_data::{MapData, KNOWN_OBJECT_KINDS};
+use crate::script_repr::FromScriptRepr;
+use std::collections::HashSet;
 use std::{path::Path, vec::Vec};
 
 use super::grid_orientation::GridOrientation;
+use super::spawn_placement::spread_spawn_locations;
+use crate::maptile_logic::MaptileLogic;
 
+#[derive(Clone)]
 pub struct GridBattleMap<T> {
     width: usize,
     height: usize,
@@ -62,8 +67,14 @@ where
         }
     }
 
-    /// if map data can not represent grid map - error is returned
-    pub fn new_from_data(map_data: MapData<T>) -> Result<GridBattleMap<T>, ()> {
+    /// if map data can not represent grid map - error is returned,
+    /// that includes spawn points and objects outside of the map, spawns with bad orientation,
+    /// several spawns on one tile, objects of unknown kind,
+    /// and spawns or objects on tiles that can't hold them
+    pub fn new_from_data(map_data: MapData<T>) -> Result<GridBattleMap<T>, ()>
+    where
+        T: ObjectPlacementTile,
+    {
         let height = map_data.row_count();
         if height == 0 {
             return Err(());
@@ -77,11 +88,30 @@ where
                 return Err(());
             }
         }
-        Ok(GridBattleMap {
+        let map = GridBattleMap {
             width,
             height,
             map_data,
-        })
+        };
+        let mut spawn_positions = HashSet::new();
+        for spawn in map.map_data.spawns() {
+            if !map.is_within_bounds(spawn.x, spawn.y)
+                || GridOrientation::from_script_repr(&spawn.orientation).is_none()
+                || !map.get_tile_at(spawn.x, spawn.y).can_hold_objects()
+                || !spawn_positions.insert((spawn.x, spawn.y))
+            {
+                return Err(());
+            }
+        }
+        for object in map.map_data.objects() {
+            if !map.is_within_bounds(object.x, object.y)
+                || !KNOWN_OBJECT_KINDS.contains(&object.kind.as_str())
+                || !map.get_tile_at(object.x, object.y).can_hold_objects()
+            {
+                return Err(());
+            }
+        }
+        Ok(map)
     }
 
     pub fn map_data(&self) -> &MapData<T> {
@@ -89,10 +119,24 @@ where
     }
 
     // will fail if map cannot have "count" of player spawn places
+    // spawn points defined by the map are used first, otherwise players spawn in map corners
     pub fn get_spawn_locations(
         &self,
         count: usize,
     ) -> Result<Vec<(i64, i64, GridOrientation)>, ()> {
+        if !self.map_data.spawns().is_empty() {
+            if count == 0 || count > self.map_data.spawns().len() {
+                return Err(());
+            }
+            return self.map_data.spawns()[..count]
+                .iter()
+                .map(|spawn| {
+                    GridOrientation::from_script_repr(&spawn.orientation)
+                        .map(|ori| (spawn.x, spawn.y, ori))
+                        .ok_or(())
+                })
+                .collect();
+        }
         match count {
             0 => Err(()),
             1 => Ok(vec![(0, 0, GridOrientation::East)]),
@@ -123,7 +167,32 @@ where
                 ),
                 (self.width as i64 - 1, 0, GridOrientation::South),
             ]),
-            _ => Err(()), // FOR NOW we don't support more
+            _ => Err(()), // see get_spawn_locations_for for more
+        }
+    }
+
+    /// same as get_spawn_locations, but any number of players is spread over tiles passable by logic,
+    /// corners are used only while all of them are passable
+    pub fn get_spawn_locations_for<L>(
+        &self,
+        count: usize,
+        logic: &L,
+    ) -> Option<Vec<(i64, i64, GridOrientation)>>
+    where
+        L: MaptileLogic<T>,
+    {
+        if !self.map_data.spawns().is_empty() {
+            return self.get_spawn_locations(count).ok();
+        }
+        if count <= 4 {
+            let corners = self.get_spawn_locations(count).ok()?;
+            if corners
+                .iter()
+                .all(|&(x, y, _)| logic.passable(self.get_tile_at(x, y)))
+            {
+                return Some(corners);
+            }
         }
+        spread_spawn_locations(self, logic, count)
     }
 }
@@ -2,6 +2,7 @@ use std::{fs, io};
 
 use super::grid_map::GridBattleMap;
 use crate::serialization::{FromFile, ToFile};
+use crate::map::ObjectPlacementTile;
 use crate::map_data::MapData;
 
 use serde::de::DeserializeOwned;
@@ -10,7 +11,7 @@ use serde_json;
 
 impl<T> FromFile for GridBattleMap<T>
 where
-    T: Copy + Clone + DeserializeOwned,
+    T: Copy + Clone + DeserializeOwned + ObjectPlacementTile,
 {
     fn load_from_reader<R>(r: R) -> std::io::Result<Self>
     where
@@ -0,0 +1,333 @@
+//!
+//! plain text map format, one character per tile:
+//!
+//! ```text
+//! name: duel
+//! players: 2
+//! facing: east west
+//!
+//! 1..#..
+//! .~~#a.
+//! ..#..2
+//! ```
+//!
+//! optional `key: value` header lines come first: `name`, `author`, `players`,
+//! `outer` (tile character for everything outside the map, wall by default)
+//! and `facing` (orientations of spawns 1, 2, ... in order, by default spawns face the map center).
+//! lines starting with `;` are comments.
+//! tiles are `.` ground, `#` wall, `~` mud, `%` `+` `-` brick wall with 3, 2 and 1 hit points left,
+//! `,` grass, `:` tracks, `_` thin ice and `=` water.
+//! `1`..`9` are player spawns, `a` is an ammo crate and `h` is a health crate, all standing on ground.
+//!
+
+use std::io;
+use std::path::Path;
+
+use super::grid_map::GridBattleMap;
+use super::grid_orientation::GridOrientation;
+use super::spawn_placement::facing_center;
+use super::tile_types::TileType;
+use crate::log_data::LogRepresentable;
+use crate::map::MapReadAccess;
+use crate::map_data::{MapData, ObjectPlacement, SpawnPoint};
+use crate::script_repr::FromScriptRepr;
+use crate::serialization::{FromFile, ToFile};
+
+/// tile characters indexed by tile code
+const TILE_CHARS: [char; 10] = ['.', '#', '~', '%', '+', '-', ',', ':', '_', '='];
+
+/// text map object characters and object kinds they stand for
+const OBJECT_CHARS: [(char, &str); 2] = [('a', "ammocrate"), ('h', "healthcrate")];
+
+/// grid map saved and loaded in the plain text format
+#[derive(Clone)]
+pub struct TextGridBattleMap(pub GridBattleMap<TileType>);
+
+pub fn tile_char(tile: TileType) -> char {
+    TILE_CHARS[tile.code() as usize]
+}
+
+pub fn tile_from_char(c: char) -> Option<TileType> {
+    TILE_CHARS
+        .iter()
+        .position(|&x| x == c)
+        .and_then(|code| TileType::from_code(code as u8))
+}
+
+/// files with .txt or .map extension are treated as text maps, everything else as json
+pub fn is_text_map_path(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|x| x.to_str()),
+        Some("txt") | Some("map")
+    )
+}
+
+/// load map in the format detected by file extension
+pub fn load_map_file(path: &Path) -> io::Result<GridBattleMap<TileType>> {
+    if is_text_map_path(path) {
+        TextGridBattleMap::load_from_file(path).map(|x| x.0)
+    } else {
+        GridBattleMap::load_from_file(path)
+    }
+}
+
+/// save map in the format detected by file extension
+pub fn save_map_file(map: &GridBattleMap<TileType>, path: &Path) -> io::Result<()> {
+    if is_text_map_path(path) {
+        TextGridBattleMap(map.clone()).save_to_file(path)
+    } else {
+        map.save_to_file(path)
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+impl FromFile for TextGridBattleMap {
+    fn load_from_reader<R>(mut r: R) -> io::Result<Self>
+    where
+        R: io::Read,
+    {
+        let mut text = String::new();
+        r.read_to_string(&mut text)?;
+
+        let mut name = None;
+        let mut author = None;
+        let mut recommended_players = None;
+        let mut outer_value = TileType::Wall;
+        let mut facing = None;
+        let mut rows: Vec<Vec<TileType>> = Vec::new();
+        let mut spawns: Vec<(usize, i64, i64)> = Vec::new();
+        let mut objects = Vec::new();
+
+        for (line_num, line) in text.lines().enumerate() {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+            // rows never contain spaces, so anything with ": " is a header line
+            if let Some((key, value)) = line.split_once(": ") {
+                if !rows.is_empty() {
+                    return Err(invalid_data(format!(
+                        "line {}: header line after map rows",
+                        line_num + 1
+                    )));
+                }
+                let value = value.trim();
+                match key {
+                    "name" => name = Some(value.to_owned()),
+                    "author" => author = Some(value.to_owned()),
+                    "players" => {
+                        recommended_players = match value.parse::<usize>() {
+                            Ok(x) => Some(x),
+                            Err(e) => {
+                                return Err(invalid_data(format!("line {}: {}", line_num + 1, e)))
+                            }
+                        }
+                    }
+                    "outer" => {
+                        let mut chars = value.chars();
+                        outer_value = match (chars.next().and_then(tile_from_char), chars.next()) {
+                            (Some(x), None) => x,
+                            _ => {
+                                return Err(invalid_data(format!(
+                                    "line {}: outer must be a single tile character",
+                                    line_num + 1
+                                )))
+                            }
+                        }
+                    }
+                    "facing" => {
+                        let mut orientations = Vec::new();
+                        for word in value.split_whitespace() {
+                            match GridOrientation::from_script_repr(word) {
+                                Some(x) => orientations.push(x),
+                                None => {
+                                    return Err(invalid_data(format!(
+                                        "line {}: unknown orientation '{}'",
+                                        line_num + 1,
+                                        word
+                                    )))
+                                }
+                            }
+                        }
+                        facing = Some(orientations);
+                    }
+                    _ => {
+                        return Err(invalid_data(format!(
+                            "line {}: unknown header '{}'",
+                            line_num + 1,
+                            key
+                        )))
+                    }
+                }
+                continue;
+            }
+
+            let y = rows.len() as i64;
+            let mut row = Vec::with_capacity(line.len());
+            for (x, c) in line.chars().enumerate() {
+                let x = x as i64;
+                if let Some(tile) = tile_from_char(c) {
+                    row.push(tile);
+                } else if let Some(digit) = c.to_digit(10).filter(|&d| d > 0) {
+                    if spawns.iter().any(|&(n, _, _)| n == digit as usize) {
+                        return Err(invalid_data(format!("spawn {} is placed twice", digit)));
+                    }
+                    spawns.push((digit as usize, x, y));
+                    row.push(TileType::Ground);
+                } else if let Some(&(_, kind)) = OBJECT_CHARS.iter().find(|(oc, _)| *oc == c) {
+                    objects.push(ObjectPlacement {
+                        kind: kind.to_owned(),
+                        x,
+                        y,
+                    });
+                    row.push(TileType::Ground);
+                } else {
+                    return Err(invalid_data(format!(
+                        "line {}: unknown map character '{}'",
+                        line_num + 1,
+                        c
+                    )));
+                }
+            }
+            rows.push(row);
+        }
+
+        spawns.sort();
+        for (i, &(n, _, _)) in spawns.iter().enumerate() {
+            if n != i + 1 {
+                return Err(invalid_data(format!("spawn {} is missing", i + 1)));
+            }
+        }
+        let height = rows.len() as i64;
+        let width = rows.first().map(|row| row.len()).unwrap_or(0) as i64;
+        let center = (width - 1, height - 1);
+        let spawns = match facing {
+            Some(orientations) => {
+                if orientations.len() != spawns.len() {
+                    return Err(invalid_data(format!(
+                        "facing lists {} orientations for {} spawns",
+                        orientations.len(),
+                        spawns.len()
+                    )));
+                }
+                spawns
+                    .iter()
+                    .zip(orientations.iter())
+                    .map(|(&(_, x, y), ori)| (x, y, *ori))
+                    .collect::<Vec<_>>()
+            }
+            None => spawns
+                .iter()
+                .map(|&(_, x, y)| (x, y, facing_center(x, y, center)))
+                .collect(),
+        };
+
+        let row_refs: Vec<&[TileType]> = rows.iter().map(|row| row.as_slice()).collect();
+        let mut data = MapData::new_from_rows(&row_refs, outer_value);
+        data.set_name(name);
+        data.set_author(author);
+        data.set_recommended_players(recommended_players);
+        data.set_spawns(
+            spawns
+                .into_iter()
+                .map(|(x, y, ori)| SpawnPoint {
+                    x,
+                    y,
+                    orientation: ori.log_repr(),
+                })
+                .collect(),
+        );
+        data.set_objects(objects);
+
+        match GridBattleMap::new_from_data(data) {
+            Ok(x) => Ok(TextGridBattleMap(x)),
+            Err(_) => Err(invalid_data(
+                "map rows must be non-empty and of the same length".to_owned(),
+            )),
+        }
+    }
+}
+
+impl ToFile for TextGridBattleMap {
+    fn save_to_writer<W>(&self, mut w: W) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        let map = &self.0;
+        let data = map.map_data();
+        let mut rows: Vec<Vec<char>> = (0..data.row_count())
+            .map(|y| data.row(y).iter().map(|&tile| tile_char(tile)).collect())
+            .collect();
+
+        // spawns and objects take the whole cell, so they can only stand on free ground
+        let mut put = |x: i64, y: i64, c: char, what: String| {
+            if map.get_tile_at(x, y) != TileType::Ground || rows[y as usize][x as usize] != '.' {
+                return Err(invalid_data(format!(
+                    "{} at {},{} is not on a free ground tile and can not be saved as text",
+                    what, x, y
+                )));
+            }
+            rows[y as usize][x as usize] = c;
+            Ok(())
+        };
+        if data.spawns().len() > 9 {
+            return Err(invalid_data(
+                "text maps can not have more than 9 spawns".to_owned(),
+            ));
+        }
+        let mut orientations = Vec::new();
+        for (i, spawn) in data.spawns().iter().enumerate() {
+            let digit = char::from_digit(i as u32 + 1, 10).expect("spawn count is checked");
+            put(spawn.x, spawn.y, digit, format!("spawn {}", i + 1))?;
+            orientations.push(
+                GridOrientation::from_script_repr(&spawn.orientation)
+                    .expect("map validates spawn orientations")
+                    .log_repr(),
+            );
+        }
+        for object in data.objects() {
+            let c = match OBJECT_CHARS.iter().find(|(_, kind)| *kind == object.kind) {
+                Some(&(c, _)) => c,
+                None => {
+                    return Err(invalid_data(format!(
+                        "object kind '{}' can not be saved as text",
+                        object.kind
+                    )))
+                }
+            };
+            put(object.x, object.y, c, format!("object {}", object.kind))?;
+        }
+
+        let mut has_header = false;
+        if let Some(name) = data.name() {
+            writeln!(w, "name: {}", name)?;
+            has_header = true;
+        }
+        if let Some(author) = data.author() {
+            writeln!(w, "author: {}", author)?;
+            has_header = true;
+        }
+        if let Some(players) = data.recommended_players() {
+            writeln!(w, "players: {}", players)?;
+            has_header = true;
+        }
+        if data.outer_value() != TileType::Wall {
+            writeln!(w, "outer: {}", tile_char(data.outer_value()))?;
+            has_header = true;
+        }
+        if !orientations.is_empty() {
+            writeln!(w, "facing: {}", orientations.join(" "))?;
+            has_header = true;
+        }
+        if has_header {
+            writeln!(w)?;
+        }
+        for row in rows {
+            writeln!(w, "{}", row.into_iter().collect::<String>())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use super::log_events::{LogAction, LogEvent};
+use crate::log_data::{LogRepresentable, LogWriter};
+
+use std::io::Write;
+
+/// writes one json object per log line, see LogEvent for the fields
+pub struct JsonLinesLogWriter<W> {
+    buf: W,
+}
+
+impl<W, LRO> LogWriter<LRO, LogAction> for JsonLinesLogWriter<W>
+where
+    W: Write,
+    LRO: LogRepresentable,
+{
+    fn add_log_data(
+        &mut self,
+        object: LRO,
+        action: LogAction,
+        time: crate::gametime::GameTime,
+        duration: crate::gametime::GameTime,
+    ) {
+        let event = LogEvent::new(&object.to_log_repr(), action, time, duration);
+        if let Err(e) = serde_json::to_writer(&mut self.buf, &event) {
+            eprintln!("error writing log: {}", e);
+        };
+        if let Err(e) = writeln!(self.buf) {
+            eprintln!("error writing log: {}", e);
+        };
+        if let Err(e) = self.buf.flush() { // flush every line!
+            eprintln!("error flushing log: {}", e);
+        };
+    }
+}
+
+impl<W> JsonLinesLogWriter<W>
+where
+    W: Write,
+{
+    pub fn new(buffer: W) -> JsonLinesLogWriter<W> {
+        JsonLinesLogWriter {
+            buf: buffer,
+        }
+    }
+}
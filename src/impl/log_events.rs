@@ -0,0 +1,503 @@
+use crate::battle::ProgramExit;
+use crate::gametime::GameTime;
+use crate::log_data::{BattleLogAction, LogCommand, LogRepresentable};
+
+use serde::Serialize;
+
+/// object a log line is about, parsed from "player[name](uid)" or "kind(uid)"
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct LogObject {
+    #[serde(rename = "object_kind")]
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uid: Option<u64>,
+}
+
+/// actions written to the log by Battle and SimpleBattleLogic
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", rename_all = "kebab-case")]
+pub enum LogAction {
+    Spawn {
+        x: i64,
+        y: i64,
+        orientation: String,
+    },
+    /// team of a player, logged right after its spawn
+    Team {
+        team: usize,
+    },
+    Move {
+        x: i64,
+        y: i64,
+    },
+    Turn {
+        orientation: String,
+    },
+    Shoot {
+        x: i64,
+        y: i64,
+        hit_x: i64,
+        hit_y: i64,
+    },
+    Dying,
+    Die,
+    Win,
+    Picked,
+    Break,
+    Log {
+        text: String,
+    },
+    /// radio message sent to allies
+    Radio {
+        text: String,
+    },
+    /// map tile changed into tile given by its map code
+    TileChange {
+        x: i64,
+        y: i64,
+        tile: String,
+    },
+    ThinkOverrun {
+        steps: u64,
+    },
+    /// player program ended: finished, error, compile-error, think-timeout or stopped,
+    /// errors come with the last line of their message
+    Exit {
+        reason: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    /// "-command[argument](id)", logged with full command duration
+    CommandStart {
+        command: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        argument: Option<String>,
+        command_id: usize,
+    },
+    /// "+command[argument](id)" or "!command[argument](id)" if command failed
+    CommandFinish {
+        command: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        argument: Option<String>,
+        command_id: usize,
+        success: bool,
+    },
+    /// anything this parser does not know about
+    Unknown {
+        raw: String,
+    },
+}
+
+/// single typed log line
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct LogEvent {
+    pub time: GameTime,
+    pub duration: GameTime,
+    #[serde(flatten)]
+    pub object: LogObject,
+    #[serde(flatten)]
+    pub action: LogAction,
+}
+
+impl LogEvent {
+    pub fn new(object: &str, action: LogAction, time: GameTime, duration: GameTime) -> LogEvent {
+        LogEvent {
+            time,
+            duration,
+            object: LogObject::parse(object),
+            action,
+        }
+    }
+
+    /// event from text log line parts, see LogAction::parse
+    pub fn from_parts(object: &str, action: &str, time: GameTime, duration: GameTime) -> LogEvent {
+        LogEvent {
+            time,
+            duration,
+            object: LogObject::parse(object),
+            action: LogAction::parse(action),
+        }
+    }
+}
+
+impl LogObject {
+    pub fn parse(repr: &str) -> LogObject {
+        let (base, uid) = split_trailing_id(repr);
+        let uid = uid.map(|x| x as u64);
+        match base.find('[') {
+            Some(i) if base.ends_with(']') => LogObject {
+                kind: base[..i].to_owned(),
+                name: Some(base[i + 1..base.len() - 1].to_owned()),
+                uid,
+            },
+            _ => LogObject {
+                kind: base.to_owned(),
+                name: None,
+                uid,
+            },
+        }
+    }
+}
+
+impl LogAction {
+    pub fn parse(repr: &str) -> LogAction {
+        Self::try_parse(repr).unwrap_or_else(|| LogAction::Unknown {
+            raw: repr.to_owned(),
+        })
+    }
+
+    fn try_parse(repr: &str) -> Option<LogAction> {
+        if let Some(command) = repr.strip_prefix('-') {
+            let (command, argument, command_id) = split_command(command)?;
+            return Some(LogAction::CommandStart {
+                command,
+                argument,
+                command_id,
+            });
+        }
+        if let Some((command, success)) = repr
+            .strip_prefix('+')
+            .map(|x| (x, true))
+            .or_else(|| repr.strip_prefix('!').map(|x| (x, false)))
+        {
+            let (command, argument, command_id) = split_command(command)?;
+            return Some(LogAction::CommandFinish {
+                command,
+                argument,
+                command_id,
+                success,
+            });
+        }
+
+        let (name, argument) = split_argument(repr);
+        let action = match (name, argument) {
+            ("spawn", Some(arg)) => {
+                let mut parts = arg.split(',');
+                let x = parts.next()?.parse().ok()?;
+                let y = parts.next()?.parse().ok()?;
+                let orientation = parts.next()?.to_owned();
+                LogAction::Spawn { x, y, orientation }
+            }
+            ("team", Some(arg)) => LogAction::Team {
+                team: arg.parse().ok()?,
+            },
+            ("move", Some(arg)) => {
+                let [x, y] = parse_numbers(arg)?;
+                LogAction::Move { x, y }
+            }
+            ("turn", Some(arg)) => LogAction::Turn {
+                orientation: arg.to_owned(),
+            },
+            ("shoot", Some(arg)) => {
+                let [x, y, hit_x, hit_y] = parse_numbers(arg)?;
+                LogAction::Shoot { x, y, hit_x, hit_y }
+            }
+            ("log", Some(arg)) => LogAction::Log {
+                text: arg.to_owned(),
+            },
+            ("radio", Some(arg)) => LogAction::Radio {
+                text: arg.to_owned(),
+            },
+            ("tile-change", Some(arg)) => {
+                let mut parts = arg.splitn(3, ',');
+                let x = parts.next()?.parse().ok()?;
+                let y = parts.next()?.parse().ok()?;
+                let tile = parts.next()?.to_owned();
+                LogAction::TileChange { x, y, tile }
+            }
+            ("think-overrun", Some(arg)) => LogAction::ThinkOverrun {
+                steps: arg.parse().ok()?,
+            },
+            ("exit", Some(arg)) => {
+                let mut parts = arg.splitn(2, ',');
+                LogAction::Exit {
+                    reason: parts.next()?.to_owned(),
+                    message: parts.next().map(|x| x.to_owned()),
+                }
+            }
+            ("dying", None) => LogAction::Dying,
+            ("die", None) => LogAction::Die,
+            ("win", None) => LogAction::Win,
+            ("picked", None) => LogAction::Picked,
+            ("break", None) => LogAction::Break,
+            _ => return None,
+        };
+        Some(action)
+    }
+}
+
+impl LogRepresentable for LogAction {
+    /// text log form, LogAction::parse reads it back
+    fn log_repr(&self) -> String {
+        match self {
+            LogAction::Spawn { x, y, orientation } => format!("spawn[{},{},{}]", x, y, orientation),
+            LogAction::Team { team } => format!("team[{}]", team),
+            LogAction::Move { x, y } => format!("move[{},{}]", x, y),
+            LogAction::Turn { orientation } => format!("turn[{}]", orientation),
+            LogAction::Shoot { x, y, hit_x, hit_y } => {
+                format!("shoot[{},{},{},{}]", x, y, hit_x, hit_y)
+            }
+            LogAction::Dying => "dying".to_owned(),
+            LogAction::Die => "die".to_owned(),
+            LogAction::Win => "win".to_owned(),
+            LogAction::Picked => "picked".to_owned(),
+            LogAction::Break => "break".to_owned(),
+            LogAction::Log { text } => format!("log[{}]", text),
+            LogAction::Radio { text } => format!("radio[{}]", text),
+            LogAction::TileChange { x, y, tile } => format!("tile-change[{},{},{}]", x, y, tile),
+            LogAction::ThinkOverrun { steps } => format!("think-overrun[{}]", steps),
+            LogAction::Exit { reason, message } => match message {
+                Some(message) => format!("exit[{},{}]", reason, message),
+                None => format!("exit[{}]", reason),
+            },
+            LogAction::CommandStart {
+                command,
+                argument,
+                command_id,
+            } => format!(
+                "-{}({})",
+                LogCommand::new(command, argument.clone()).log_repr(),
+                command_id
+            ),
+            LogAction::CommandFinish {
+                command,
+                argument,
+                command_id,
+                success,
+            } => format!(
+                "{}{}({})",
+                if *success { "+" } else { "!" },
+                LogCommand::new(command, argument.clone()).log_repr(),
+                command_id
+            ),
+            LogAction::Unknown { raw } => raw.clone(),
+        }
+    }
+}
+
+impl BattleLogAction for LogAction {
+    fn die() -> Self {
+        LogAction::Die
+    }
+
+    fn win() -> Self {
+        LogAction::Win
+    }
+
+    fn exit(program_exit: &ProgramExit) -> Self {
+        LogAction::Exit {
+            reason: program_exit.log_reason().to_owned(),
+            message: program_exit.log_message(),
+        }
+    }
+
+    fn think_overrun(steps: u64) -> Self {
+        LogAction::ThinkOverrun { steps }
+    }
+
+    fn command_start(command: LogCommand, command_id: usize) -> Self {
+        LogAction::CommandStart {
+            command: command.name,
+            argument: command.argument,
+            command_id,
+        }
+    }
+
+    fn command_finish(command: LogCommand, command_id: usize, success: bool) -> Self {
+        LogAction::CommandFinish {
+            command: command.name,
+            argument: command.argument,
+            command_id,
+            success,
+        }
+    }
+}
+
+/// "name[arg]" -> (name, Some(arg)), "name" -> (name, None)
+fn split_argument(repr: &str) -> (&str, Option<&str>) {
+    match repr.find('[') {
+        Some(i) if repr.ends_with(']') => (&repr[..i], Some(&repr[i + 1..repr.len() - 1])),
+        _ => (repr, None),
+    }
+}
+
+/// "name(123)" -> (name, Some(123))
+fn split_trailing_id(repr: &str) -> (&str, Option<usize>) {
+    if let Some(i) = repr.rfind('(') {
+        if repr.ends_with(')') {
+            if let Ok(id) = repr[i + 1..repr.len() - 1].parse() {
+                return (&repr[..i], Some(id));
+            }
+        }
+    }
+    (repr, None)
+}
+
+fn split_command(repr: &str) -> Option<(String, Option<String>, usize)> {
+    let (command, command_id) = split_trailing_id(repr);
+    let (command, argument) = split_argument(command);
+    Some((command.to_owned(), argument.map(|x| x.to_owned()), command_id?))
+}
+
+fn parse_numbers<const N: usize>(repr: &str) -> Option<[i64; N]> {
+    let mut ret = [0; N];
+    let mut parts = repr.split(',');
+    for val in ret.iter_mut() {
+        *val = parts.next()?.parse().ok()?;
+    }
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_objects() {
+        assert_eq!(
+            LogObject {
+                kind: "player".to_owned(),
+                name: Some("foo[1]".to_owned()),
+                uid: Some(12)
+            },
+            LogObject::parse("player[foo[1]](12)")
+        );
+        assert_eq!(
+            LogObject {
+                kind: "ammocrate".to_owned(),
+                name: None,
+                uid: Some(3)
+            },
+            LogObject::parse("ammocrate(3)")
+        );
+    }
+
+    #[test]
+    fn parse_actions() {
+        assert_eq!(
+            LogAction::Shoot {
+                x: 1,
+                y: 2,
+                hit_x: 1,
+                hit_y: -1
+            },
+            LogAction::parse("shoot[1,2,1,-1]")
+        );
+        assert_eq!(
+            LogAction::CommandStart {
+                command: "move-forward".to_owned(),
+                argument: None,
+                command_id: 12
+            },
+            LogAction::parse("-move-forward(12)")
+        );
+        assert_eq!(
+            LogAction::CommandFinish {
+                command: "look".to_owned(),
+                argument: Some("east".to_owned()),
+                command_id: 5,
+                success: false,
+            },
+            LogAction::parse("!look[east](5)")
+        );
+        assert_eq!(
+            LogAction::Log {
+                text: "a [b] c".to_owned()
+            },
+            LogAction::parse("log[a [b] c]")
+        );
+        assert_eq!(LogAction::Team { team: 2 }, LogAction::parse("team[2]"));
+        assert_eq!(
+            LogAction::Radio {
+                text: "go [left]".to_owned()
+            },
+            LogAction::parse("radio[go [left]]")
+        );
+        assert_eq!(
+            LogAction::TileChange {
+                x: 3,
+                y: 4,
+                tile: "0".to_owned()
+            },
+            LogAction::parse("tile-change[3,4,0]")
+        );
+        assert_eq!(
+            LogAction::Exit {
+                reason: "error".to_owned(),
+                message: Some("ValueError: a, b".to_owned())
+            },
+            LogAction::parse("exit[error,ValueError: a, b]")
+        );
+        assert_eq!(
+            LogAction::Exit {
+                reason: "think-timeout".to_owned(),
+                message: None
+            },
+            LogAction::parse("exit[think-timeout]")
+        );
+        assert_eq!(LogAction::Die, LogAction::parse("die"));
+        assert_eq!(
+            LogAction::Unknown {
+                raw: "move[1]".to_owned()
+            },
+            LogAction::parse("move[1]")
+        );
+    }
+
+    #[test]
+    fn actions_round_trip() {
+        for repr in [
+            "spawn[1,2,north]",
+            "team[1]",
+            "move[-1,2]",
+            "turn[east]",
+            "shoot[1,2,1,-1]",
+            "dying",
+            "die",
+            "win",
+            "picked",
+            "break",
+            "log[a [b] c]",
+            "radio[go [left]]",
+            "tile-change[3,4,0]",
+            "think-overrun[120]",
+            "exit[error,ValueError: a, b]",
+            "exit[stopped]",
+            "-move-forward(12)",
+            "+add-ammo[17](4)",
+            "!look[east](5)",
+            "move[1]",
+        ] {
+            assert_eq!(repr, LogAction::parse(repr).log_repr());
+        }
+    }
+
+    #[test]
+    fn battle_actions_match_text_log() {
+        let exit = ProgramExit::Errored("Traceback\n  line 1\nValueError: a, b\n".to_owned());
+        assert_eq!(String::exit(&exit), LogAction::exit(&exit).log_repr());
+        let command = LogCommand::new("look", Some("east".to_owned()));
+        assert_eq!(
+            String::command_finish(command.clone(), 5, false),
+            LogAction::command_finish(command.clone(), 5, false).log_repr()
+        );
+        assert_eq!(
+            String::command_start(command.clone(), 5),
+            LogAction::command_start(command, 5).log_repr()
+        );
+    }
+
+    #[test]
+    fn serialize_event() {
+        let event = LogEvent::from_parts("player[p1](1)", "+add-ammo[17](4)", 12, 0);
+        assert_eq!(
+            r#"{"time":12,"duration":0,"object_kind":"player","name":"p1","uid":1,"action":"command-finish","command":"add-ammo","argument":"17","command_id":4,"success":true}"#,
+            serde_json::to_string(&event).unwrap()
+        );
+    }
+}
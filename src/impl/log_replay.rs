@@ -0,0 +1,241 @@
+use super::log_events::{LogAction, LogEvent};
+use crate::gametime::GameTime;
+
+use std::collections::BTreeMap;
+
+/// command an object is busy with
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReplayCommand {
+    pub command: String,
+    pub argument: Option<String>,
+    pub command_id: usize,
+    pub start: GameTime,
+    pub duration: GameTime,
+}
+
+/// state of a single object on the field
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReplayObject {
+    pub kind: String,
+    pub name: Option<String>,
+    pub x: i64,
+    pub y: i64,
+    pub orientation: String,
+    /// None for objects and players on their own
+    pub team: Option<usize>,
+    pub dying: bool,
+    pub dead: bool,
+    pub command: Option<ReplayCommand>,
+}
+
+/// shot fired at the current tick
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReplayShot {
+    pub shooter: u64,
+    pub x: i64,
+    pub y: i64,
+    pub hit_x: i64,
+    pub hit_y: i64,
+}
+
+/// world as reconstructed from the log at some game time.
+/// objects are keyed by uid, picked and broken objects are removed,
+/// dead players stay with dead flag set
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WorldState {
+    pub time: GameTime,
+    pub objects: BTreeMap<u64, ReplayObject>,
+    /// map tiles changed since the battle start, as (x, y) -> tile log repr
+    pub tiles: BTreeMap<(i64, i64), String>,
+    /// shots fired exactly at this tick
+    pub shots: Vec<ReplayShot>,
+    /// log[] messages written exactly at this tick
+    pub messages: Vec<(u64, String)>,
+    /// radio messages sent exactly at this tick
+    pub radio: Vec<(u64, String)>,
+    pub winners: Vec<u64>,
+}
+
+impl WorldState {
+    /// move to given time, forgetting what happened at the previous tick
+    fn start_tick(&mut self, time: GameTime) {
+        self.time = time;
+        self.shots.clear();
+        self.messages.clear();
+        self.radio.clear();
+    }
+
+    /// apply a single event, events without object uid are ignored, except map tile changes
+    pub fn apply(&mut self, event: &LogEvent) {
+        if let LogAction::TileChange { x, y, tile } = &event.action {
+            self.tiles.insert((*x, *y), tile.clone());
+            return;
+        }
+        let uid = if let Some(uid) = event.object.uid {
+            uid
+        } else {
+            return;
+        };
+        if let LogAction::Spawn { x, y, orientation } = &event.action {
+            self.objects.insert(
+                uid,
+                ReplayObject {
+                    kind: event.object.kind.clone(),
+                    name: event.object.name.clone(),
+                    x: *x,
+                    y: *y,
+                    orientation: orientation.clone(),
+                    team: None,
+                    dying: false,
+                    dead: false,
+                    command: None,
+                },
+            );
+            return;
+        }
+        let object = if let Some(object) = self.objects.get_mut(&uid) {
+            object
+        } else {
+            return;
+        };
+        match &event.action {
+            LogAction::Team { team } => {
+                object.team = Some(*team);
+            }
+            LogAction::Move { x, y } => {
+                object.x = *x;
+                object.y = *y;
+            }
+            LogAction::Turn { orientation } => {
+                object.orientation = orientation.clone();
+            }
+            LogAction::Shoot { x, y, hit_x, hit_y } => {
+                self.shots.push(ReplayShot {
+                    shooter: uid,
+                    x: *x,
+                    y: *y,
+                    hit_x: *hit_x,
+                    hit_y: *hit_y,
+                });
+            }
+            LogAction::Dying => {
+                object.dying = true;
+            }
+            LogAction::Die => {
+                object.dead = true;
+                object.command = None;
+            }
+            LogAction::Win => {
+                self.winners.push(uid);
+            }
+            LogAction::Picked | LogAction::Break => {
+                self.objects.remove(&uid);
+            }
+            LogAction::Log { text } => {
+                self.messages.push((uid, text.clone()));
+            }
+            LogAction::Radio { text } => {
+                self.radio.push((uid, text.clone()));
+            }
+            LogAction::CommandStart {
+                command,
+                argument,
+                command_id,
+            } => {
+                object.command = Some(ReplayCommand {
+                    command: command.clone(),
+                    argument: argument.clone(),
+                    command_id: *command_id,
+                    start: event.time,
+                    duration: event.duration,
+                });
+            }
+            LogAction::CommandFinish { command_id, .. } => {
+                if object.command.as_ref().map(|x| x.command_id) == Some(*command_id) {
+                    object.command = None;
+                }
+            }
+            LogAction::Spawn { .. }
+            | LogAction::TileChange { .. }
+            | LogAction::ThinkOverrun { .. }
+            | LogAction::Exit { .. }
+            | LogAction::Unknown { .. } => {}
+        }
+    }
+}
+
+/// reconstructs world state of a battle from its log events
+pub struct LogReplay {
+    events: Vec<LogEvent>,
+}
+
+impl LogReplay {
+    pub fn new(mut events: Vec<LogEvent>) -> LogReplay {
+        // log is mostly, but not strictly, time ordered. keep order within same time
+        events.sort_by_key(|x| x.time);
+        LogReplay { events }
+    }
+
+    pub fn events(&self) -> &[LogEvent] {
+        &self.events
+    }
+
+    /// time of the last event
+    pub fn end_time(&self) -> GameTime {
+        self.events.last().map_or(0, |x| x.time)
+    }
+
+    /// world state after all events up to and including given time
+    pub fn state_at(&self, time: GameTime) -> WorldState {
+        let mut state = WorldState::default();
+        for event in self.events.iter().take_while(|x| x.time <= time) {
+            if event.time != state.time {
+                state.start_tick(event.time);
+            }
+            state.apply(event);
+        }
+        if state.time != time {
+            state.start_tick(time);
+        }
+        state
+    }
+
+    /// world state for every tick from 0 till end_time
+    pub fn ticks(&self) -> ReplayTicks<'_> {
+        ReplayTicks {
+            events: &self.events,
+            next_event: 0,
+            next_time: 0,
+            end_time: self.end_time(),
+            state: WorldState::default(),
+        }
+    }
+}
+
+pub struct ReplayTicks<'a> {
+    events: &'a [LogEvent],
+    next_event: usize,
+    next_time: GameTime,
+    end_time: GameTime,
+    state: WorldState,
+}
+
+impl Iterator for ReplayTicks<'_> {
+    type Item = WorldState;
+
+    fn next(&mut self) -> Option<WorldState> {
+        if self.next_time > self.end_time {
+            return None;
+        }
+        self.state.start_tick(self.next_time);
+        while let Some(event) = self.events.get(self.next_event) {
+            if event.time > self.next_time {
+                break;
+            }
+            self.state.apply(event);
+            self.next_event += 1;
+        }
+        self.next_time += 1;
+        Some(self.state.clone())
+    }
+}
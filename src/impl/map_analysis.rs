@@ -0,0 +1,294 @@
+use super::grid_map::GridBattleMap;
+use super::grid_orientation::GridOrientation;
+use crate::map::MapReadAccess;
+use crate::maptile_logic::MaptileLogic;
+
+use std::collections::VecDeque;
+
+/// what a player gets at its spawn point
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpawnReport {
+    pub x: i64,
+    pub y: i64,
+    pub orientation: GridOrientation,
+    pub passable: bool,
+    /// passable region the spawn is in, index into MapAnalysis::region_sizes
+    pub region: Option<usize>,
+    /// number of cells this spawn can be seen and shot from along the grid lines
+    pub exposure: usize,
+    /// path length to the closest reachable pickup
+    pub nearest_pickup: Option<usize>,
+    /// path length to the closest reachable other spawn
+    pub nearest_enemy: Option<usize>,
+}
+
+/// passable region no player can get into
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnreachableRegion {
+    pub size: usize,
+    /// top-left most cell of the region
+    pub cell: (i64, i64),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapAnalysis {
+    pub width: usize,
+    pub height: usize,
+    pub spawns: Vec<SpawnReport>,
+    pub region_sizes: Vec<usize>,
+    pub unreachable_regions: Vec<UnreachableRegion>,
+    /// 1.0 if the map looks the same rotated by 180 degrees or mirrored, lower the less it does
+    pub symmetry: f64,
+    /// 1.0 if all spawns have the same exposure and distances to pickups and enemies
+    pub fairness: f64,
+}
+
+impl MapAnalysis {
+    /// problems that make map unplayable: blocked spawns and spawns players can't reach each other from
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (i, spawn) in self.spawns.iter().enumerate() {
+            if !spawn.passable {
+                problems.push(format!(
+                    "spawn {} at {},{} is on an impassable tile",
+                    i, spawn.x, spawn.y
+                ));
+            }
+        }
+        // regions are all or nothing, so checking against the first passable spawn covers every pair
+        let mut passable_spawns = self.spawns.iter().enumerate().filter(|(_, x)| x.passable);
+        if let Some((first_i, first)) = passable_spawns.next() {
+            for (i, spawn) in passable_spawns {
+                if spawn.region != first.region {
+                    problems.push(format!(
+                        "spawn {} at {},{} can not be reached from spawn {} at {},{}",
+                        i, spawn.x, spawn.y, first_i, first.x, first.y
+                    ));
+                }
+            }
+        }
+        problems
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.problems().is_empty()
+    }
+}
+
+///
+/// analyze map for given spawn locations and pickup positions.
+/// movement and sight follow tile logic, objects are not taken into account
+///
+pub fn analyze_map<T, L>(
+    map: &GridBattleMap<T>,
+    logic: &L,
+    spawns: &[(i64, i64, GridOrientation)],
+    pickups: &[(i64, i64)],
+) -> MapAnalysis
+where
+    T: Copy + Clone,
+    L: MaptileLogic<T>,
+{
+    let height = map.map_data().row_count();
+    let width = if height > 0 {
+        map.map_data().row(0).len()
+    } else {
+        0
+    };
+    let passable =
+        |x: i64, y: i64| map.is_within_bounds(x, y) && logic.passable(map.get_tile_at(x, y));
+
+    // label passable regions
+    let mut regions: Vec<Option<usize>> = vec![None; width * height];
+    let mut region_sizes = Vec::new();
+    let mut region_cells = Vec::new();
+    for y in 0..height as i64 {
+        for x in 0..width as i64 {
+            if !passable(x, y) || regions[y as usize * width + x as usize].is_some() {
+                continue;
+            }
+            let region = region_sizes.len();
+            let distances = path_distances(width, height, (x, y), &passable);
+            let mut size = 0;
+            for (cell, distance) in regions.iter_mut().zip(distances.iter()) {
+                if distance.is_some() {
+                    *cell = Some(region);
+                    size += 1;
+                }
+            }
+            region_sizes.push(size);
+            region_cells.push((x, y));
+        }
+    }
+    let region_at = |x: i64, y: i64| {
+        if map.is_within_bounds(x, y) {
+            regions[y as usize * width + x as usize]
+        } else {
+            None
+        }
+    };
+
+    let spawn_reports: Vec<SpawnReport> = spawns
+        .iter()
+        .enumerate()
+        .map(|(i, &(x, y, orientation))| {
+            let distances = path_distances(width, height, (x, y), &passable);
+            let distance_to = |(tx, ty): (i64, i64)| {
+                if map.is_within_bounds(tx, ty) {
+                    distances[ty as usize * width + tx as usize]
+                } else {
+                    None
+                }
+            };
+            SpawnReport {
+                x,
+                y,
+                orientation,
+                passable: passable(x, y),
+                region: region_at(x, y),
+                exposure: exposure(map, logic, (x, y)),
+                nearest_pickup: pickups.iter().filter_map(|&p| distance_to(p)).min(),
+                nearest_enemy: spawns
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .filter_map(|(_, &(ex, ey, _))| distance_to((ex, ey)))
+                    .min(),
+            }
+        })
+        .collect();
+
+    let unreachable_regions = region_sizes
+        .iter()
+        .zip(region_cells.iter())
+        .enumerate()
+        .filter(|(region, _)| spawn_reports.iter().all(|s| s.region != Some(*region)))
+        .map(|(_, (&size, &cell))| UnreachableRegion { size, cell })
+        .collect();
+
+    let fairness = {
+        let ratios: Vec<f64> = [
+            spawn_reports
+                .iter()
+                .map(|s| Some(s.exposure))
+                .collect::<Vec<_>>(),
+            spawn_reports.iter().map(|s| s.nearest_pickup).collect(),
+            spawn_reports.iter().map(|s| s.nearest_enemy).collect(),
+        ]
+        .iter()
+        .filter(|values| values.iter().any(|x| x.is_some()))
+        .map(|values| min_max_ratio(values))
+        .collect();
+        if ratios.is_empty() {
+            1.0
+        } else {
+            ratios.iter().sum::<f64>() / ratios.len() as f64
+        }
+    };
+
+    MapAnalysis {
+        width,
+        height,
+        spawns: spawn_reports,
+        region_sizes,
+        unreachable_regions,
+        symmetry: symmetry(map, logic, width, height),
+        fairness,
+    }
+}
+
+/// breadth first path lengths from start over passable cells, None for unreachable
+fn path_distances<F>(
+    width: usize,
+    height: usize,
+    start: (i64, i64),
+    passable: &F,
+) -> Vec<Option<usize>>
+where
+    F: Fn(i64, i64) -> bool,
+{
+    let mut distances = vec![None; width * height];
+    if !passable(start.0, start.1) {
+        return distances;
+    }
+    distances[start.1 as usize * width + start.0 as usize] = Some(0);
+    let mut queue = VecDeque::from([(start, 0)]);
+    while let Some(((x, y), distance)) = queue.pop_front() {
+        for (nx, ny) in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+            if !passable(nx, ny) {
+                continue;
+            }
+            let cell = &mut distances[ny as usize * width + nx as usize];
+            if cell.is_none() {
+                *cell = Some(distance + 1);
+                queue.push_back(((nx, ny), distance + 1));
+            }
+        }
+    }
+    distances
+}
+
+/// cells along the four grid directions from which given cell is visible
+fn exposure<T, L>(map: &GridBattleMap<T>, logic: &L, (x, y): (i64, i64)) -> usize
+where
+    T: Copy + Clone,
+    L: MaptileLogic<T>,
+{
+    let mut count = 0;
+    for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+        let (mut cx, mut cy) = (x + dx, y + dy);
+        while map.is_within_bounds(cx, cy) && logic.seethroughable(map.get_tile_at(cx, cy)) {
+            count += 1;
+            cx += dx;
+            cy += dy;
+        }
+    }
+    count
+}
+
+/// smallest over largest value, missing values count as 0
+fn min_max_ratio(values: &[Option<usize>]) -> f64 {
+    let max = values.iter().map(|x| x.unwrap_or(0)).max().unwrap_or(0);
+    let min = values.iter().map(|x| x.unwrap_or(0)).min().unwrap_or(0);
+    if max == 0 {
+        1.0
+    } else {
+        min as f64 / max as f64
+    }
+}
+
+/// best share of cells that behave the same as their image under 180 degree rotation or mirroring
+fn symmetry<T, L>(map: &GridBattleMap<T>, logic: &L, width: usize, height: usize) -> f64
+where
+    T: Copy + Clone,
+    L: MaptileLogic<T>,
+{
+    if width == 0 || height == 0 {
+        return 1.0;
+    }
+    let (w, h) = (width as i64, height as i64);
+    let behaviour = |x: i64, y: i64| {
+        let tile = map.get_tile_at(x, y);
+        (logic.passable(tile), logic.seethroughable(tile))
+    };
+    let images: [&dyn Fn(i64, i64) -> (i64, i64); 3] = [
+        &|x, y| (w - 1 - x, h - 1 - y),
+        &|x, y| (w - 1 - x, y),
+        &|x, y| (x, h - 1 - y),
+    ];
+    images
+        .iter()
+        .map(|image| {
+            let mut same = 0;
+            for y in 0..h {
+                for x in 0..w {
+                    let (ix, iy) = image(x, y);
+                    if behaviour(x, y) == behaviour(ix, iy) {
+                        same += 1;
+                    }
+                }
+            }
+            same as f64 / (width * height) as f64
+        })
+        .fold(0.0, f64::max)
+}
@@ -0,0 +1,404 @@
+use super::grid_map::GridBattleMap;
+use super::tile_types::TileType;
+use crate::map::{MapReadAccess, MapWriteAccess};
+
+use rand::prelude::*;
+use std::collections::VecDeque;
+
+/// how walls are laid out
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MapGenMode {
+    /// scattered single walls
+    Random,
+    /// cellular automaton caves
+    Caves,
+    /// maze with some extra passages, so there are loops
+    Maze,
+    /// rectangular rooms connected by corridors
+    Rooms,
+}
+
+/// symmetry of generated map, so every spawn gets the same surroundings
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MapSymmetry {
+    None,
+    /// left half mirrored to the right, for 2 players
+    Mirror,
+    /// rotated by 180 degrees around the center, for 2 players
+    Rotational,
+    /// mirrored both ways, for 4 players
+    Mirror4,
+    /// rotated by 90 degrees, for 4 players. non square maps get 180 degrees rotation
+    Rotational4,
+}
+
+const RANDOM_WALL_SHARE: f64 = 0.07;
+const CAVE_INITIAL_WALL_CHANCE: f64 = 0.45;
+const CAVE_SMOOTHING_STEPS: usize = 4;
+// share of maze walls between passages that are knocked out
+const MAZE_BRAID_CHANCE: f64 = 0.15;
+const ROOM_MIN_SIZE: usize = 3;
+const ROOM_MAX_SIZE: usize = 7;
+const ROOM_PLACEMENT_TRIES: usize = 50;
+const MUD_PATCH_MAX_RADIUS: i64 = 2;
+
+///
+/// generates maps of TileType tiles.
+/// same settings and seed always give the same map,
+/// every passable cell of a generated map is reachable from every other,
+/// and map corners, where up to 4 players spawn, are always free
+///
+pub struct MapGenerator {
+    width: usize,
+    height: usize,
+    seed: u64,
+    mode: MapGenMode,
+    symmetry: MapSymmetry,
+    mud_share: f64,
+    opened: bool,
+}
+
+impl MapGenerator {
+    pub fn new(width: usize, height: usize, seed: u64) -> MapGenerator {
+        MapGenerator {
+            width,
+            height,
+            seed,
+            mode: MapGenMode::Random,
+            symmetry: MapSymmetry::None,
+            mud_share: 0.0,
+            opened: false,
+        }
+    }
+
+    pub fn set_mode(&mut self, mode: MapGenMode) {
+        self.mode = mode;
+    }
+
+    pub fn set_symmetry(&mut self, symmetry: MapSymmetry) {
+        self.symmetry = symmetry;
+    }
+
+    /// about this share of ground is turned into mud patches
+    pub fn set_mud_share(&mut self, mud_share: f64) {
+        self.mud_share = mud_share;
+    }
+
+    /// opened maps have ground instead of walls beyond their borders
+    pub fn set_opened(&mut self, opened: bool) {
+        self.opened = opened;
+    }
+
+    pub fn generate(&self) -> GridBattleMap<TileType> {
+        let mut map = GridBattleMap::new(
+            self.width,
+            self.height,
+            TileType::Ground,
+            if self.opened {
+                TileType::Ground
+            } else {
+                TileType::Wall
+            },
+        );
+        if self.width == 0 || self.height == 0 {
+            return map;
+        }
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        match self.mode {
+            MapGenMode::Random => self.scatter_walls(&mut map, &mut rng),
+            MapGenMode::Caves => self.dig_caves(&mut map, &mut rng),
+            MapGenMode::Maze => self.dig_maze(&mut map, &mut rng),
+            MapGenMode::Rooms => self.dig_rooms(&mut map, &mut rng),
+        }
+        self.symmetrize(&mut map);
+        self.spill_mud(&mut map, &mut rng);
+        let (w, h) = (self.width as i64, self.height as i64);
+        for (x, y) in [(0, 0), (w - 1, 0), (0, h - 1), (w - 1, h - 1)] {
+            self.carve(&mut map, x, y);
+        }
+        self.connect_regions(&mut map);
+        map
+    }
+
+    fn scatter_walls(&self, map: &mut GridBattleMap<TileType>, rng: &mut StdRng) {
+        for _ in 0..((self.height * self.width) as f64 * RANDOM_WALL_SHARE) as usize {
+            let x: i64 = rng.gen_range(0..self.width).try_into().unwrap();
+            let y: i64 = rng.gen_range(0..self.height).try_into().unwrap();
+
+            map.set_tile_at(x, y, TileType::Wall);
+        }
+    }
+
+    fn dig_caves(&self, map: &mut GridBattleMap<TileType>, rng: &mut StdRng) {
+        let (w, h) = (self.width as i64, self.height as i64);
+        for y in 0..h {
+            for x in 0..w {
+                if rng.gen_bool(CAVE_INITIAL_WALL_CHANCE) {
+                    map.set_tile_at(x, y, TileType::Wall);
+                }
+            }
+        }
+        for _ in 0..CAVE_SMOOTHING_STEPS {
+            let previous = map.clone();
+            for y in 0..h {
+                for x in 0..w {
+                    // cells beyond the border count as walls
+                    let walls_around = (-1..=1)
+                        .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+                        .filter(|&(dx, dy)| (dx, dy) != (0, 0))
+                        .filter(|&(dx, dy)| {
+                            !previous.is_within_bounds(x + dx, y + dy)
+                                || is_wall(previous.get_tile_at(x + dx, y + dy))
+                        })
+                        .count();
+                    let wall = walls_around >= 5
+                        || walls_around >= 4 && is_wall(previous.get_tile_at(x, y));
+                    map.set_tile_at(
+                        x,
+                        y,
+                        if wall {
+                            TileType::Wall
+                        } else {
+                            TileType::Ground
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    fn dig_maze(&self, map: &mut GridBattleMap<TileType>, rng: &mut StdRng) {
+        let (w, h) = (self.width as i64, self.height as i64);
+        fill(map, TileType::Wall);
+        // passages are on even cells, walls between them get knocked out by backtracking walk
+        let mut visited = vec![false; self.width * self.height];
+        let mut stack = vec![(0, 0)];
+        visited[0] = true;
+        map.set_tile_at(0, 0, TileType::Ground);
+        while let Some(&(x, y)) = stack.last() {
+            let mut steps: Vec<(i64, i64)> = [(2, 0), (-2, 0), (0, 2), (0, -2)]
+                .into_iter()
+                .filter(|&(dx, dy)| {
+                    map.is_within_bounds(x + dx, y + dy)
+                        && !visited[((y + dy) * w + x + dx) as usize]
+                })
+                .collect();
+            if steps.is_empty() {
+                stack.pop();
+                continue;
+            }
+            steps.shuffle(rng);
+            let (dx, dy) = steps[0];
+            visited[((y + dy) * w + x + dx) as usize] = true;
+            map.set_tile_at(x + dx / 2, y + dy / 2, TileType::Ground);
+            map.set_tile_at(x + dx, y + dy, TileType::Ground);
+            stack.push((x + dx, y + dy));
+        }
+        // a perfect maze has a single path everywhere, loops make it playable
+        for y in 0..h {
+            for x in 0..w {
+                let between_passages = (x % 2 == 1) != (y % 2 == 1);
+                if between_passages && rng.gen_bool(MAZE_BRAID_CHANCE) {
+                    map.set_tile_at(x, y, TileType::Ground);
+                }
+            }
+        }
+    }
+
+    fn dig_rooms(&self, map: &mut GridBattleMap<TileType>, rng: &mut StdRng) {
+        fill(map, TileType::Wall);
+        let room_count = 1.max(self.width * self.height / 40);
+        let mut rooms: Vec<(usize, usize, usize, usize)> = Vec::new();
+        for _ in 0..ROOM_PLACEMENT_TRIES {
+            if rooms.len() >= room_count {
+                break;
+            }
+            let rw = rng.gen_range(ROOM_MIN_SIZE..=ROOM_MAX_SIZE).min(self.width);
+            let rh = rng
+                .gen_range(ROOM_MIN_SIZE..=ROOM_MAX_SIZE)
+                .min(self.height);
+            let rx = rng.gen_range(0..=self.width - rw);
+            let ry = rng.gen_range(0..=self.height - rh);
+            // keep at least one wall between rooms
+            let overlaps = rooms.iter().any(|&(ox, oy, ow, oh)| {
+                rx <= ox + ow && ox <= rx + rw && ry <= oy + oh && oy <= ry + rh
+            });
+            if overlaps {
+                continue;
+            }
+            for y in ry..ry + rh {
+                for x in rx..rx + rw {
+                    map.set_tile_at(x as i64, y as i64, TileType::Ground);
+                }
+            }
+            rooms.push((rx, ry, rw, rh));
+        }
+        let centers: Vec<(i64, i64)> = rooms
+            .iter()
+            .map(|&(x, y, w, h)| ((x + w / 2) as i64, (y + h / 2) as i64))
+            .collect();
+        for pair in centers.windows(2) {
+            let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+            for x in x0.min(x1)..=x0.max(x1) {
+                map.set_tile_at(x, y0, TileType::Ground);
+            }
+            for y in y0.min(y1)..=y0.max(y1) {
+                map.set_tile_at(x1, y, TileType::Ground);
+            }
+        }
+    }
+
+    fn spill_mud(&self, map: &mut GridBattleMap<TileType>, rng: &mut StdRng) {
+        if self.mud_share <= 0.0 {
+            return;
+        }
+        let target = ((self.width * self.height) as f64 * self.mud_share) as usize;
+        let mut mud = 0;
+        // patches may overlap or fall on walls, so give up after some tries
+        for _ in 0..target {
+            if mud >= target {
+                break;
+            }
+            let cx = rng.gen_range(0..self.width) as i64;
+            let cy = rng.gen_range(0..self.height) as i64;
+            let radius = rng.gen_range(0..=MUD_PATCH_MAX_RADIUS);
+            for y in cy - radius..=cy + radius {
+                for x in cx - radius..=cx + radius {
+                    if (x - cx).abs() + (y - cy).abs() > radius || !map.is_within_bounds(x, y) {
+                        continue;
+                    }
+                    for (ix, iy) in self.images(x, y) {
+                        if matches!(map.get_tile_at(ix, iy), TileType::Ground) {
+                            map.set_tile_at(ix, iy, TileType::Mud);
+                            mud += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// cells that must look the same as given cell
+    fn images(&self, x: i64, y: i64) -> Vec<(i64, i64)> {
+        let (w, h) = (self.width as i64, self.height as i64);
+        match self.symmetry {
+            MapSymmetry::None => vec![(x, y)],
+            MapSymmetry::Mirror => vec![(x, y), (w - 1 - x, y)],
+            MapSymmetry::Rotational => vec![(x, y), (w - 1 - x, h - 1 - y)],
+            MapSymmetry::Mirror4 => vec![
+                (x, y),
+                (w - 1 - x, y),
+                (x, h - 1 - y),
+                (w - 1 - x, h - 1 - y),
+            ],
+            MapSymmetry::Rotational4 if w == h => vec![
+                (x, y),
+                (w - 1 - y, x),
+                (w - 1 - x, h - 1 - y),
+                (y, h - 1 - x),
+            ],
+            MapSymmetry::Rotational4 => vec![(x, y), (w - 1 - x, h - 1 - y)],
+        }
+    }
+
+    /// every cell takes the tile of the first, in reading order, of its images
+    fn symmetrize(&self, map: &mut GridBattleMap<TileType>) {
+        let source = map.clone();
+        for y in 0..self.height as i64 {
+            for x in 0..self.width as i64 {
+                let (sx, sy) = self
+                    .images(x, y)
+                    .into_iter()
+                    .min_by_key(|&(ix, iy)| (iy, ix))
+                    .unwrap();
+                map.set_tile_at(x, y, source.get_tile_at(sx, sy));
+            }
+        }
+    }
+
+    /// make cell and its images passable
+    fn carve(&self, map: &mut GridBattleMap<TileType>, x: i64, y: i64) {
+        for (ix, iy) in self.images(x, y) {
+            if is_wall(map.get_tile_at(ix, iy)) {
+                map.set_tile_at(ix, iy, TileType::Ground);
+            }
+        }
+    }
+
+    /// dig shortest tunnels from the region of the first passable cell to every other region
+    fn connect_regions(&self, map: &mut GridBattleMap<TileType>) {
+        let (w, h) = (self.width as i64, self.height as i64);
+        let index = |x: i64, y: i64| (y * w + x) as usize;
+        let start = match (0..h)
+            .flat_map(|y| (0..w).map(move |x| (x, y)))
+            .find(|&(x, y)| !is_wall(map.get_tile_at(x, y)))
+        {
+            Some(x) => x,
+            None => return,
+        };
+        loop {
+            // main region first
+            let mut connected = vec![false; self.width * self.height];
+            let mut queue = VecDeque::from([start]);
+            connected[index(start.0, start.1)] = true;
+            while let Some((x, y)) = queue.pop_front() {
+                for (nx, ny) in neighbours(x, y) {
+                    if map.is_within_bounds(nx, ny)
+                        && !connected[index(nx, ny)]
+                        && !is_wall(map.get_tile_at(nx, ny))
+                    {
+                        connected[index(nx, ny)] = true;
+                        queue.push_back((nx, ny));
+                    }
+                }
+            }
+            // then walk through walls till any passable cell outside of it is found
+            let mut came_from: Vec<Option<(i64, i64)>> = vec![None; self.width * self.height];
+            let mut seen = connected.clone();
+            let mut queue: VecDeque<(i64, i64)> = (0..h)
+                .flat_map(|y| (0..w).map(move |x| (x, y)))
+                .filter(|&(x, y)| connected[index(x, y)])
+                .collect();
+            let mut found = None;
+            while let Some((x, y)) = queue.pop_front() {
+                if !connected[index(x, y)] && !is_wall(map.get_tile_at(x, y)) {
+                    found = Some((x, y));
+                    break;
+                }
+                for (nx, ny) in neighbours(x, y) {
+                    if map.is_within_bounds(nx, ny) && !seen[index(nx, ny)] {
+                        seen[index(nx, ny)] = true;
+                        came_from[index(nx, ny)] = Some((x, y));
+                        queue.push_back((nx, ny));
+                    }
+                }
+            }
+            let mut cell = match found {
+                Some(x) => x,
+                None => return,
+            };
+            while let Some(previous) = came_from[index(cell.0, cell.1)] {
+                self.carve(map, previous.0, previous.1);
+                cell = previous;
+            }
+        }
+    }
+}
+
+fn is_wall(tile: TileType) -> bool {
+    matches!(tile, TileType::Wall | TileType::BrickWall(_))
+}
+
+fn fill(map: &mut GridBattleMap<TileType>, tile: TileType) {
+    let height = map.map_data().row_count() as i64;
+    let width = map.map_data().row(0).len() as i64;
+    for y in 0..height {
+        for x in 0..width {
+            map.set_tile_at(x, y, tile);
+        }
+    }
+}
+
+fn neighbours(x: i64, y: i64) -> [(i64, i64); 4] {
+    [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]
+}
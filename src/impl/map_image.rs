@@ -0,0 +1,490 @@
+use super::grid_map::GridBattleMap;
+use super::log_replay::{LogReplay, ReplayShot, WorldState};
+use super::tile_types::{TileType, BRICK_WALL_HIT_POINTS};
+use crate::map::MapReadAccess;
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+pub type Rgb = [u8; 3];
+
+/// colors players are drawn with, in player order, repeating for more players
+pub const PLAYER_COLORS: [Rgb; 9] = [
+    [220, 40, 40],
+    [40, 90, 220],
+    [40, 170, 60],
+    [230, 150, 20],
+    [150, 60, 200],
+    [20, 180, 190],
+    [230, 90, 170],
+    [120, 120, 20],
+    [250, 250, 250],
+];
+
+const DEAD_COLOR: Rgb = [90, 90, 90];
+const HEAT_COLOR: Rgb = [255, 0, 0];
+const TRACER_COLOR: Rgb = [255, 235, 90];
+const BURST_COLOR: Rgb = [255, 130, 0];
+
+pub fn tile_color(tile: TileType) -> Rgb {
+    match tile {
+        TileType::Ground => [205, 195, 160],
+        TileType::Mud => [125, 95, 55],
+        TileType::Wall => [55, 55, 60],
+        TileType::BrickWall(hp) => {
+            // damaged bricks get lighter
+            let lost = BRICK_WALL_HIT_POINTS.saturating_sub(hp) as u32 * 30;
+            [
+                (160 + lost).min(255) as u8,
+                (65 + lost) as u8,
+                (45 + lost) as u8,
+            ]
+        }
+        TileType::Grass => [105, 165, 70],
+        TileType::Tracks => [150, 145, 95],
+        TileType::ThinIce => [195, 225, 240],
+        TileType::Water => [45, 105, 195],
+    }
+}
+
+fn object_color(kind: &str) -> Rgb {
+    match kind {
+        "ammocrate" => [225, 185, 40],
+        "healthcrate" => [240, 240, 240],
+        _ => [160, 160, 160],
+    }
+}
+
+fn player_color(player_i: usize) -> Rgb {
+    PLAYER_COLORS[player_i % PLAYER_COLORS.len()]
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Shape {
+    Rect {
+        x: f64,
+        y: f64,
+        w: f64,
+        h: f64,
+        color: Rgb,
+        opacity: f64,
+    },
+    Circle {
+        x: f64,
+        y: f64,
+        r: f64,
+        color: Rgb,
+    },
+    Triangle {
+        points: [(f64, f64); 3],
+        color: Rgb,
+    },
+}
+
+impl Shape {
+    /// pixel bounds (x0, y0, x1, y1) the shape may cover, x1 and y1 exclusive
+    fn bounds(&self) -> (f64, f64, f64, f64) {
+        match self {
+            Shape::Rect { x, y, w, h, .. } => (*x, *y, x + w, y + h),
+            Shape::Circle { x, y, r, .. } => (x - r, y - r, x + r, y + r),
+            Shape::Triangle { points, .. } => {
+                let xs = points.iter().map(|p| p.0);
+                let ys = points.iter().map(|p| p.1);
+                (
+                    xs.clone().fold(f64::MAX, f64::min),
+                    ys.clone().fold(f64::MAX, f64::min),
+                    xs.fold(f64::MIN, f64::max),
+                    ys.fold(f64::MIN, f64::max),
+                )
+            }
+        }
+    }
+
+    fn contains(&self, px: f64, py: f64) -> bool {
+        match self {
+            Shape::Rect { x, y, w, h, .. } => px >= *x && px < x + w && py >= *y && py < y + h,
+            Shape::Circle { x, y, r, .. } => (px - x).powi(2) + (py - y).powi(2) <= r * r,
+            Shape::Triangle { points, .. } => {
+                let side = |a: (f64, f64), b: (f64, f64)| {
+                    (b.0 - a.0) * (py - a.1) - (b.1 - a.1) * (px - a.0)
+                };
+                let s = [
+                    side(points[0], points[1]),
+                    side(points[1], points[2]),
+                    side(points[2], points[0]),
+                ];
+                s.iter().all(|&x| x >= 0.0) || s.iter().all(|&x| x <= 0.0)
+            }
+        }
+    }
+
+    fn color(&self) -> (Rgb, f64) {
+        match self {
+            Shape::Rect { color, opacity, .. } => (*color, *opacity),
+            Shape::Circle { color, .. } | Shape::Triangle { color, .. } => (*color, 1.0),
+        }
+    }
+}
+
+/// rendered map picture that can be saved as svg or png
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapImage {
+    width: u32,
+    height: u32,
+    shapes: Vec<Shape>,
+}
+
+impl MapImage {
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn to_svg(&self) -> String {
+        let hex = |c: Rgb| format!("#{:02x}{:02x}{:02x}", c[0], c[1], c[2]);
+        let mut ret = String::new();
+        let _ = writeln!(
+            ret,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+            self.width, self.height
+        );
+        for shape in self.shapes.iter() {
+            let _ = match shape {
+                Shape::Rect {
+                    x,
+                    y,
+                    w,
+                    h,
+                    color,
+                    opacity,
+                } => {
+                    if *opacity < 1.0 {
+                        writeln!(
+                            ret,
+                            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" fill-opacity="{:.3}"/>"#,
+                            x,
+                            y,
+                            w,
+                            h,
+                            hex(*color),
+                            opacity
+                        )
+                    } else {
+                        writeln!(
+                            ret,
+                            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                            x,
+                            y,
+                            w,
+                            h,
+                            hex(*color)
+                        )
+                    }
+                }
+                Shape::Circle { x, y, r, color } => writeln!(
+                    ret,
+                    r#"<circle cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+                    x,
+                    y,
+                    r,
+                    hex(*color)
+                ),
+                Shape::Triangle { points, color } => writeln!(
+                    ret,
+                    r#"<polygon points="{},{} {},{} {},{}" fill="{}"/>"#,
+                    points[0].0,
+                    points[0].1,
+                    points[1].0,
+                    points[1].1,
+                    points[2].0,
+                    points[2].1,
+                    hex(*color)
+                ),
+            };
+        }
+        ret.push_str("</svg>\n");
+        ret
+    }
+
+    /// rgb pixels row by row, every pixel takes the color of shapes covering its center
+    pub fn to_rgb(&self) -> Vec<u8> {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let mut pixels = vec![0u8; width * height * 3];
+        for shape in self.shapes.iter() {
+            let (x0, y0, x1, y1) = shape.bounds();
+            let (color, opacity) = shape.color();
+            let px_range = |a: f64, b: f64, limit: usize| {
+                (a.floor().max(0.0) as usize)..(b.ceil().max(0.0) as usize).min(limit)
+            };
+            for py in px_range(y0, y1, height) {
+                for px in px_range(x0, x1, width) {
+                    if !shape.contains(px as f64 + 0.5, py as f64 + 0.5) {
+                        continue;
+                    }
+                    let pixel = &mut pixels[(py * width + px) * 3..(py * width + px) * 3 + 3];
+                    for (channel, value) in pixel.iter_mut().zip(color.iter()) {
+                        *channel = (*channel as f64 * (1.0 - opacity) + *value as f64 * opacity)
+                            .round() as u8;
+                    }
+                }
+            }
+        }
+        pixels
+    }
+
+    pub fn write_png<W>(&self, w: W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        let mut encoder = png::Encoder::new(w, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.to_rgb())?;
+        Ok(())
+    }
+
+    /// save as svg if path ends with .svg, png otherwise
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let file = BufWriter::new(fs::File::create(path)?);
+        if path.extension().and_then(|x| x.to_str()) == Some("svg") {
+            let mut file = file;
+            file.write_all(self.to_svg().as_bytes())?;
+            file.flush()
+        } else {
+            self.write_png(file)
+        }
+    }
+}
+
+/// number of ticks live players spent on every cell during the battle
+pub fn position_heatmap(replay: &LogReplay) -> BTreeMap<(i64, i64), u64> {
+    let mut heat = BTreeMap::new();
+    for state in replay.ticks() {
+        for player in state
+            .objects
+            .values()
+            .filter(|x| x.kind == "player" && !x.dead)
+        {
+            *heat.entry((player.x, player.y)).or_insert(0) += 1;
+        }
+    }
+    heat
+}
+
+///
+/// draws map tiles, spawn points and map objects.
+/// with a world state set, tiles changed during battle, objects, tanks and shots of that state
+/// are drawn instead of spawn points and map objects. heatmap is drawn as red over the tiles
+///
+pub struct MapRenderer<'a> {
+    map: &'a GridBattleMap<TileType>,
+    cell_size: u32,
+    state: Option<&'a WorldState>,
+    heatmap: Option<&'a BTreeMap<(i64, i64), u64>>,
+}
+
+impl<'a> MapRenderer<'a> {
+    pub fn new(map: &'a GridBattleMap<TileType>) -> MapRenderer<'a> {
+        MapRenderer {
+            map,
+            cell_size: 16,
+            state: None,
+            heatmap: None,
+        }
+    }
+
+    /// size of a map cell in pixels, at least 1
+    pub fn set_cell_size(&mut self, cell_size: u32) {
+        self.cell_size = cell_size.max(1);
+    }
+
+    pub fn set_state(&mut self, state: Option<&'a WorldState>) {
+        self.state = state;
+    }
+
+    pub fn set_heatmap(&mut self, heatmap: Option<&'a BTreeMap<(i64, i64), u64>>) {
+        self.heatmap = heatmap;
+    }
+
+    pub fn render(&self) -> MapImage {
+        let data = self.map.map_data();
+        let height = data.row_count();
+        let width = if height > 0 { data.row(0).len() } else { 0 };
+        let cell = self.cell_size as f64;
+        let mut shapes = Vec::new();
+
+        let mut tiles: Vec<Vec<TileType>> = (0..height).map(|y| data.row(y).to_vec()).collect();
+        if let Some(state) = self.state {
+            for ((x, y), tile) in state.tiles.iter() {
+                let tile = tile.parse().ok().and_then(TileType::from_code);
+                if let (Some(tile), true) = (tile, self.map.is_within_bounds(*x, *y)) {
+                    tiles[*y as usize][*x as usize] = tile;
+                }
+            }
+        }
+        for (y, row) in tiles.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                shapes.push(Shape::Rect {
+                    x: x as f64 * cell,
+                    y: y as f64 * cell,
+                    w: cell,
+                    h: cell,
+                    color: tile_color(*tile),
+                    opacity: 1.0,
+                });
+            }
+        }
+
+        if let Some(heatmap) = self.heatmap {
+            let max = heatmap.values().copied().max().unwrap_or(0);
+            for (&(x, y), &count) in heatmap.iter() {
+                if max == 0 || count == 0 || !self.map.is_within_bounds(x, y) {
+                    continue;
+                }
+                shapes.push(Shape::Rect {
+                    x: x as f64 * cell,
+                    y: y as f64 * cell,
+                    w: cell,
+                    h: cell,
+                    color: HEAT_COLOR,
+                    opacity: 0.1 + 0.6 * count as f64 / max as f64,
+                });
+            }
+        }
+
+        let object_shape = |x: i64, y: i64, kind: &str| Shape::Rect {
+            x: (x as f64 + 0.25) * cell,
+            y: (y as f64 + 0.25) * cell,
+            w: cell * 0.5,
+            h: cell * 0.5,
+            color: object_color(kind),
+            opacity: 1.0,
+        };
+        match self.state {
+            Some(state) => {
+                for object in state.objects.values().filter(|x| x.kind != "player") {
+                    shapes.push(object_shape(object.x, object.y, &object.kind));
+                }
+                for (i, player) in state
+                    .objects
+                    .values()
+                    .filter(|x| x.kind == "player")
+                    .enumerate()
+                {
+                    let color = if player.dead {
+                        DEAD_COLOR
+                    } else {
+                        player_color(i)
+                    };
+                    let facing = if player.dead {
+                        None
+                    } else {
+                        Some(player.orientation.as_str())
+                    };
+                    if player.dying && !player.dead {
+                        shapes.push(Shape::Circle {
+                            x: (player.x as f64 + 0.5) * cell,
+                            y: (player.y as f64 + 0.5) * cell,
+                            r: cell * 0.5,
+                            color: BURST_COLOR,
+                        });
+                    }
+                    tank_shapes(&mut shapes, player.x, player.y, facing, color, cell);
+                }
+                for shot in state.shots.iter() {
+                    shot_shapes(&mut shapes, shot, cell);
+                }
+            }
+            None => {
+                for object in data.objects() {
+                    shapes.push(object_shape(object.x, object.y, &object.kind));
+                }
+                for (i, spawn) in data.spawns().iter().enumerate() {
+                    tank_shapes(
+                        &mut shapes,
+                        spawn.x,
+                        spawn.y,
+                        Some(&spawn.orientation),
+                        player_color(i),
+                        cell,
+                    );
+                }
+            }
+        }
+
+        MapImage {
+            width: width as u32 * self.cell_size,
+            height: height as u32 * self.cell_size,
+            shapes,
+        }
+    }
+}
+
+/// tracer from the shooter's cell center to the hit cell center with a burst at the end
+fn shot_shapes(shapes: &mut Vec<Shape>, shot: &ReplayShot, cell: f64) {
+    let center = |v: i64| (v as f64 + 0.5) * cell;
+    let thickness = (cell * 0.12).max(1.0);
+    let (x0, x1) = (
+        center(shot.x.min(shot.hit_x)),
+        center(shot.x.max(shot.hit_x)),
+    );
+    let (y0, y1) = (
+        center(shot.y.min(shot.hit_y)),
+        center(shot.y.max(shot.hit_y)),
+    );
+    shapes.push(Shape::Rect {
+        x: x0 - thickness / 2.0,
+        y: y0 - thickness / 2.0,
+        w: x1 - x0 + thickness,
+        h: y1 - y0 + thickness,
+        color: TRACER_COLOR,
+        opacity: 1.0,
+    });
+    shapes.push(Shape::Circle {
+        x: center(shot.hit_x),
+        y: center(shot.hit_y),
+        r: cell * 0.25,
+        color: BURST_COLOR,
+    });
+}
+
+/// circle in player color with a dark triangle pointing where the tank looks
+fn tank_shapes(
+    shapes: &mut Vec<Shape>,
+    x: i64,
+    y: i64,
+    facing: Option<&str>,
+    color: Rgb,
+    cell: f64,
+) {
+    let (cx, cy) = ((x as f64 + 0.5) * cell, (y as f64 + 0.5) * cell);
+    shapes.push(Shape::Circle {
+        x: cx,
+        y: cy,
+        r: cell * 0.4,
+        color,
+    });
+    let (dx, dy) = match facing {
+        Some("north") | Some("forward") => (0.0, -1.0),
+        Some("east") | Some("right") => (1.0, 0.0),
+        Some("south") | Some("back") => (0.0, 1.0),
+        Some("west") | Some("left") => (-1.0, 0.0),
+        _ => return,
+    };
+    let (tip, base) = (cell * 0.4, cell * 0.2);
+    shapes.push(Shape::Triangle {
+        points: [
+            (cx + dx * tip, cy + dy * tip),
+            (cx - dy * base, cy + dx * base),
+            (cx + dy * base, cy - dx * base),
+        ],
+        color: [color[0] / 3, color[1] / 3, color[2] / 3],
+    });
+}
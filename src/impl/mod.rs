@@ -1,16 +1,33 @@
+pub mod ascii_render;
+pub mod battle_batch;
 pub mod battle_maptile_logic;
+pub mod buf_battle_log_reader;
 pub mod buf_battle_logger;
+pub mod dry_run;
 mod timestamped_container;
 pub mod grid_battle;
 pub mod grid_map;
 pub mod grid_map_prober;
 pub mod grid_orientation;
 pub mod gridmap_json_file;
+pub mod gridmap_text_file;
+pub mod jsonl_battle_logger;
+pub mod log_events;
+pub mod log_replay;
+pub mod map_analysis;
+pub mod map_generator;
+pub mod map_image;
 pub mod player_gridmap_control;
+pub mod replay_export;
+pub mod script_api;
 pub mod simple_battle_logic;
 pub mod simple_battle_object_layer;
 pub mod simple_object;
+pub mod spawn_placement;
+pub mod standard_battle;
 pub mod tile_types;
 pub mod tile_types_logic;
 mod tile_types_serde;
+pub mod tournament;
 mod unique_id_counter;
+pub mod verify_log_writer;
@@ -2,7 +2,7 @@ use std::sync::atomic::Ordering;
 
 use crate::map_object::MapObject;
 use crate::player_state::PlayerControl;
-use crate::script_repr::ToScriptRepr;
+use crate::script_repr::{ToPlayerScriptRepr, ToScriptRepr};
 use crate::log_data::LogRepresentable;
 
 use super::unique_id_counter::NEXT_OBJID;
@@ -19,6 +19,7 @@ pub struct GridPlayerState {
     resources: Vec<u64>,
     last_hit_repr: u64,
     pub name: String,
+    pub team: Option<usize>,
     unique_id: u64,
 }
 
@@ -37,6 +38,7 @@ impl GridPlayerState {
             resources: init_resources,
             last_hit_repr: 0,
             name: name.to_owned(),
+            team: None,
             unique_id: NEXT_OBJID.fetch_add(1, Ordering::Relaxed),
         }
     }
@@ -104,6 +106,10 @@ impl PlayerControl for GridPlayerState
         let res = &mut self.resources[res_id];
         *res = amount;
     }
+
+    fn team(&self) -> Option<usize> {
+        self.team
+    }
 }
 
 impl GridPlayerState {
@@ -123,6 +129,7 @@ impl MapObject<GridOrientation> for GridPlayerState {
             resources: source.resources.clone(),
             last_hit_repr: source.last_hit_repr,
             name: source.name.clone(),
+            team: source.team,
             unique_id: new_uid,
         }
     }
@@ -142,7 +149,14 @@ impl MapObject<GridOrientation> for GridPlayerState {
 
 impl ToScriptRepr for GridPlayerState {
     fn to_script_repr(&self) -> String {
-        format!("player[{}]", self.name)
+        self.to_player_script_repr(false)
+    }
+}
+
+impl ToPlayerScriptRepr for GridPlayerState {
+    fn to_player_script_repr(&self, ally: bool) -> String {
+        let kind = if ally { "ally" } else { "player" };
+        format!("{}[{}]", kind, self.name)
     }
 }
 
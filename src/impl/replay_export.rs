@@ -0,0 +1,139 @@
+use super::grid_map::GridBattleMap;
+use super::log_replay::LogReplay;
+use super::map_image::{MapImage, MapRenderer};
+use super::tile_types::TileType;
+use crate::gametime::GameTime;
+
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+///
+/// turns a battle replay into animation frames, one frame every ticks_per_frame ticks.
+/// a frame shows the world at its time together with all shots fired since the previous frame,
+/// last frame is always the end of the battle
+///
+pub struct ReplayExporter<'a> {
+    map: &'a GridBattleMap<TileType>,
+    replay: &'a LogReplay,
+    cell_size: u32,
+    ticks_per_frame: GameTime,
+    frame_delay_ms: u32,
+}
+
+impl<'a> ReplayExporter<'a> {
+    pub fn new(map: &'a GridBattleMap<TileType>, replay: &'a LogReplay) -> ReplayExporter<'a> {
+        ReplayExporter {
+            map,
+            replay,
+            cell_size: 16,
+            ticks_per_frame: 1,
+            frame_delay_ms: 100,
+        }
+    }
+
+    /// size of a map cell in pixels, at least 1
+    pub fn set_cell_size(&mut self, cell_size: u32) {
+        self.cell_size = cell_size.max(1);
+    }
+
+    /// game ticks between frames, at least 1
+    pub fn set_ticks_per_frame(&mut self, ticks_per_frame: GameTime) {
+        self.ticks_per_frame = ticks_per_frame.max(1);
+    }
+
+    /// how long a frame is shown in animations
+    pub fn set_frame_delay_ms(&mut self, frame_delay_ms: u32) {
+        self.frame_delay_ms = frame_delay_ms;
+    }
+
+    /// game times of frames
+    pub fn frame_times(&self) -> Vec<GameTime> {
+        let end_time = self.replay.end_time();
+        let mut times: Vec<GameTime> = (0..=end_time)
+            .step_by(self.ticks_per_frame as usize)
+            .collect();
+        if times.last() != Some(&end_time) {
+            times.push(end_time);
+        }
+        times
+    }
+
+    pub fn frames(&self) -> Vec<MapImage> {
+        let mut frame_times = self.frame_times().into_iter().peekable();
+        let mut frames = Vec::new();
+        let mut shots = Vec::new();
+        for mut state in self.replay.ticks() {
+            shots.append(&mut state.shots);
+            if frame_times.peek() != Some(&state.time) {
+                continue;
+            }
+            frame_times.next();
+            state.shots = std::mem::take(&mut shots);
+            let mut renderer = MapRenderer::new(self.map);
+            renderer.set_cell_size(self.cell_size);
+            renderer.set_state(Some(&state));
+            frames.push(renderer.render());
+        }
+        frames
+    }
+
+    /// looping gif animation, last frame is held 2 seconds longer
+    pub fn write_gif<W>(&self, w: W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        let frames = self.frames();
+        let (width, height) = match frames.first() {
+            Some(x) => (x.width(), x.height()),
+            None => return Err(io::Error::other("battle log has no frames")),
+        };
+        let (width, height) = match (u16::try_from(width), u16::try_from(height)) {
+            (Ok(w), Ok(h)) => (w, h),
+            _ => {
+                return Err(io::Error::other(
+                    "image is too large for gif, use a smaller cell size",
+                ))
+            }
+        };
+        let mut encoder = gif::Encoder::new(w, width, height, &[]).map_err(io::Error::other)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(io::Error::other)?;
+        let delay = (self.frame_delay_ms / 10).min(u16::MAX as u32) as u16;
+        let frame_count = frames.len();
+        for (i, image) in frames.into_iter().enumerate() {
+            let mut frame = gif::Frame::from_rgb_speed(width, height, &image.to_rgb(), 10);
+            frame.delay = if i + 1 == frame_count {
+                delay.saturating_add(200)
+            } else {
+                delay
+            };
+            encoder.write_frame(&frame).map_err(io::Error::other)?;
+        }
+        Ok(())
+    }
+
+    /// write frames as frame_00000.png, frame_00001.png, ... into given directory, returns frame count
+    pub fn save_png_frames(&self, dir: &Path) -> io::Result<usize> {
+        fs::create_dir_all(dir)?;
+        let frames = self.frames();
+        for (i, image) in frames.iter().enumerate() {
+            let file = fs::File::create(dir.join(format!("frame_{:05}.png", i)))?;
+            image.write_png(BufWriter::new(file))?;
+        }
+        Ok(frames.len())
+    }
+
+    /// gif animation if path ends with .gif, otherwise png frames in a directory at path
+    pub fn save(&self, path: &Path) -> io::Result<usize> {
+        if path.extension().and_then(|x| x.to_str()) == Some("gif") {
+            let mut file = BufWriter::new(fs::File::create(path)?);
+            self.write_gif(&mut file)?;
+            file.flush()?;
+            Ok(self.frame_times().len())
+        } else {
+            self.save_png_frames(path)
+        }
+    }
+}
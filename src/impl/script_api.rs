@@ -0,0 +1,321 @@
+use super::simple_battle_logic::{CommandTimer, PlayerCommand};
+use crate::gametime::GameTime;
+
+use serde::Serialize;
+use std::fmt::Write;
+
+/// argument of a function available to player programs
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct ScriptArg {
+    pub name: &'static str,
+    /// python type annotation
+    pub py_type: &'static str,
+}
+
+/// function available to player programs
+#[derive(Clone, PartialEq, Eq)]
+pub struct ScriptFunction<R> {
+    pub name: &'static str,
+    /// other names the same function is available under
+    pub aliases: &'static [&'static str],
+    pub args: &'static [ScriptArg],
+    /// python return type annotation
+    pub returns: &'static str,
+    pub doc: &'static str,
+    /// game command the function issues, None if it takes no game time
+    pub command: Option<PlayerCommand<R>>,
+}
+
+///
+/// every function player programs can call, this is what initialize_scope registers.
+/// rand is added by the battle itself, independent of battle logic
+///
+pub fn script_functions<R>() -> Vec<ScriptFunction<R>>
+where
+    R: From<u64>,
+{
+    const NO_ARGS: &[ScriptArg] = &[];
+    vec![
+        ScriptFunction {
+            name: "turn_cw",
+            aliases: &["turn_right", "turn_r"],
+            args: NO_ARGS,
+            returns: "None",
+            doc: "turn 90 degrees clockwise",
+            command: Some(PlayerCommand::TurnCW),
+        },
+        ScriptFunction {
+            name: "turn_ccw",
+            aliases: &["turn_left", "turn_l"],
+            args: NO_ARGS,
+            returns: "None",
+            doc: "turn 90 degrees counterclockwise",
+            command: Some(PlayerCommand::TurnCCW),
+        },
+        ScriptFunction {
+            name: "move_forward",
+            aliases: &["move_fwd"],
+            args: NO_ARGS,
+            returns: "None",
+            doc: "move one cell forward, tank stays in place if the cell is taken or impassable",
+            command: Some(PlayerCommand::MoveFwd),
+        },
+        ScriptFunction {
+            name: "move_backward",
+            aliases: &["move_backwards", "move_back"],
+            args: NO_ARGS,
+            returns: "None",
+            doc: "move one cell back without turning, tank stays in place if the cell is taken or impassable",
+            command: Some(PlayerCommand::MoveBack),
+        },
+        ScriptFunction {
+            name: "shoot",
+            aliases: &["fire"],
+            args: NO_ARGS,
+            returns: "None",
+            doc: "shoot forward if there is ammo left, the gun then needs to cool down",
+            command: Some(PlayerCommand::Shoot),
+        },
+        ScriptFunction {
+            name: "wait",
+            aliases: &[],
+            args: NO_ARGS,
+            returns: "None",
+            doc: "do nothing for a while",
+            command: Some(PlayerCommand::Wait),
+        },
+        ScriptFunction {
+            name: "print",
+            aliases: &[],
+            args: &[ScriptArg {
+                name: "*args",
+                py_type: "object",
+            }],
+            returns: "None",
+            doc: "write arguments separated by spaces to the battle log, long lines are cut",
+            command: Some(PlayerCommand::Print(String::new())),
+        },
+        ScriptFunction {
+            name: "look",
+            aliases: &[],
+            args: &[ScriptArg {
+                name: "direction",
+                py_type: "str",
+            }],
+            returns: "list[tuple[str, str | None]]",
+            doc: "cells seen in direction \"forward\", \"right\", \"back\" or \"left\" of the tank, \
+                  nearest first, as (tile, object) pairs. \
+                  object is like \"player[front]\", \"ally[left-side]\" or \"ammocrate[back]\", \
+                  telling which side of it faces the tank",
+            command: Some(PlayerCommand::Look(R::from(0))),
+        },
+        ScriptFunction {
+            name: "listen",
+            aliases: &[],
+            args: NO_ARGS,
+            returns: "list[str]",
+            doc: "directions to recent sounds of moving, turning and shooting tanks, nearest first, \
+                  like \"front-left-side\" or \"back-right-along\"",
+            command: Some(PlayerCommand::Listen),
+        },
+        ScriptFunction {
+            name: "check_ammo",
+            aliases: &[],
+            args: NO_ARGS,
+            returns: "int",
+            doc: "shots left",
+            command: Some(PlayerCommand::CheckAmmo),
+        },
+        ScriptFunction {
+            name: "check_health",
+            aliases: &[],
+            args: NO_ARGS,
+            returns: "int",
+            doc: "health left",
+            command: Some(PlayerCommand::CheckHealth),
+        },
+        ScriptFunction {
+            name: "check_hit",
+            aliases: &[],
+            args: NO_ARGS,
+            returns: "str | None",
+            doc: "direction relative to the tank the last hit came from, None if not hit since last check",
+            command: Some(PlayerCommand::CheckHit),
+        },
+        ScriptFunction {
+            name: "reset_hit",
+            aliases: &[],
+            args: NO_ARGS,
+            returns: "None",
+            doc: "forget the last hit, faster than check_hit",
+            command: Some(PlayerCommand::ResetHit),
+        },
+        ScriptFunction {
+            name: "send_message",
+            aliases: &[],
+            args: &[ScriptArg {
+                name: "text",
+                py_type: "str",
+            }],
+            returns: "None",
+            doc: "radio text to living allies, it arrives after a short delay",
+            command: Some(PlayerCommand::SendMessage(String::new())),
+        },
+        ScriptFunction {
+            name: "receive_messages",
+            aliases: &[],
+            args: NO_ARGS,
+            returns: "list[tuple[str, str]]",
+            doc: "(sender, text) radio messages arrived since the last call",
+            command: Some(PlayerCommand::ReceiveMessages),
+        },
+        ScriptFunction {
+            name: "time",
+            aliases: &[],
+            args: NO_ARGS,
+            returns: "int",
+            doc: "current game time",
+            command: Some(PlayerCommand::Time),
+        },
+        ScriptFunction {
+            name: "rand",
+            aliases: &[],
+            args: NO_ARGS,
+            returns: "float",
+            doc: "random number from 0 to 1, the same sequence for the same battle seed",
+            command: None,
+        },
+    ]
+}
+
+/// aliases given function is registered under
+pub fn script_function_aliases(name: &str) -> &'static [&'static str] {
+    script_functions::<u64>()
+        .into_iter()
+        .find(|x| x.name == name)
+        .map_or(&[], |x| x.aliases)
+}
+
+/// name of the function that issues given command, None for commands programs cannot issue
+pub fn script_function_name<R>(command: &PlayerCommand<R>) -> Option<&'static str>
+where
+    R: From<u64>,
+{
+    let discriminant = std::mem::discriminant(command);
+    script_functions::<R>()
+        .into_iter()
+        .find(|x| {
+            x.command
+                .as_ref()
+                .is_some_and(|x| std::mem::discriminant(x) == discriminant)
+        })
+        .map(|x| x.name)
+}
+
+/// machine readable description of a function available to player programs
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ApiFunction {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub args: &'static [ScriptArg],
+    pub returns: &'static str,
+    pub doc: &'static str,
+    /// game time until the function returns, before tile speed modifiers, None if it takes no time
+    pub time: Option<GameTime>,
+    /// game time until the command takes effect, before tile speed modifiers
+    pub effect_time: Option<GameTime>,
+}
+
+/// describe script functions with game time costs given by timer
+pub fn describe_api<R, C>(timer: &C) -> Vec<ApiFunction>
+where
+    R: From<u64>,
+    C: CommandTimer<PlayerCommand<R>>,
+{
+    script_functions::<R>()
+        .into_iter()
+        .map(|f| {
+            let effect_time = f.command.as_ref().map(|x| timer.get_base_duration(x));
+            let time = f
+                .command
+                .as_ref()
+                .map(|x| timer.get_base_duration(x) + timer.get_reply_delay(x));
+            ApiFunction {
+                name: f.name,
+                aliases: f.aliases,
+                args: f.args,
+                returns: f.returns,
+                doc: f.doc,
+                time,
+                effect_time,
+            }
+        })
+        .collect()
+}
+
+pub fn api_to_json(api: &[ApiFunction]) -> String {
+    serde_json::to_string_pretty(api).expect("api description is always serializable") + "\n"
+}
+
+fn signature(name: &str, f: &ApiFunction) -> String {
+    let args: Vec<String> = f
+        .args
+        .iter()
+        .map(|a| format!("{}: {}", a.name, a.py_type))
+        .collect();
+    format!("{}({}) -> {}", name, args.join(", "), f.returns)
+}
+
+fn time_note(f: &ApiFunction) -> String {
+    match (f.time, f.effect_time) {
+        (Some(time), Some(effect_time)) if time != effect_time => {
+            format!("takes {} game time, acts after {}", time, effect_time)
+        }
+        (Some(time), _) => format!("takes {} game time", time),
+        (None, _) => "takes no game time".to_owned(),
+    }
+}
+
+///
+/// python stub with every function and alias.
+/// saved as __builtins__.pyi next to bot programs it makes editors know the functions
+///
+pub fn api_to_pyi(api: &[ApiFunction]) -> String {
+    let mut ret = String::from("# functions available to tankgrid player programs\n");
+    for f in api {
+        for name in std::iter::once(&f.name).chain(f.aliases.iter()) {
+            let _ = write!(ret, "\ndef {}:\n", signature(name, f));
+            let _ = write!(ret, "    \"\"\"{}. {}", f.doc, time_note(f));
+            if *name != f.name {
+                let _ = write!(ret, ". same as {}", f.name);
+            }
+            ret.push_str("\"\"\"\n");
+        }
+    }
+    ret
+}
+
+/// markdown reference table of script functions
+pub fn api_to_markdown(api: &[ApiFunction]) -> String {
+    let mut ret = String::from(
+        "# Player program functions\n\n\
+         Game time is given before tile speed modifiers: mud makes moving and turning slower.\n\
+         A command acts after its effect time, the program continues after the full time.\n\n\
+         | function | aliases | game time | effect time | description |\n\
+         |---|---|---|---|---|\n",
+    );
+    let or_dash = |x: Option<GameTime>| x.map_or("-".to_owned(), |x| x.to_string());
+    for f in api {
+        let aliases: Vec<String> = f.aliases.iter().map(|x| format!("`{}`", x)).collect();
+        let _ = writeln!(
+            ret,
+            "| `{}` | {} | {} | {} | {} |",
+            signature(f.name, f).replace('|', "\\|"),
+            aliases.join(", "),
+            or_dash(f.time),
+            or_dash(f.effect_time),
+            f.doc.replace('|', "\\|"),
+        );
+    }
+    ret
+}
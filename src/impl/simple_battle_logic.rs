@@ -2,19 +2,22 @@ use crate::battle_logic::BattleLogic;
 use crate::battle_state_info::BattleStateInfo;
 use crate::command_and_reply::CommandReplyStat;
 use crate::gametime::GameTime;
-use crate::log_data::{LogRepresentable, MaybeLogRepresentable};
-use crate::map::MapReadAccess;
+use crate::log_data::{LogCommand, LogRepresentable, ToLogCommand};
+use crate::map::{MapReadAccess, MapWriteAccess};
 use crate::map_object::MapObject;
 use crate::map_prober::MapProber;
 use crate::maptile_logic::MaptileLogic;
 use crate::object_layer::ObjectLayer;
 use crate::orientation::SimpleOrientation;
 use crate::player_state::PlayerControl;
-use crate::script_repr::{FromScriptRepr, ToScriptRepr};
+use crate::script_repr::{FromScriptRepr, ToPlayerScriptRepr, ToScriptRepr};
 
+use super::log_events::LogAction;
+use super::script_api::script_function_aliases;
 use super::timestamped_container::ExpiringContainer;
-use super::simple_object::{ObjectCacheType, SimpleObject};
+use super::simple_object::{ObjectCacheType, PickupEffect, SimpleObject};
 
+use std::collections::{HashSet, VecDeque};
 use std::hash::Hash;
 use std::marker::PhantomData;
 use std::vec;
@@ -47,32 +50,40 @@ pub enum PlayerCommand<R> {
     AddAmmo(u64),   // generated after picking up ammo crate
     AddHealth(u64), // generated after picking up health
     Time,
+    SendMessage(String), // radio message to allies
+    ReceiveMessages,
 }
 
-impl<R> MaybeLogRepresentable for PlayerCommand<R>
+impl<R> ToLogCommand for PlayerCommand<R>
 where
     R: LogRepresentable,
 {
-    fn try_log_repr(&self) -> Option<String> {
+    fn to_log_command(&self) -> Option<LogCommand> {
         match self {
-            PlayerCommand::MoveFwd => Some("move-forward".to_owned()),
-            PlayerCommand::MoveBack => Some("move-backward".to_owned()),
-            PlayerCommand::TurnCW => Some("turn-cw".to_owned()),
-            PlayerCommand::TurnCCW => Some("turn-ccw".to_owned()),
-            PlayerCommand::Shoot => Some("shoot".to_owned()),
-            PlayerCommand::AfterShootCooldown => Some("cooldown".to_owned()),
+            PlayerCommand::MoveFwd => Some(LogCommand::new("move-forward", None)),
+            PlayerCommand::MoveBack => Some(LogCommand::new("move-backward", None)),
+            PlayerCommand::TurnCW => Some(LogCommand::new("turn-cw", None)),
+            PlayerCommand::TurnCCW => Some(LogCommand::new("turn-ccw", None)),
+            PlayerCommand::Shoot => Some(LogCommand::new("shoot", None)),
+            PlayerCommand::AfterShootCooldown => Some(LogCommand::new("cooldown", None)),
             PlayerCommand::ShotHitSound => None,
-            PlayerCommand::Wait => Some("wait".to_owned()),
-            PlayerCommand::Look(dir) => Some(format!("look[{}]", dir.log_repr())),
-            PlayerCommand::Listen => Some(format!("listen")),
-            PlayerCommand::AddAmmo(ammo) => Some(format!("add-ammo[{}]", ammo)),
-            PlayerCommand::AddHealth(health) => Some(format!("heal[{}]", health)),
-            PlayerCommand::CheckAmmo => Some(format!("check-ammo")),
-            PlayerCommand::CheckHealth => Some(format!("check-health")),
-            PlayerCommand::CheckHit => Some(format!("check-hit")),
-            PlayerCommand::ResetHit => Some(format!("reset-hit")),
+            PlayerCommand::Wait => Some(LogCommand::new("wait", None)),
+            PlayerCommand::Look(dir) => Some(LogCommand::new("look", Some(dir.log_repr()))),
+            PlayerCommand::Listen => Some(LogCommand::new("listen", None)),
+            PlayerCommand::AddAmmo(ammo) => {
+                Some(LogCommand::new("add-ammo", Some(ammo.to_string())))
+            }
+            PlayerCommand::AddHealth(health) => {
+                Some(LogCommand::new("heal", Some(health.to_string())))
+            }
+            PlayerCommand::CheckAmmo => Some(LogCommand::new("check-ammo", None)),
+            PlayerCommand::CheckHealth => Some(LogCommand::new("check-health", None)),
+            PlayerCommand::CheckHit => Some(LogCommand::new("check-hit", None)),
+            PlayerCommand::ResetHit => Some(LogCommand::new("reset-hit", None)),
             PlayerCommand::Print(_) => None,
             PlayerCommand::Time => None,
+            PlayerCommand::SendMessage(_) => Some(LogCommand::new("send-message", None)),
+            PlayerCommand::ReceiveMessages => Some(LogCommand::new("receive-messages", None)),
         }
     }
 }
@@ -87,6 +98,7 @@ pub enum PlayerCommandReply<R> {
     HitDirection(Option<R>),
     LookResult(Vec<(String, Option<String>)>),
     ListenResult(Vec<String>),
+    Messages(Vec<(String, String)>), // (sender, text)
 }
 
 impl<R> CommandReplyStat for PlayerCommandReply<R> {
@@ -108,16 +120,55 @@ pub trait CommandTimer<PC> {
     }
 }
 
+/// how radio messages between allies travel
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RadioSettings {
+    /// game time from sending till message can be received
+    pub delivery_delay: GameTime,
+    /// max manhattan distance from sender to receiver at sending time, None for no limit
+    pub range: Option<u64>,
+    /// longer messages are cut to this many characters
+    pub max_message_length: usize,
+    /// messages a player may hold unread, oldest are dropped first
+    pub max_inbox_size: usize,
+}
+
+impl Default for RadioSettings {
+    fn default() -> Self {
+        RadioSettings {
+            delivery_delay: 5,
+            range: None,
+            max_message_length: 64,
+            max_inbox_size: 16,
+        }
+    }
+}
+
+/// everything needed to put a picked or broken pickup back
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PickupSpawn {
+    pub effect: PickupEffect,
+    pub pos: (i64, i64),
+    pub orientation: u64,
+    pub seethroughable: bool,
+    pub passable: bool,
+    pub shootable: bool,
+}
+
+// if pickup spawn location is occupied - try again after this time
+const PICKUP_RESPAWN_RETRY_DELAY: GameTime = 10;
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum SimpleGameEvent {
     Noop,
     FinalizeDeath(usize),
+    RespawnPickup(PickupSpawn),
 }
 
 pub struct SimpleBattleLogic<T, M, L, Pr, R, OLayer, Fdur>
 where
     L: MaptileLogic<T>,
-    M: MapReadAccess<T>,
+    M: MapReadAccess<T> + MapWriteAccess<T>,
     Pr: MapProber<T, R, M, L, SimpleObject<R>, OLayer>,
     R: Copy,
     OLayer: ObjectLayer<R, SimpleObject<R>>,
@@ -130,6 +181,11 @@ where
     object_layer: OLayer,
     player_count_to_win: usize,
     live_with_no_hp_time: GameTime,
+    friendly_fire: bool,
+    pickup_respawn_delay: Option<GameTime>,
+    radio: RadioSettings,
+    // per player: (time message can be received at, sender, text)
+    radio_inboxes: Vec<VecDeque<(GameTime, String, String)>>,
     sound_log: ExpiringContainer<(usize, usize), GameTime, (i64, i64)>,
     _marker0: PhantomData<R>,
     _marker1: PhantomData<T>,
@@ -140,14 +196,16 @@ pub const AMMO_RES: usize = 1;
 pub const HIT_DIR_RES: usize = 2;
 pub const PRINT_COUNTER_RES: usize = 3;
 pub const DEATH_CHECK_TIME: usize = 4;
+/// health can't be healed above this, new_player sets it to initial health
+pub const MAX_HEALTH_RES: usize = 5;
 
 impl<T, M, L, R, P, Pr, OLayer, Fdur>
-    BattleLogic<P, PlayerCommand<R>, PlayerCommandReply<R>, SimpleGameEvent, String, String>
+    BattleLogic<P, PlayerCommand<R>, PlayerCommandReply<R>, SimpleGameEvent, String, LogAction>
     for SimpleBattleLogic<T, M, L, Pr, R, OLayer, Fdur>
 where
-    T: Copy + Clone + Send + ToScriptRepr,
+    T: Copy + Clone + PartialEq + Send + ToScriptRepr + LogRepresentable,
     L: MaptileLogic<T>,
-    M: MapReadAccess<T>,
+    M: MapReadAccess<T> + MapWriteAccess<T>,
     R: Copy
         + Clone
         + Eq
@@ -162,7 +220,7 @@ where
         + From<u64>
         + std::fmt::Debug,
     OLayer: ObjectLayer<R, SimpleObject<R>>,
-    P: PlayerControl + MapObject<R> + ToScriptRepr + LogRepresentable,
+    P: PlayerControl + MapObject<R> + ToScriptRepr + ToPlayerScriptRepr + LogRepresentable,
     Pr: MapProber<T, R, M, L, SimpleObject<R>, OLayer>,
     Fdur: CommandTimer<PlayerCommand<R>>,
 {
@@ -171,9 +229,10 @@ where
     }
 
     fn game_finished(&self, players: &[P]) -> Option<Vec<usize>> {
-        // default impl returns true when only single player left
+        // game is finished when only player_count_to_win sides left,
+        // a side is a team or a player on its own
         let mut some_are_dying = false;
-        let maybe_winners: Vec<usize> = players
+        let alive: Vec<usize> = players
             .iter()
             .enumerate()
             .filter(|(_, p)| !self.is_player_dead(*p))
@@ -183,8 +242,20 @@ where
                 i
             })
             .collect();
-        if maybe_winners.len() <= self.player_count_to_win && !some_are_dying {
-            Some(maybe_winners)
+        let alive_teams: HashSet<usize> = alive.iter().filter_map(|&i| players[i].team()).collect();
+        let alive_sides =
+            alive_teams.len() + alive.iter().filter(|&&i| players[i].team().is_none()).count();
+        if alive_sides <= self.player_count_to_win && !some_are_dying {
+            // team wins as a whole, including its fallen members
+            Some(
+                (0..players.len())
+                    .filter(|&i| {
+                        players[i]
+                            .team()
+                            .map_or(alive.contains(&i), |team| alive_teams.contains(&team))
+                    })
+                    .collect(),
+            )
         } else {
             None
         }
@@ -192,7 +263,7 @@ where
 
     fn initial_setup<LWF>(&mut self, player_states: &mut [P], logger: &mut LWF)
     where
-        LWF: FnMut(String, String),
+        LWF: FnMut(String, LogAction),
     {
         // log spawn
         for player in player_states.iter() {
@@ -200,8 +271,15 @@ where
             let ori = player.orientation();
             logger(
                 player.log_repr(),
-                format!("spawn[{},{},{}]", x, y, ori.log_repr()),
+                LogAction::Spawn {
+                    x,
+                    y,
+                    orientation: ori.log_repr(),
+                },
             );
+            if let Some(team) = player.team() {
+                logger(player.log_repr(), LogAction::Team { team });
+            }
         }
         // log initial objects and players
         for object in self.object_layer.objects() {
@@ -213,7 +291,11 @@ where
             let ori = object.orientation();
             logger(
                 object.log_repr(),
-                format!("spawn[{},{},{}]", x, y, ori.log_repr()),
+                LogAction::Spawn {
+                    x,
+                    y,
+                    orientation: ori.log_repr(),
+                },
             );
         }
     }
@@ -223,9 +305,12 @@ where
         event: &SimpleGameEvent,
         players_states: &mut [P],
         battle_info: &BattleStateInfo,
-        _logger: &mut LWF,
-    ) -> Option<Vec<(GameTime, SimpleGameEvent)>> {
-        let new_events = Vec::new();
+        logger: &mut LWF,
+    ) -> Option<Vec<(GameTime, SimpleGameEvent)>>
+    where
+        LWF: FnMut(String, LogAction),
+    {
+        let mut new_events = Vec::new();
 
         match event {
             SimpleGameEvent::Noop => (),
@@ -238,6 +323,38 @@ where
                     player_state.set_resource(DEATH_CHECK_TIME, 0);
                 }
             }
+            SimpleGameEvent::RespawnPickup(spawn) => {
+                self.recache_players_to_object_layer(players_states);
+                let (x, y) = spawn.pos;
+                if !self.logic.passable(self.map.get_tile_at(x, y)) {
+                    // tile changed under the pickup, like thin ice into water,
+                    // nobody can reach it there, so it is not coming back
+                } else if self.object_layer.objects_at(x, y).is_empty() {
+                    let orientation = R::from(spawn.orientation);
+                    let uid = self.object_layer.add(SimpleObject::new(
+                        x,
+                        y,
+                        orientation,
+                        ObjectCacheType::Pickup(spawn.effect),
+                        spawn.seethroughable,
+                        spawn.passable,
+                        spawn.shootable,
+                    ));
+                    logger(
+                        self.object_layer.object_by_id(uid).unwrap().log_repr(),
+                        LogAction::Spawn {
+                            x,
+                            y,
+                            orientation: orientation.log_repr(),
+                        },
+                    );
+                } else {
+                    new_events.push((
+                        PICKUP_RESPAWN_RETRY_DELAY,
+                        SimpleGameEvent::RespawnPickup(spawn.clone()),
+                    ));
+                }
+            }
         };
 
         if new_events.len() > 0 {
@@ -304,7 +421,7 @@ where
         Option<Vec<(GameTime, SimpleGameEvent)>>,
     )
     where
-        LWF: FnMut(String, String),
+        LWF: FnMut(String, LogAction),
     {
         // doing any command other than print resets the print counter
         match command {
@@ -319,6 +436,7 @@ where
                 self.recache_players_to_object_layer(player_states);
                 let player_state = &mut player_states[player_i];
                 let mut extra_commands = None;
+                let mut events = Vec::new();
 
                 let move_orientation = match dir_command {
                     PlayerCommand::MoveFwd => player_state.orientation(),
@@ -335,31 +453,45 @@ where
                         .object_layer
                         .objects_at_are_passable(fwd_pos_x, fwd_pos_y)
                 {
+                    let (from_x, from_y) = player_state.position();
                     player_state.move_to((fwd_pos_x, fwd_pos_y));
                     logger(
                         player_state.log_repr(),
-                        format!("move[{},{}]", fwd_pos_x, fwd_pos_y),
+                        LogAction::Move {
+                            x: fwd_pos_x,
+                            y: fwd_pos_y,
+                        },
                     );
 
+                    // terrain may change under tanks, like grass getting tracks
+                    let from_tile = self.map.get_tile_at(from_x, from_y);
+                    self.change_tile(from_x, from_y, self.logic.move_from(from_tile), logger);
+                    self.change_tile(fwd_pos_x, fwd_pos_y, self.logic.move_onto(tile), logger);
+
                     // pick up pickable objects
 
                     let mut objs_to_destroy = Vec::new();
+                    let mut pickup_commands = Vec::new();
                     for obj in self.object_layer.objects_at(fwd_pos_x, fwd_pos_y) {
-                        match obj.obj_type {
-                            ObjectCacheType::AmmoCrate(ammo_size) => {
-                                objs_to_destroy.push(obj.unique_id());
-                                extra_commands = Some(vec![PlayerCommand::AddAmmo(ammo_size)]);
+                        if let ObjectCacheType::Pickup(effect) = obj.obj_type {
+                            objs_to_destroy.push(obj.unique_id());
+                            match effect.resource {
+                                AMMO_RES => {
+                                    pickup_commands.push(PlayerCommand::AddAmmo(effect.amount))
+                                }
+                                HEALTH_RES => {
+                                    pickup_commands.push(PlayerCommand::AddHealth(effect.amount))
+                                }
+                                // resources without a command of their own are just added
+                                _ => player_state.gain_resource(effect.resource, effect.amount),
                             }
-                            _ => (),
                         }
                     }
+                    if !pickup_commands.is_empty() {
+                        extra_commands = Some(pickup_commands);
+                    }
                     for obj_id in objs_to_destroy {
-                        logger(
-                            // unwrap cuz obj must exist as checked in prev loop
-                            self.object_layer.object_by_id(obj_id).unwrap().log_repr(),
-                            "picked".to_owned(),
-                        );
-                        self.object_layer.remove_object(obj_id);
+                        events.extend(self.remove_pickup(obj_id, LogAction::Picked, logger));
                     }
 
                     PlayerCommandReply::Ok
@@ -367,7 +499,7 @@ where
                     PlayerCommandReply::Failed
                 };
 
-                (reply, extra_commands, None)
+                (reply, extra_commands, Some(events).filter(|x| !x.is_empty()))
             }
             PlayerCommand::TurnCW => {
                 self.recache_players_to_object_layer(player_states);
@@ -375,7 +507,9 @@ where
                 player_state.turn_cw();
                 logger(
                     player_state.log_repr(),
-                    format!("turn[{}]", player_state.orientation().log_repr()),
+                    LogAction::Turn {
+                        orientation: player_state.orientation().log_repr(),
+                    },
                 );
                 (PlayerCommandReply::Ok, None, None)
             }
@@ -385,12 +519,14 @@ where
                 player_state.turn_ccw();
                 logger(
                     player_state.log_repr(),
-                    format!("turn[{}]", player_state.orientation().log_repr()),
+                    LogAction::Turn {
+                        orientation: player_state.orientation().log_repr(),
+                    },
                 );
                 (PlayerCommandReply::Ok, None, None)
             }
             PlayerCommand::Shoot => {
-                let mut event_maybe = None;
+                let mut events = Vec::new();
                 let player_state = &mut player_states[player_i];
                 if player_state.resource_value(AMMO_RES) > 0 {
                     player_state.expend_resource(AMMO_RES, 1);
@@ -410,7 +546,7 @@ where
                             let (x, y) = player_state.position();
                             logger(
                                 player_state.log_repr(),
-                                format!("shoot[{x},{y},{hit_x},{hit_y}]"),
+                                LogAction::Shoot { x, y, hit_x, hit_y },
                             );
 
                             // make sound
@@ -428,8 +564,12 @@ where
                                 ),
                             );
                         }
+                        // tiles may turn into something else when shot, like walls breaking
+                        let hit_tile = self.map.get_tile_at(hit_x, hit_y);
+                        self.change_tile(hit_x, hit_y, self.logic.shoot(hit_tile), logger);
                         let mut objs_to_destroy = Vec::new();
                         let player_ori = player_state.orientation();
+                        let player_team = player_state.team();
                         for obj in self.object_layer.objects_at(hit_x, hit_y).into_iter() {
                             if !obj.shootable() {
                                 continue;
@@ -437,6 +577,13 @@ where
                             match obj.obj_type {
                                 ObjectCacheType::Player(other_player_i) => {
                                     let hit_enemy = &mut player_states[other_player_i];
+                                    if !self.friendly_fire
+                                        && player_team.is_some()
+                                        && hit_enemy.team() == player_team
+                                    {
+                                        // shot is stopped by the ally, but does no harm
+                                        continue;
+                                    }
 
                                     let was_alive = hit_enemy.resource_value(HEALTH_RES) > 0;
                                     hit_enemy.expend_resource(HEALTH_RES, 1);
@@ -448,11 +595,11 @@ where
                                             DEATH_CHECK_TIME,
                                             battle_state.game_time + self.live_with_no_hp_time,
                                         );
-                                        event_maybe = Some(vec![(
+                                        events.push((
                                             self.live_with_no_hp_time,
                                             SimpleGameEvent::FinalizeDeath(other_player_i),
-                                        )]);
-                                        logger(hit_enemy.log_repr(), format!("dying"));
+                                        ));
+                                        logger(hit_enemy.log_repr(), LogAction::Dying);
                                     }
 
                                     let hit_relative_direction = player_ori
@@ -464,23 +611,19 @@ where
                                             1 + hit_relative_direction.into(),
                                         );
                                 }
-                                ObjectCacheType::AmmoCrate(_) => {
+                                ObjectCacheType::Pickup(_) => {
                                     objs_to_destroy.push(obj.unique_id());
                                 }
                             }
                         }
                         for obj_id in objs_to_destroy {
-                            logger(
-                                self.object_layer.object_by_id(obj_id).unwrap().log_repr(),
-                                format!("break"),
-                            );
-                            self.object_layer.remove_object(obj_id);
+                            events.extend(self.remove_pickup(obj_id, LogAction::Break, logger));
                         }
                     };
                     (
                         PlayerCommandReply::Ok,
                         Some(vec![PlayerCommand::AfterShootCooldown]),
-                        event_maybe,
+                        Some(events).filter(|x| !x.is_empty()),
                     ) // some wait after shooting
                 } else {
                     (PlayerCommandReply::Failed, None, None)
@@ -494,6 +637,8 @@ where
                 // so we need to convert it to global orientation
                 let ori = ori.from_relative_to_global(&player_states[player_i].orientation());
                 self.recache_players_to_object_layer(player_states);
+                let player_team = player_states[player_i].team();
+                let player_states = &*player_states;
                 let look_result = self
                     .map_prober
                     .look(
@@ -519,7 +664,16 @@ where
                                 } else {
                                     "unknown"
                                 };
-                                format!("{}[{}]", obj.to_script_repr(), obj_ori)
+                                let obj_repr = match obj.obj_type {
+                                    ObjectCacheType::Player(other_player_i) => {
+                                        let other_player = &player_states[other_player_i];
+                                        let is_ally = player_team.is_some()
+                                            && other_player.team() == player_team;
+                                        other_player.to_player_script_repr(is_ally)
+                                    }
+                                    _ => obj.to_script_repr(),
+                                };
+                                format!("{}[{}]", obj_repr, obj_ori)
                             }),
                         )
                     })
@@ -601,9 +755,12 @@ where
             }
             PlayerCommand::AddHealth(health) => {
                 let player_state = &mut player_states[player_i];
-                player_state.gain_resource(HEALTH_RES, *health);
-                if player_state.resource_value(HEALTH_RES) > 0 {
-                    player_state.set_resource(DEATH_CHECK_TIME, 0);
+                // dying tanks are beyond repair, others heal up to their initial health
+                let current = player_state.resource_value(HEALTH_RES);
+                if current > 0 {
+                    let max_health = player_state.resource_value(MAX_HEALTH_RES);
+                    let healed = (current + health).min(max_health).max(current);
+                    player_state.set_resource(HEALTH_RES, healed);
                 }
                 (PlayerCommandReply::Ok, None, None)
             }
@@ -640,14 +797,73 @@ where
                     0 => penalty = Some(vec![PlayerCommand::Wait; 4]), // penalize player for abusing print with forced waits
                     1 => logger(
                         player_state.log_repr(),
-                        "log[---next print will be muted and penalized with game time unless a valid game comand called---]".to_owned(),
+                        LogAction::Log {
+                            text: "---next print will be muted and penalized with game time unless a valid game comand called---".to_owned(),
+                        },
                     ),
-                    _ => logger(player_state.log_repr(), format!("log[{}]", line)),
+                    _ => logger(player_state.log_repr(), LogAction::Log { text: line.clone() }),
                 }
                 player_state.expend_resource(PRINT_COUNTER_RES, 1);
                 (PlayerCommandReply::Ok, penalty, None)
             }
             PlayerCommand::Time => (PlayerCommandReply::Uint(battle_state.game_time), None, None),
+            PlayerCommand::SendMessage(text) => {
+                let text: String = text
+                    .chars()
+                    .take(self.radio.max_message_length)
+                    .map(|c| if c.is_control() { '_' } else { c })
+                    .collect();
+                let sender = &player_states[player_i];
+                logger(sender.log_repr(), LogAction::Radio { text: text.clone() });
+                let sender_team = if let Some(team) = sender.team() {
+                    team
+                } else {
+                    // nobody to talk to
+                    return (PlayerCommandReply::Ok, None, None);
+                };
+                let (x, y) = sender.position();
+                let sender_repr = sender.to_script_repr();
+                let deliver_at = battle_state.game_time + self.radio.delivery_delay;
+
+                self.radio_inboxes
+                    .resize(player_states.len(), VecDeque::new());
+                for (i, receiver) in player_states.iter().enumerate() {
+                    if i == player_i
+                        || receiver.team() != Some(sender_team)
+                        || self.is_player_dead(receiver)
+                    {
+                        continue;
+                    }
+                    let (rx, ry) = receiver.position();
+                    if self
+                        .radio
+                        .range
+                        .is_some_and(|range| (rx - x).unsigned_abs() + (ry - y).unsigned_abs() > range)
+                    {
+                        continue;
+                    }
+                    let inbox = &mut self.radio_inboxes[i];
+                    if inbox.len() >= self.radio.max_inbox_size {
+                        inbox.pop_front();
+                    }
+                    inbox.push_back((deliver_at, sender_repr.clone(), text.clone()));
+                }
+                (PlayerCommandReply::Ok, None, None)
+            }
+            PlayerCommand::ReceiveMessages => {
+                let mut messages = Vec::new();
+                if let Some(inbox) = self.radio_inboxes.get_mut(player_i) {
+                    // messages are delivered in sending order, so all arrived ones are in front
+                    while inbox
+                        .front()
+                        .is_some_and(|(deliver_at, _, _)| *deliver_at <= battle_state.game_time)
+                    {
+                        let (_, sender, text) = inbox.pop_front().unwrap();
+                        messages.push((sender, text));
+                    }
+                }
+                (PlayerCommandReply::Messages(messages), None, None)
+            }
         }
     }
 
@@ -675,6 +891,15 @@ where
         (dur * 100) / (speed_percentage as u64)
     }
 
+    fn think_overrun_penalty(
+        &self,
+        _player_state: &P,
+        penalty_units: u64,
+    ) -> Option<Vec<PlayerCommand<R>>> {
+        // same as print abuse - penalize with forced waits
+        Some(vec![PlayerCommand::Wait; penalty_units as usize])
+    }
+
     fn get_command_reply_delay(&self, _player_state: &P, com: &PlayerCommand<R>) -> GameTime {
         self.command_duration.get_reply_delay(com)
     }
@@ -683,29 +908,23 @@ where
     where
         FSR: Fn(PlayerCommand<R>) -> Result<PlayerCommandReply<R>, ()> + Clone + 'static,
     {
+        // aliases come from script_functions, so the api description always matches the scope
         macro_rules! add_function {
-            ($fname:expr, ( $($fnames:expr),+ ) , $fn:block) => {
+            ($fname:expr, $fn:block) => {
                 let func: PyObjectRef = vm.new_function($fname, $fn).into();
-                $(
-                    let func1 = func.clone();
+                for alias in script_function_aliases($fname) {
                     scope
-                    .globals
-                    .set_item($fnames, func1, vm)
-                    .unwrap();
-                )+
+                        .globals
+                        .set_item(*alias, func.clone(), vm)
+                        .unwrap();
+                }
                 scope
                     .globals
                     .set_item($fname, func, vm)
                     .unwrap();
             };
-            ($fname:expr, $fn:block) => {
-                scope
-                    .globals
-                    .set_item($fname, vm.new_function($fname, $fn).into(), vm)
-                    .unwrap();
-            };
         }
-        add_function!("turn_cw", ("turn_right", "turn_r") , {
+        add_function!("turn_cw", {
             // TODO: figure out why do I have to downgrade refs?
             //  it's as if interpreter is not dropped properly and keeps refs
             let comm_chan = comm_chan.clone();
@@ -714,28 +933,28 @@ where
                 PyResult::Ok(())
             }
         });
-        add_function!("turn_ccw", ("turn_left", "turn_l"), {
+        add_function!("turn_ccw", {
             let comm_chan = comm_chan.clone();
             move |_vm: &VirtualMachine| -> PyResult<()> {
                 let _ret = comm_chan(PlayerCommand::TurnCCW);
                 PyResult::Ok(())
             }
         });
-        add_function!("move_forward", ("move_fwd"), {
+        add_function!("move_forward", {
             let comm_chan = comm_chan.clone();
             move |_vm: &VirtualMachine| -> PyResult<()> {
                 let _ret = comm_chan(PlayerCommand::MoveFwd);
                 PyResult::Ok(())
             }
         });
-        add_function!("move_backward", ("move_backwards", "move_back"), {
+        add_function!("move_backward", {
             let comm_chan = comm_chan.clone();
             move |_vm: &VirtualMachine| -> PyResult<()> {
                 let _ret = comm_chan(PlayerCommand::MoveBack);
                 PyResult::Ok(())
             }
         });
-        add_function!("shoot", ("fire"), {
+        add_function!("shoot", {
             let comm_chan = comm_chan.clone();
             move |_vm: &VirtualMachine| -> PyResult<()> {
                 let _ret = comm_chan(PlayerCommand::Shoot);
@@ -884,6 +1103,35 @@ where
                 PyResult::Ok(())
             }
         });
+        add_function!("send_message", {
+            let comm_chan = comm_chan.clone();
+            move |text: String, _vm: &VirtualMachine| -> PyResult<()> {
+                let _ret = comm_chan(PlayerCommand::SendMessage(text));
+                PyResult::Ok(())
+            }
+        });
+        add_function!("receive_messages", {
+            let comm_chan = comm_chan.clone();
+            move |vm: &VirtualMachine| {
+                let ret = if let Ok(x) = comm_chan(PlayerCommand::ReceiveMessages) {
+                    x
+                } else {
+                    return PyResult::Err(vm.new_runtime_error("game closed".to_owned()));
+                };
+                if let PlayerCommandReply::Messages(messages) = ret {
+                    PyResult::Ok(
+                        messages
+                            .into_iter()
+                            .map(|t| t.to_pyobject(vm))
+                            .collect::<Vec<_>>(),
+                    )
+                } else {
+                    PyResult::Err(
+                        vm.new_runtime_error(format!("unexpected messages reply: {:?}", ret)),
+                    )
+                }
+            }
+        });
         add_function!("time", {
             let comm_chan = comm_chan.clone();
             move |vm: &VirtualMachine| {
@@ -906,9 +1154,9 @@ where
 
 impl<T, M, L, Pr, R, OLayer, Fdur> SimpleBattleLogic<T, M, L, Pr, R, OLayer, Fdur>
 where
-    T: Copy + Clone + Send + ToScriptRepr,
+    T: Copy + Clone + PartialEq + Send + ToScriptRepr + LogRepresentable,
     L: MaptileLogic<T>,
-    M: MapReadAccess<T>,
+    M: MapReadAccess<T> + MapWriteAccess<T>,
     Pr: MapProber<T, R, M, L, SimpleObject<R>, OLayer>,
     R: Copy
         + Clone
@@ -943,16 +1191,84 @@ where
             command_duration,
             player_count_to_win,
             live_with_no_hp_time,
+            friendly_fire: true,
+            pickup_respawn_delay: None,
+            radio: RadioSettings::default(),
+            radio_inboxes: Vec::new(),
             sound_log: ExpiringContainer::new(),
             _marker0: PhantomData,
             _marker1: PhantomData,
         }
     }
 
+    /// with friendly fire off, shots stop at allies without harming them
+    pub fn set_friendly_fire(&mut self, friendly_fire: bool) {
+        self.friendly_fire = friendly_fire;
+    }
+
+    /// picked and broken pickups come back at the same place after given game time,
+    /// None - they are gone for good
+    pub fn set_pickup_respawn_delay(&mut self, delay: Option<GameTime>) {
+        self.pickup_respawn_delay = delay;
+    }
+
+    pub fn set_radio_settings(&mut self, radio: RadioSettings) {
+        self.radio = radio;
+    }
+
+    /// set map tile and log the change, if tile is actually different
+    fn change_tile<LWF>(&mut self, x: i64, y: i64, tile: T, logger: &mut LWF)
+    where
+        LWF: FnMut(String, LogAction),
+    {
+        if !self.map.is_within_bounds(x, y) || self.map.get_tile_at(x, y) == tile {
+            return;
+        }
+        self.map.set_tile_at(x, y, tile);
+        logger(
+            "map".to_owned(),
+            LogAction::TileChange {
+                x,
+                y,
+                tile: tile.log_repr(),
+            },
+        );
+    }
+
+    /// log and remove a pickup, returns event to respawn it if respawning is on
+    fn remove_pickup<LWF>(
+        &mut self,
+        uid: u64,
+        action: LogAction,
+        logger: &mut LWF,
+    ) -> Option<(GameTime, SimpleGameEvent)>
+    where
+        LWF: FnMut(String, LogAction),
+    {
+        let obj = self.object_layer.object_by_id(uid)?;
+        logger(obj.log_repr(), action);
+        let respawn = match (self.pickup_respawn_delay, obj.obj_type) {
+            (Some(delay), ObjectCacheType::Pickup(effect)) => Some((
+                delay,
+                SimpleGameEvent::RespawnPickup(PickupSpawn {
+                    effect,
+                    pos: obj.position(),
+                    orientation: obj.orientation().into(),
+                    seethroughable: obj.seethroughable,
+                    passable: obj.passable,
+                    shootable: obj.shootable,
+                }),
+            )),
+            _ => None,
+        };
+        self.object_layer.remove_object(uid);
+        respawn
+    }
+
     fn recache_players_to_object_layer<P>(&mut self, player_states: &[P])
     where
         // TODO: player does NOT have to impl MapObject
-        P: PlayerControl + MapObject<R> + ToScriptRepr + LogRepresentable,
+        P: PlayerControl + MapObject<R> + ToScriptRepr + ToPlayerScriptRepr + LogRepresentable,
     {
         // clear player cache
         self.object_layer.clear_by(|m| {
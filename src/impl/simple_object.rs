@@ -4,20 +4,39 @@ use crate::log_data::LogRepresentable;
 use crate::map_object::MapObject;
 use crate::script_repr::{self, ToScriptRepr};
 
+use super::simple_battle_logic::{AMMO_RES, HEALTH_RES};
 use super::unique_id_counter::NEXT_OBJID;
 
+/// what a player gets for picking an object up: amount of one of player resources,
+/// like AMMO_RES or HEALTH_RES
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PickupEffect {
+    pub resource: usize,
+    pub amount: u64,
+}
+
+impl PickupEffect {
+    pub fn new(resource: usize, amount: u64) -> PickupEffect {
+        PickupEffect { resource, amount }
+    }
+}
+
 #[derive(Clone, Copy)]
 pub enum ObjectCacheType {
     Player(usize),
-    AmmoCrate(u64),
-    //Stuff, // TODO: add stuff like pickable items
+    /// picked up by moving onto it, broken by a shot
+    Pickup(PickupEffect),
 }
 
 impl LogRepresentable for ObjectCacheType {
     fn log_repr(&self) -> String {
         match self {
             ObjectCacheType::Player(_) => "player",
-            ObjectCacheType::AmmoCrate(_) => "ammocrate",
+            ObjectCacheType::Pickup(effect) => match effect.resource {
+                AMMO_RES => "ammocrate",
+                HEALTH_RES => "healthcrate",
+                _ => "pickup",
+            },
         }
         .to_owned()
     }
@@ -0,0 +1,214 @@
+use super::grid_map::GridBattleMap;
+use super::grid_orientation::GridOrientation;
+use crate::map::MapReadAccess;
+use crate::maptile_logic::MaptileLogic;
+
+use std::collections::BTreeMap;
+
+///
+/// pick spawn locations for any number of players.
+/// spawns are passable cells without map objects, as far from each other as possible,
+/// and, where map allows, all at the same distance to the nearest wall or map object,
+/// so nobody starts with more cover than others. players face the map center.
+/// None if there are fewer free passable cells than players
+///
+pub fn spread_spawn_locations<T, L>(
+    map: &GridBattleMap<T>,
+    logic: &L,
+    count: usize,
+) -> Option<Vec<(i64, i64, GridOrientation)>>
+where
+    T: Copy + Clone,
+    L: MaptileLogic<T>,
+{
+    let height = map.map_data().row_count();
+    let width = if height > 0 {
+        map.map_data().row(0).len()
+    } else {
+        0
+    };
+    let blocked = |x: i64, y: i64| {
+        !logic.passable(map.get_tile_at(x, y))
+            || map
+                .map_data()
+                .objects()
+                .iter()
+                .any(|o| (o.x, o.y) == (x, y))
+    };
+
+    let distances = cover_distances(width, height, &blocked);
+    let mut candidates_by_distance: BTreeMap<usize, Vec<(i64, i64)>> = BTreeMap::new();
+    let mut all_candidates = Vec::new();
+    for y in 0..height as i64 {
+        for x in 0..width as i64 {
+            if blocked(x, y) {
+                continue;
+            }
+            candidates_by_distance
+                .entry(distances[y as usize * width + x as usize])
+                .or_default()
+                .push((x, y));
+            all_candidates.push((x, y));
+        }
+    }
+    if count == 0 || all_candidates.len() < count {
+        return None;
+    }
+
+    let center = (width as i64 - 1, height as i64 - 1); // doubled, to stay in integers
+    let (mut best, best_spread) = spread_out(&all_candidates, count, center);
+    // fair placement is preferred as long as players are not squeezed much closer together
+    let mut best_fair_spread = 0;
+    for candidates in candidates_by_distance.values() {
+        if candidates.len() < count {
+            continue;
+        }
+        let (chosen, spread) = spread_out(candidates, count, center);
+        if spread * 2 >= best_spread && spread > best_fair_spread {
+            best_fair_spread = spread;
+            best = chosen;
+        }
+    }
+
+    Some(
+        best.into_iter()
+            .map(|(x, y)| (x, y, facing_center(x, y, center)))
+            .collect(),
+    )
+}
+
+/// manhattan distance from every cell to the nearest blocked cell, out of bounds counts as blocked
+fn cover_distances<F>(width: usize, height: usize, blocked: &F) -> Vec<usize>
+where
+    F: Fn(i64, i64) -> bool,
+{
+    let outside = if blocked(-1, -1) { 0 } else { usize::MAX / 2 };
+    let mut distances: Vec<usize> = (0..width * height)
+        .map(|i| {
+            if blocked((i % width) as i64, (i / width) as i64) {
+                0
+            } else {
+                usize::MAX / 2
+            }
+        })
+        .collect();
+    let at = |d: &Vec<usize>, x: i64, y: i64| {
+        if x < 0 || y < 0 || x as usize >= width || y as usize >= height {
+            outside
+        } else {
+            d[y as usize * width + x as usize]
+        }
+    };
+    // two pass distance transform, first from top-left, then from bottom-right
+    for y in 0..height as i64 {
+        for x in 0..width as i64 {
+            let best = at(&distances, x - 1, y).min(at(&distances, x, y - 1)) + 1;
+            let cell = &mut distances[y as usize * width + x as usize];
+            *cell = (*cell).min(best);
+        }
+    }
+    for y in (0..height as i64).rev() {
+        for x in (0..width as i64).rev() {
+            let best = at(&distances, x + 1, y).min(at(&distances, x, y + 1)) + 1;
+            let cell = &mut distances[y as usize * width + x as usize];
+            *cell = (*cell).min(best);
+        }
+    }
+    distances
+}
+
+///
+/// greedy farthest point sampling: start with the cell farthest from the center,
+/// then keep adding the cell farthest from all already chosen.
+/// returns chosen cells and the smallest distance between them
+///
+fn spread_out(
+    candidates: &[(i64, i64)],
+    count: usize,
+    center: (i64, i64),
+) -> (Vec<(i64, i64)>, i64) {
+    let manhattan = |a: (i64, i64), b: (i64, i64)| (a.0 - b.0).abs() + (a.1 - b.1).abs();
+    let first = candidates
+        .iter()
+        .copied()
+        .max_by_key(|&(x, y)| (manhattan((2 * x, 2 * y), center), -y, -x))
+        .expect("candidates must not be empty");
+    let mut chosen = vec![first];
+    let mut closest: Vec<i64> = candidates.iter().map(|&c| manhattan(c, first)).collect();
+    let mut spread = i64::MAX;
+    while chosen.len() < count {
+        let (next_i, &next_dist) = closest
+            .iter()
+            .enumerate()
+            .max_by_key(|&(i, d)| (*d, -(i as i64)))
+            .expect("candidates must not be empty");
+        let next = candidates[next_i];
+        spread = spread.min(next_dist);
+        chosen.push(next);
+        for (dist, &c) in closest.iter_mut().zip(candidates.iter()) {
+            *dist = (*dist).min(manhattan(c, next));
+        }
+    }
+    (chosen, spread)
+}
+
+/// orientation looking towards the doubled center coordinates
+pub(crate) fn facing_center(x: i64, y: i64, center: (i64, i64)) -> GridOrientation {
+    let (dx, dy) = (center.0 - 2 * x, center.1 - 2 * y);
+    if dx.abs() >= dy.abs() && dx != 0 {
+        if dx > 0 {
+            GridOrientation::East
+        } else {
+            GridOrientation::West
+        }
+    } else if dy > 0 {
+        GridOrientation::South
+    } else {
+        GridOrientation::North
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::MapWriteAccess;
+    use crate::r#impl::tile_types::TileType;
+    use crate::r#impl::tile_types_logic::TileTypeLogic;
+
+    #[test]
+    fn spawns_are_free_distinct_and_fair() {
+        let mut map = GridBattleMap::new(12, 12, TileType::Ground, TileType::Wall);
+        for i in 3..9 {
+            map.set_tile_at(i, 6, TileType::Wall);
+        }
+        let logic = TileTypeLogic::new();
+        for count in [5, 8, 16] {
+            let spawns = spread_spawn_locations(&map, &logic, count).unwrap();
+            assert_eq!(count, spawns.len());
+            let distances = cover_distances(12, 12, &|x, y| !logic.passable(map.get_tile_at(x, y)));
+            let cover = distances[spawns[0].1 as usize * 12 + spawns[0].0 as usize];
+            for (i, &(x, y, _)) in spawns.iter().enumerate() {
+                assert!(logic.passable(map.get_tile_at(x, y)));
+                assert!(spawns[..i].iter().all(|&(ox, oy, _)| (ox, oy) != (x, y)));
+                assert_eq!(cover, distances[y as usize * 12 + x as usize]);
+            }
+        }
+    }
+
+    #[test]
+    fn too_many_players() {
+        let map = GridBattleMap::new(2, 2, TileType::Ground, TileType::Wall);
+        let logic = TileTypeLogic::new();
+        assert!(spread_spawn_locations(&map, &logic, 4).is_some());
+        assert!(spread_spawn_locations(&map, &logic, 5).is_none());
+    }
+
+    #[test]
+    fn facing() {
+        // 5x5 map, doubled center is (4, 4)
+        assert_eq!(GridOrientation::East, facing_center(0, 0, (4, 4)));
+        assert_eq!(GridOrientation::West, facing_center(4, 1, (4, 4)));
+        assert_eq!(GridOrientation::North, facing_center(2, 4, (4, 4)));
+        assert_eq!(GridOrientation::South, facing_center(2, 0, (4, 4)));
+    }
+}
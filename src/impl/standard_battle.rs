@@ -0,0 +1,332 @@
+use super::grid_battle::{new_player, new_team_player, GridBattle};
+use super::grid_map::GridBattleMap;
+use super::grid_map_prober::GridMapProber;
+use super::grid_orientation::GridOrientation;
+use super::log_events::LogAction;
+use super::map_analysis::{analyze_map, MapAnalysis};
+use super::script_api::{describe_api, ApiFunction};
+use super::simple_battle_logic::{
+    CommandTimer, PlayerCommand, SimpleBattleLogic, AMMO_RES, HEALTH_RES,
+};
+use super::simple_battle_object_layer::SimpleBattleObjectLayer;
+use super::simple_object::{ObjectCacheType, PickupEffect, SimpleObject};
+use super::tile_types::TileType;
+use super::tile_types_logic::TileTypeLogic;
+use crate::gametime::GameTime;
+use crate::log_data::LogWriter;
+use crate::map::MapReadAccess;
+use crate::map_object::MapObject;
+use crate::maptile_logic::MaptileLogic;
+use crate::object_layer::ObjectLayer;
+use crate::think_budget::{StepBudget, ThinkLimit};
+
+use rand::prelude::*;
+use std::time::Duration;
+
+/// seed the battle binary scatters pickups with
+pub const DEFAULT_OBJECTS_SEED: u64 = 1234567;
+
+pub const INITIAL_AMMO: u64 = 5;
+pub const INITIAL_HEALTH: u64 = 5;
+pub const LIVE_WITH_NO_HP_TIME: GameTime = 30;
+pub const AMMO_CRATE_AMMO: u64 = 17;
+pub const HEALTH_CRATE_HEALTH: u64 = 2;
+/// picked crates come back after this game time
+pub const PICKUP_RESPAWN_DELAY: GameTime = 500;
+
+// with step budget a program may think for this many free budgets before being stopped
+const MAX_THINK_BUDGETS: u64 = 100;
+// wall clock safety net in step budget mode, normally should never be hit
+const THINK_HARD_TIMEOUT: Duration = Duration::from_secs(60);
+
+pub struct StandardCommandTimings {}
+
+impl CommandTimer<PlayerCommand<GridOrientation>> for StandardCommandTimings {
+    fn get_base_duration(&self, command: &PlayerCommand<GridOrientation>) -> GameTime {
+        match command {
+            PlayerCommand::MoveFwd => 5,  // half, half after
+            PlayerCommand::MoveBack => 7, // half, half after
+            PlayerCommand::TurnCW => 8,   // half, half after
+            PlayerCommand::TurnCCW => 8,  // half, half after
+            PlayerCommand::Shoot => 5,
+            PlayerCommand::AfterShootCooldown => 20,
+            PlayerCommand::ShotHitSound => 30,
+            PlayerCommand::Look(_) => 4,
+            PlayerCommand::Listen => 3, // start listening fast, delay reply by long
+            PlayerCommand::Wait => 5,
+            PlayerCommand::AddAmmo(_) => 2,
+            PlayerCommand::AddHealth(_) => 2,
+            PlayerCommand::CheckAmmo => 2,
+            PlayerCommand::CheckHealth => 2,
+            PlayerCommand::CheckHit => 2,
+            PlayerCommand::ResetHit => 1,
+            PlayerCommand::Print(_) => 0,
+            PlayerCommand::Time => 0,
+            PlayerCommand::SendMessage(_) => 3,
+            PlayerCommand::ReceiveMessages => 1,
+        }
+    }
+    fn get_reply_delay(&self, command: &PlayerCommand<GridOrientation>) -> GameTime {
+        match command {
+            PlayerCommand::MoveFwd => 5,
+            PlayerCommand::MoveBack => 7,
+            PlayerCommand::TurnCW => 8,
+            PlayerCommand::TurnCCW => 8,
+            PlayerCommand::Shoot => 5,
+            PlayerCommand::Listen => 12,
+            _ => 0,
+        }
+    }
+}
+
+pub type StandardBattleLogic = SimpleBattleLogic<
+    TileType,
+    GridBattleMap<TileType>,
+    TileTypeLogic,
+    GridMapProber,
+    GridOrientation,
+    SimpleBattleObjectLayer<SimpleObject<GridOrientation>>,
+    StandardCommandTimings,
+>;
+
+pub type StandardBattle<LW> = GridBattle<StandardBattleLogic, LW>;
+
+/// how players of a standard battle are split into teams
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TeamSetup {
+    /// team of every player, in player order
+    pub teams: Vec<usize>,
+    /// can allies hurt each other
+    pub friendly_fire: bool,
+}
+
+/// step think limit with given free steps, as used by --think-steps of the binaries
+pub fn step_think_limit(free_steps: u64) -> ThinkLimit {
+    ThinkLimit::Steps(StepBudget {
+        free_steps,
+        steps_per_penalty: free_steps,
+        max_steps: free_steps.saturating_mul(MAX_THINK_BUDGETS),
+        hard_timeout: Some(THINK_HARD_TIMEOUT),
+    })
+}
+
+///
+/// scatter about one ammo crate per 100 map tiles
+/// and one health crate per 200 map tiles over free passable tiles,
+/// spawn cells of players are not free
+///
+pub fn place_pickups(
+    map: &GridBattleMap<TileType>,
+    map_logic: &TileTypeLogic,
+    object_layer: &mut SimpleBattleObjectLayer<SimpleObject<GridOrientation>>,
+    spawns: &[(i64, i64, GridOrientation)],
+    seed: u64,
+) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let tile_count = map.map_data().row_count() * map.map_data().row(0).len();
+    // ammo crates go first, so they are placed same as before health crates were added
+    let pickups = [
+        (PickupEffect::new(AMMO_RES, AMMO_CRATE_AMMO), 1.max(tile_count / 100)),
+        (
+            PickupEffect::new(HEALTH_RES, HEALTH_CRATE_HEALTH),
+            1.max(tile_count / 200),
+        ),
+    ];
+    for (effect, count) in pickups {
+        place_pickup(map, map_logic, object_layer, spawns, &mut rng, effect, count);
+    }
+}
+
+///
+/// place objects listed in the map file, returns false if none were placed.
+/// maps only list known object kinds on passable tiles, GridBattleMap::new_from_data checks that
+///
+pub fn place_map_objects(
+    map: &GridBattleMap<TileType>,
+    object_layer: &mut SimpleBattleObjectLayer<SimpleObject<GridOrientation>>,
+) -> bool {
+    let mut placed = false;
+    for object in map.map_data().objects() {
+        let effect = match object.kind.as_str() {
+            "ammocrate" => PickupEffect::new(AMMO_RES, AMMO_CRATE_AMMO),
+            "healthcrate" => PickupEffect::new(HEALTH_RES, HEALTH_CRATE_HEALTH),
+            _ => continue,
+        };
+        object_layer.add(SimpleObject::new(
+            object.x,
+            object.y,
+            GridOrientation::North,
+            ObjectCacheType::Pickup(effect),
+            false,
+            true,
+            false,
+        ));
+        placed = true;
+    }
+    placed
+}
+
+fn place_pickup(
+    map: &GridBattleMap<TileType>,
+    map_logic: &TileTypeLogic,
+    object_layer: &mut SimpleBattleObjectLayer<SimpleObject<GridOrientation>>,
+    spawns: &[(i64, i64, GridOrientation)],
+    rng: &mut StdRng,
+    effect: PickupEffect,
+    count: usize,
+) {
+    for _ in 0..count {
+        for _ in 0..100 {
+            let y = rng.gen_range(0..map.map_data().row_count());
+            let x = rng.gen_range(0..map.map_data().row(y).len());
+            let x = x as i64;
+            let y = y as i64;
+            if !map_logic.passable(map.get_tile_at(x, y))
+                || !object_layer.objects_at(x, y).is_empty()
+                || spawns.iter().any(|(sx, sy, _)| (*sx, *sy) == (x, y))
+            {
+                continue;
+            }
+            object_layer.add(SimpleObject::new(
+                x,
+                y,
+                GridOrientation::North,
+                ObjectCacheType::Pickup(effect),
+                false,
+                true,
+                false,
+            ));
+            break;
+        }
+    }
+}
+
+///
+/// analyze map with spawns and pickups the way standard battle places them for player_count players.
+/// None if map has no spawn locations for that many players
+///
+pub fn analyze_standard_map(
+    map: &GridBattleMap<TileType>,
+    player_count: usize,
+    objects_seed: u64,
+) -> Option<MapAnalysis> {
+    let map_logic = TileTypeLogic::new();
+    let spawns = map.get_spawn_locations_for(player_count, &map_logic)?;
+    let mut object_layer = SimpleBattleObjectLayer::new();
+    if !place_map_objects(map, &mut object_layer) {
+        place_pickups(map, &map_logic, &mut object_layer, &spawns, objects_seed);
+    }
+    let pickups: Vec<(i64, i64)> = object_layer
+        .objects()
+        .iter()
+        .map(|x| x.position())
+        .collect();
+    Some(analyze_map(map, &map_logic, &spawns, &pickups))
+}
+
+/// functions player programs get in standard battles, with standard game time costs
+pub fn describe_standard_api() -> Vec<ApiFunction> {
+    describe_api::<GridOrientation, _>(&StandardCommandTimings {})
+}
+
+///
+/// create battle the way battle binary does:
+/// players get map spawn locations in the order given,
+/// objects listed in the map are placed, or if there are none -
+/// ammo and health crates are scattered with objects_seed. pickups respawn after PICKUP_RESPAWN_DELAY.
+/// players are (name, program source) pairs.
+/// None if map has no spawn locations for that many players
+///
+pub fn new_standard_battle<LW>(
+    map: GridBattleMap<TileType>,
+    players: Vec<(String, String)>,
+    objects_seed: u64,
+    logger: LW,
+) -> Option<StandardBattle<LW>>
+where
+    LW: LogWriter<String, LogAction>,
+{
+    new_battle(map, players, None, objects_seed, logger)
+}
+
+/// same as new_standard_battle, but players are split into teams,
+/// last team standing wins.
+/// None also if team count does not match player count
+pub fn new_standard_team_battle<LW>(
+    map: GridBattleMap<TileType>,
+    players: Vec<(String, String)>,
+    team_setup: TeamSetup,
+    objects_seed: u64,
+    logger: LW,
+) -> Option<StandardBattle<LW>>
+where
+    LW: LogWriter<String, LogAction>,
+{
+    if team_setup.teams.len() != players.len() {
+        return None;
+    }
+    new_battle(map, players, Some(team_setup), objects_seed, logger)
+}
+
+fn new_battle<LW>(
+    map: GridBattleMap<TileType>,
+    players: Vec<(String, String)>,
+    team_setup: Option<TeamSetup>,
+    objects_seed: u64,
+    logger: LW,
+) -> Option<StandardBattle<LW>>
+where
+    LW: LogWriter<String, LogAction>,
+{
+    let map_logic = TileTypeLogic::new();
+
+    let player_initial_placements = map
+        .get_spawn_locations_for(players.len(), &map_logic)?;
+    let player_initial_data = players
+        .into_iter()
+        .zip(player_initial_placements.iter().copied())
+        .enumerate()
+        .map(|(i, ((name, program), (x, y, ori)))| {
+            let player = if let Some(team_setup) = &team_setup {
+                new_team_player(
+                    x,
+                    y,
+                    ori,
+                    INITIAL_AMMO,
+                    INITIAL_HEALTH,
+                    &name,
+                    team_setup.teams[i],
+                )
+            } else {
+                new_player(x, y, ori, INITIAL_AMMO, INITIAL_HEALTH, &name)
+            };
+            (player, program)
+        })
+        .collect();
+
+    let mut object_layer = SimpleBattleObjectLayer::new();
+    if !place_map_objects(&map, &mut object_layer) {
+        place_pickups(
+            &map,
+            &map_logic,
+            &mut object_layer,
+            &player_initial_placements,
+            objects_seed,
+        );
+    }
+
+    let mut game_logic = SimpleBattleLogic::new(
+        map,
+        map_logic,
+        GridMapProber::new(),
+        object_layer,
+        StandardCommandTimings {},
+        1,
+        LIVE_WITH_NO_HP_TIME,
+    );
+    game_logic.set_pickup_respawn_delay(Some(PICKUP_RESPAWN_DELAY));
+    if let Some(team_setup) = &team_setup {
+        game_logic.set_friendly_fire(team_setup.friendly_fire);
+    }
+    Some(GridBattle::new(game_logic, player_initial_data, logger))
+}
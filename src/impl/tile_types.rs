@@ -1,10 +1,73 @@
+use super::tile_types_logic::TileTypeLogic;
+use crate::log_data::LogRepresentable;
+use crate::map::ObjectPlacementTile;
+use crate::maptile_logic::MaptileLogic;
 use crate::script_repr::ToScriptRepr;
 
-#[derive(Clone, Copy)]
+/// hit points of a fresh brick wall
+pub const BRICK_WALL_HIT_POINTS: u8 = 3;
+
+// map files store brick walls with 3, 2 and 1 hit points left as codes 3, 4 and 5,
+// codes of all other tiles are fixed, so a tougher wall needs new codes, not shifted ones
+const BRICK_WALL_MAX_CODED_HIT_POINTS: u8 = 3;
+const _: () = assert!(
+    BRICK_WALL_HIT_POINTS >= 1 && BRICK_WALL_HIT_POINTS <= BRICK_WALL_MAX_CODED_HIT_POINTS
+);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum TileType {
     Ground,
     Mud,
     Wall,
+    /// wall that becomes ground after given number of hits more
+    BrickWall(u8),
+    /// leaves tracks behind a tank driving off it
+    Grass,
+    Tracks,
+    /// breaks into water behind a tank driving off it
+    ThinIce,
+    Water,
+}
+
+impl TileType {
+    /// code this tile is stored with in map files and tile-change log entries
+    pub fn code(&self) -> u8 {
+        match self {
+            TileType::Ground => 0,
+            TileType::Wall => 1,
+            TileType::Mud => 2,
+            TileType::BrickWall(hp) => {
+                3 + BRICK_WALL_MAX_CODED_HIT_POINTS - (*hp).clamp(1, BRICK_WALL_HIT_POINTS)
+            }
+            TileType::Grass => 6,
+            TileType::Tracks => 7,
+            TileType::ThinIce => 8,
+            TileType::Water => 9,
+        }
+    }
+
+    pub fn from_code(code: u8) -> Option<TileType> {
+        match code {
+            0 => Some(TileType::Ground),
+            1 => Some(TileType::Wall),
+            2 => Some(TileType::Mud),
+            c @ 3..=5 => Some(TileType::BrickWall(
+                (BRICK_WALL_MAX_CODED_HIT_POINTS - (c - 3)).min(BRICK_WALL_HIT_POINTS),
+            )),
+            6 => Some(TileType::Grass),
+            7 => Some(TileType::Tracks),
+            8 => Some(TileType::ThinIce),
+            9 => Some(TileType::Water),
+            _ => None,
+        }
+    }
+}
+
+impl ObjectPlacementTile for TileType {
+    /// objects stand where tanks can drive to pick them up
+    fn can_hold_objects(&self) -> bool {
+        TileTypeLogic::new().passable(*self)
+    }
 }
 
 impl ToScriptRepr for TileType {
@@ -13,7 +76,18 @@ impl ToScriptRepr for TileType {
             TileType::Ground => "ground",
             TileType::Mud => "mud",
             TileType::Wall => "wall",
+            TileType::BrickWall(_) => "brickwall",
+            TileType::Grass => "grass",
+            TileType::Tracks => "tracks",
+            TileType::ThinIce => "thinice",
+            TileType::Water => "water",
         }
         .to_string()
     }
 }
+
+impl LogRepresentable for TileType {
+    fn log_repr(&self) -> String {
+        self.code().to_string()
+    }
+}
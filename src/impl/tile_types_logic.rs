@@ -9,6 +9,11 @@ impl MaptileLogic<TileType> for TileTypeLogic {
             TileType::Ground => true,
             TileType::Mud => true,
             TileType::Wall => false,
+            TileType::BrickWall(_) => false,
+            TileType::Grass => true,
+            TileType::Tracks => true,
+            TileType::ThinIce => true,
+            TileType::Water => false,
         }
     }
     fn seethroughable(&self, tile: TileType) -> bool {
@@ -16,10 +21,26 @@ impl MaptileLogic<TileType> for TileTypeLogic {
             TileType::Ground => true,
             TileType::Mud => true,
             TileType::Wall => false,
+            TileType::BrickWall(_) => false,
+            TileType::Grass => true,
+            TileType::Tracks => true,
+            TileType::ThinIce => true,
+            TileType::Water => true,
         }
     }
     fn shoot(&self, tile: TileType) -> TileType {
-        tile
+        match tile {
+            TileType::BrickWall(hp) if hp > 1 => TileType::BrickWall(hp - 1),
+            TileType::BrickWall(_) => TileType::Ground,
+            _ => tile,
+        }
+    }
+    fn move_from(&self, tile: TileType) -> TileType {
+        match tile {
+            TileType::Grass => TileType::Tracks,
+            TileType::ThinIce => TileType::Water,
+            _ => tile,
+        }
     }
 }
 
@@ -8,12 +8,7 @@ impl Serialize for TileType {
     where
         S: serde::Serializer,
     {
-        let c = match self {
-            TileType::Ground => 0,
-            TileType::Wall => 1,
-            TileType::Mud => 2,
-        };
-        serializer.serialize_i8(c)
+        serializer.serialize_i8(self.code() as i8)
     }
 }
 
@@ -40,10 +35,10 @@ impl<'de> Deserialize<'de> for TileType {
         D: serde::Deserializer<'de>,
     {
         match deserializer.deserialize_i8(TileVisitor) {
-            Ok(0) => Ok(TileType::Ground),
-            Ok(1) => Ok(TileType::Wall),
-            Ok(2) => Ok(TileType::Mud),
-            Ok(_) => Err(de::Error::custom("there are no tiles with that val")),
+            Ok(c) => u8::try_from(c)
+                .ok()
+                .and_then(TileType::from_code)
+                .ok_or_else(|| de::Error::custom("there are no tiles with that val")),
             Err(e) => Err(e),
         }
     }
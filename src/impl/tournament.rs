@@ -0,0 +1,213 @@
+use serde::Serialize;
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+pub const INITIAL_RATING: f64 = 1500.0;
+pub const ELO_K: f64 = 32.0;
+
+/// result of a single game from first player's point of view
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameOutcome {
+    FirstWon,
+    SecondWon,
+    Draw,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Standing {
+    pub name: String,
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+    /// swiss rounds sat out, each scores as a win
+    pub byes: usize,
+    pub rating: f64,
+}
+
+impl Standing {
+    /// win or bye is one point, draw is half a point
+    pub fn points(&self) -> f64 {
+        (self.wins + self.byes) as f64 + self.draws as f64 * 0.5
+    }
+
+    pub fn games(&self) -> usize {
+        self.wins + self.draws + self.losses
+    }
+}
+
+/// win/draw/loss counts and elo ratings of tournament participants
+pub struct Standings {
+    standings: Vec<Standing>,
+    played_pairs: HashSet<(usize, usize)>,
+}
+
+impl Standings {
+    pub fn new(names: Vec<String>) -> Standings {
+        Standings {
+            standings: names
+                .into_iter()
+                .map(|name| Standing {
+                    name,
+                    wins: 0,
+                    draws: 0,
+                    losses: 0,
+                    byes: 0,
+                    rating: INITIAL_RATING,
+                })
+                .collect(),
+            played_pairs: HashSet::new(),
+        }
+    }
+
+    /// standings in participant order
+    pub fn standings(&self) -> &[Standing] {
+        &self.standings
+    }
+
+    /// record a game, ratings are updated right away
+    pub fn record_game(&mut self, first: usize, second: usize, outcome: GameOutcome) {
+        let score = match outcome {
+            GameOutcome::FirstWon => 1.0,
+            GameOutcome::SecondWon => 0.0,
+            GameOutcome::Draw => 0.5,
+        };
+        let expected = 1.0
+            / (1.0
+                + 10f64
+                    .powf((self.standings[second].rating - self.standings[first].rating) / 400.0));
+        let delta = ELO_K * (score - expected);
+        self.standings[first].rating += delta;
+        self.standings[second].rating -= delta;
+
+        match outcome {
+            GameOutcome::FirstWon => {
+                self.standings[first].wins += 1;
+                self.standings[second].losses += 1;
+            }
+            GameOutcome::SecondWon => {
+                self.standings[first].losses += 1;
+                self.standings[second].wins += 1;
+            }
+            GameOutcome::Draw => {
+                self.standings[first].draws += 1;
+                self.standings[second].draws += 1;
+            }
+        }
+        self.played_pairs
+            .insert((first.min(second), first.max(second)));
+    }
+
+    pub fn have_played(&self, first: usize, second: usize) -> bool {
+        self.played_pairs
+            .contains(&(first.min(second), first.max(second)))
+    }
+
+    /// record that participant sat a swiss round out, it scores as a win without a rating change
+    pub fn record_bye(&mut self, participant: usize) {
+        self.standings[participant].byes += 1;
+    }
+
+    /// participant indices from best to worst: by points, then by rating
+    pub fn ranking(&self) -> Vec<usize> {
+        let mut ranking: Vec<usize> = (0..self.standings.len()).collect();
+        ranking.sort_by(|&a, &b| {
+            let (a, b) = (&self.standings[a], &self.standings[b]);
+            b.points()
+                .total_cmp(&a.points())
+                .then(b.rating.total_cmp(&a.rating))
+        });
+        ranking
+    }
+
+    /// who sits next swiss round out with odd count:
+    /// the lowest ranked of those who had the fewest byes
+    pub fn swiss_bye(&self) -> Option<usize> {
+        if self.standings.len().is_multiple_of(2) {
+            return None;
+        }
+        self.ranking()
+            .into_iter()
+            .rev()
+            .min_by_key(|&x| self.standings[x].byes)
+    }
+
+    ///
+    /// pairings for next swiss round: participants are sorted by ranking
+    /// and each is paired with the closest one below it that it has not played yet,
+    /// if everyone was played - with the closest free one.
+    /// with odd count the swiss_bye participant is left out, it's up to caller to record_bye
+    ///
+    pub fn swiss_pairings(&self) -> Vec<(usize, usize)> {
+        let bye = self.swiss_bye();
+        let mut free: Vec<usize> = self
+            .ranking()
+            .into_iter()
+            .filter(|&x| Some(x) != bye)
+            .collect();
+        let mut pairings = Vec::with_capacity(free.len() / 2);
+        while free.len() > 1 {
+            let first = free.remove(0);
+            let second_i = free
+                .iter()
+                .position(|&x| !self.have_played(first, x))
+                .unwrap_or(0);
+            let second = free.remove(second_i);
+            pairings.push((first, second));
+        }
+        pairings
+    }
+
+    /// standings as a text table, best first
+    pub fn to_text(&self) -> String {
+        let name_width = self
+            .standings
+            .iter()
+            .map(|x| x.name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(4);
+        let mut ret = format!(
+            "{:>3}  {:<name_width$}  {:>5}  {:>4}  {:>4}  {:>4}  {:>4}  {:>6}  {:>7}\n",
+            "#", "name", "games", "win", "draw", "loss", "bye", "points", "rating"
+        );
+        for (place, i) in self.ranking().into_iter().enumerate() {
+            let standing = &self.standings[i];
+            let _ = writeln!(
+                ret,
+                "{:>3}  {:<name_width$}  {:>5}  {:>4}  {:>4}  {:>4}  {:>4}  {:>6.1}  {:>7.1}",
+                place + 1,
+                standing.name,
+                standing.games(),
+                standing.wins,
+                standing.draws,
+                standing.losses,
+                standing.byes,
+                standing.points(),
+                standing.rating
+            );
+        }
+        ret
+    }
+
+    /// standings as json array, best first
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        let ranked: Vec<&Standing> = self
+            .ranking()
+            .into_iter()
+            .map(|i| &self.standings[i])
+            .collect();
+        serde_json::to_string_pretty(&ranked)
+    }
+}
+
+/// every participant against every other once
+pub fn round_robin_pairings(count: usize) -> Vec<(usize, usize)> {
+    let mut pairings = Vec::with_capacity(count * count.saturating_sub(1) / 2);
+    for first in 0..count {
+        for second in first + 1..count {
+            pairings.push((first, second));
+        }
+    }
+    pairings
+}
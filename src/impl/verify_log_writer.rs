@@ -0,0 +1,129 @@
+use crate::gametime::GameTime;
+use crate::log_data::{LogRepresentable, LogWriter};
+
+use super::buf_battle_log_reader::LogRecord;
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// first place where re-simulated battle does not match the recorded log
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogDivergence {
+    /// 1-based record number in the recorded log, empty lines are not records
+    pub record: usize,
+    /// None if simulation produced more records than the log has
+    pub expected: Option<LogRecord>,
+    /// None if simulation ended before the log did
+    pub actual: Option<LogRecord>,
+}
+
+impl fmt::Display for LogDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "record {}: expected '", self.record)?;
+        match &self.expected {
+            Some(x) => write!(f, "{}", x)?,
+            None => write!(f, "<end of log>")?,
+        }
+        write!(f, "', got '")?;
+        match &self.actual {
+            Some(x) => write!(f, "{}", x)?,
+            None => write!(f, "<end of battle>")?,
+        }
+        write!(f, "'")
+    }
+}
+
+///
+/// instead of writing - checks every log line against a recorded log.
+/// object unique ids depend on what else was created in the process,
+/// so objects are compared by their order of first appearance instead of by uid
+///
+pub struct VerifyLogWriter {
+    expected: Vec<LogRecord>,
+    next_record: usize,
+    divergence: Option<LogDivergence>,
+    expected_uids: HashMap<String, usize>,
+    actual_uids: HashMap<String, usize>,
+}
+
+impl<LRO, LRA> LogWriter<LRO, LRA> for VerifyLogWriter
+where
+    LRO: LogRepresentable,
+    LRA: LogRepresentable,
+{
+    fn add_log_data(&mut self, object: LRO, action: LRA, time: GameTime, duration: GameTime) {
+        if self.divergence.is_some() {
+            // only first divergence is of interest
+            return;
+        }
+        let actual = LogRecord {
+            object: object.to_log_repr(),
+            action: action.to_log_repr(),
+            time,
+            duration,
+        };
+        let matches = match self.expected.get(self.next_record) {
+            Some(expected) => {
+                expected.action == actual.action
+                    && expected.time == actual.time
+                    && expected.duration == actual.duration
+                    && normalize_object(&mut self.expected_uids, &expected.object)
+                        == normalize_object(&mut self.actual_uids, &actual.object)
+            }
+            None => false,
+        };
+        if matches {
+            self.next_record += 1;
+        } else {
+            self.divergence = Some(LogDivergence {
+                record: self.next_record + 1,
+                expected: self.expected.get(self.next_record).cloned(),
+                actual: Some(actual),
+            });
+        }
+    }
+}
+
+impl VerifyLogWriter {
+    pub fn new(expected: Vec<LogRecord>) -> VerifyLogWriter {
+        VerifyLogWriter {
+            expected,
+            next_record: 0,
+            divergence: None,
+            expected_uids: HashMap::new(),
+            actual_uids: HashMap::new(),
+        }
+    }
+
+    /// to be called after simulation is done.
+    /// returns first divergence, including recorded log being longer than simulated one,
+    /// or None if simulation matched the log
+    pub fn divergence(&self) -> Option<LogDivergence> {
+        if let Some(divergence) = &self.divergence {
+            return Some(divergence.clone());
+        }
+        if self.next_record < self.expected.len() {
+            return Some(LogDivergence {
+                record: self.next_record + 1,
+                expected: Some(self.expected[self.next_record].clone()),
+                actual: None,
+            });
+        }
+        None
+    }
+}
+
+/// replaces trailing "(uid)" of object repr with the order of object's first appearance
+fn normalize_object(uids: &mut HashMap<String, usize>, object: &str) -> String {
+    let uid_start = match object.rfind('(') {
+        Some(i) if object.ends_with(')') => i,
+        _ => return object.to_owned(),
+    };
+    let uid = &object[uid_start + 1..object.len() - 1];
+    if uid.is_empty() || !uid.chars().all(|c| c.is_ascii_digit()) {
+        return object.to_owned();
+    }
+    let next_index = uids.len();
+    let index = *uids.entry(object.to_owned()).or_insert(next_index);
+    format!("{}#{}", &object[..uid_start], index)
+}
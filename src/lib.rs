@@ -12,6 +12,8 @@ pub mod maptile_logic;
 pub mod object_layer;
 pub mod orientation;
 pub mod player_state;
+mod program_stopper;
 pub mod script_repr;
 pub mod serialization;
+pub mod think_budget;
 mod battle_state_info;
\ No newline at end of file
@@ -1,3 +1,4 @@
+use super::battle::ProgramExit;
 use super::gametime::GameTime;
 
 pub trait LogRepresentable
@@ -43,6 +44,80 @@ impl LogRepresentable for String {
     }
 }
 
+/// command as it is logged: "name" or "name[argument]"
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogCommand {
+    pub name: String,
+    pub argument: Option<String>,
+}
+
+impl LogCommand {
+    pub fn new(name: &str, argument: Option<String>) -> LogCommand {
+        LogCommand {
+            name: name.to_owned(),
+            argument,
+        }
+    }
+}
+
+impl LogRepresentable for LogCommand {
+    fn log_repr(&self) -> String {
+        match &self.argument {
+            Some(argument) => format!("{}[{}]", self.name, argument),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// player commands that show up in the log, None for commands that are not logged
+pub trait ToLogCommand {
+    fn to_log_command(&self) -> Option<LogCommand>;
+}
+
+/// actions Battle itself writes to the log, battle logic may log any action type that has them
+pub trait BattleLogAction: LogRepresentable {
+    fn die() -> Self;
+    fn win() -> Self;
+    fn exit(program_exit: &ProgramExit) -> Self;
+    fn think_overrun(steps: u64) -> Self;
+    /// "-command[argument](id)"
+    fn command_start(command: LogCommand, command_id: usize) -> Self;
+    /// "+command[argument](id)", or "!command[argument](id)" if command failed
+    fn command_finish(command: LogCommand, command_id: usize, success: bool) -> Self;
+}
+
+/// plain text actions, exactly as they appear in the text log
+impl BattleLogAction for String {
+    fn die() -> Self {
+        "die".to_owned()
+    }
+
+    fn win() -> Self {
+        "win".to_owned()
+    }
+
+    fn exit(program_exit: &ProgramExit) -> Self {
+        program_exit.log_repr()
+    }
+
+    fn think_overrun(steps: u64) -> Self {
+        format!("think-overrun[{}]", steps)
+    }
+
+    fn command_start(command: LogCommand, command_id: usize) -> Self {
+        format!("-{}({})", command.log_repr(), command_id)
+    }
+
+    fn command_finish(command: LogCommand, command_id: usize, success: bool) -> Self {
+        format!(
+            "{}{}({})",
+            if success { "+" } else { "!" },
+            command.log_repr(),
+            command_id
+        )
+    }
+}
+
 // pub trait ToLogAction<LR>
 // where
 //     LR: LogRepresentable,
@@ -57,3 +132,13 @@ where
 {
     fn add_log_data(&mut self, object: LRO, action: LRA, time: GameTime, duration: GameTime);
 }
+
+impl<LRO, LRA> LogWriter<LRO, LRA> for Box<dyn LogWriter<LRO, LRA>>
+where
+    LRO: LogRepresentable,
+    LRA: LogRepresentable,
+{
+    fn add_log_data(&mut self, object: LRO, action: LRA, time: GameTime, duration: GameTime) {
+        (**self).add_log_data(object, action, time, duration)
+    }
+}
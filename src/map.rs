@@ -6,3 +6,10 @@ pub trait MapReadAccess<T> {
 pub trait MapWriteAccess<T> {
     fn set_tile_at(&mut self, x: i64, y: i64, val: T);
 }
+
+/// tile types that know if map objects may be placed on them
+pub trait ObjectPlacementTile {
+    fn can_hold_objects(&self) -> bool {
+        true
+    }
+}
@@ -1,9 +1,42 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
+/// player spawn point, orientation is one of "north", "east", "south", "west"
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpawnPoint {
+    pub x: i64,
+    pub y: i64,
+    pub orientation: String,
+}
+
+/// object kinds a map may place
+pub const KNOWN_OBJECT_KINDS: [&str; 2] = ["ammocrate", "healthcrate"];
+
+/// object placed on the map before battle starts, kind is one of KNOWN_OBJECT_KINDS
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObjectPlacement {
+    pub kind: String,
+    pub x: i64,
+    pub y: i64,
+}
+
+///
+/// map tiles with optional metadata.
+/// everything except rows and outer_value may be missing from map files
+///
+#[derive(Clone, Serialize, Deserialize)]
 pub struct MapData<T> {
     rows: Vec<Vec<T>>,
     outer_value: T,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    recommended_players: Option<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    spawns: Vec<SpawnPoint>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    objects: Vec<ObjectPlacement>,
 }
 
 impl<T> MapData<T>
@@ -15,10 +48,7 @@ where
         for row in rows {
             row_vecs.push(row.to_vec());
         }
-        MapData {
-            rows: row_vecs,
-            outer_value,
-        }
+        Self::new_with_rows(row_vecs, outer_value)
     }
 
     pub fn new_from_constant_rows(value: T, row_sizes: &[usize], outer_value: T) -> MapData<T> {
@@ -28,9 +58,18 @@ where
             row.resize(row_size, value);
             row_vecs.push(row);
         }
+        Self::new_with_rows(row_vecs, outer_value)
+    }
+
+    fn new_with_rows(rows: Vec<Vec<T>>, outer_value: T) -> MapData<T> {
         MapData {
-            rows: row_vecs,
+            rows,
             outer_value,
+            name: None,
+            author: None,
+            recommended_players: None,
+            spawns: Vec::new(),
+            objects: Vec::new(),
         }
     }
 
@@ -53,6 +92,47 @@ where
     pub fn set_outer_value(&mut self, value: T) {
         self.outer_value = value;
     }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn set_name(&mut self, name: Option<String>) {
+        self.name = name;
+    }
+
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    pub fn set_author(&mut self, author: Option<String>) {
+        self.author = author;
+    }
+
+    pub fn recommended_players(&self) -> Option<usize> {
+        self.recommended_players
+    }
+
+    pub fn set_recommended_players(&mut self, count: Option<usize>) {
+        self.recommended_players = count;
+    }
+
+    /// spawn points in player order, empty if map does not define them
+    pub fn spawns(&self) -> &[SpawnPoint] {
+        &self.spawns
+    }
+
+    pub fn set_spawns(&mut self, spawns: Vec<SpawnPoint>) {
+        self.spawns = spawns;
+    }
+
+    pub fn objects(&self) -> &[ObjectPlacement] {
+        &self.objects
+    }
+
+    pub fn set_objects(&mut self, objects: Vec<ObjectPlacement>) {
+        self.objects = objects;
+    }
 }
 
 #[cfg(test)]
@@ -62,10 +142,7 @@ mod tests {
 
     #[test]
     fn simple_ser() {
-        let data = MapData::<usize> {
-            rows: vec![vec![1, 2], vec![3, 4, 5], vec![]],
-            outer_value: 123,
-        };
+        let data = MapData::<usize>::new_with_rows(vec![vec![1, 2], vec![3, 4, 5], vec![]], 123);
 
         let ser = if let Ok(x) = serde_json::to_string(&data) {
             x
@@ -93,4 +170,42 @@ mod tests {
 
         assert_eq!(vec![vec![1, 2], vec![3, 4, 5], vec![]], data.rows);
     }
+    #[test]
+    fn metadata_de() {
+        let data: MapData<usize> = serde_json::from_str(
+            r#"{
+        "rows": [[0, 0], [0, 1]],
+        "outer_value": 1,
+        "name": "tiny",
+        "recommended_players": 2,
+        "spawns": [{"x": 0, "y": 0, "orientation": "east"}],
+        "objects": [{"kind": "ammocrate", "x": 1, "y": 0}]
+        }"#,
+        )
+        .unwrap();
+
+        assert_eq!(Some("tiny"), data.name());
+        assert_eq!(None, data.author());
+        assert_eq!(Some(2), data.recommended_players());
+        assert_eq!(
+            &[SpawnPoint {
+                x: 0,
+                y: 0,
+                orientation: "east".to_owned()
+            }],
+            data.spawns()
+        );
+        assert_eq!(
+            &[ObjectPlacement {
+                kind: "ammocrate".to_owned(),
+                x: 1,
+                y: 0
+            }],
+            data.objects()
+        );
+        assert_eq!(
+            r#"{"rows":[[0,0],[0,1]],"outer_value":1,"name":"tiny","recommended_players":2,"spawns":[{"x":0,"y":0,"orientation":"east"}],"objects":[{"kind":"ammocrate","x":1,"y":0}]}"#,
+            serde_json::to_string(&data).unwrap()
+        );
+    }
 }
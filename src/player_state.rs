@@ -10,4 +10,9 @@ pub trait PlayerControl
     fn set_resource(&mut self, res_id: usize, amount: u64);
 
     fn resource_value(&self, res_id: usize) -> u64;
+
+    /// players of the same team are allies, None means player is on its own
+    fn team(&self) -> Option<usize> {
+        None
+    }
 }
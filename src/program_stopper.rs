@@ -0,0 +1,81 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use rustpython_vm::builtins::PyBaseExceptionRef;
+use rustpython_vm::signal::{
+    user_signal_channel, UserSignal, UserSignalReceiver, UserSignalSender,
+};
+use rustpython_vm::VirtualMachine;
+
+///
+/// stops a player program running in a vm of another thread.
+///
+/// once stop is requested the program gets an exception on every next instruction,
+/// and on every call of injected builtins, so no try-except can keep it running.
+/// vm signal flag is shared by all vms of the process, so a signal may be
+/// noticed late - battle nudges stopping vms till their threads finish.
+///
+#[derive(Clone)]
+pub(crate) struct ProgramStopper {
+    stop_requested: Arc<AtomicBool>,
+    signal_sender: UserSignalSender,
+}
+
+/// vm thread side of a stopper: signals to install into the vm and the stopper to check
+pub(crate) struct ProgramStopReceiver {
+    pub stopper: ProgramStopper,
+    pub signal_receiver: UserSignalReceiver,
+}
+
+impl ProgramStopper {
+    /// stopper for the battle and its counterpart for the vm thread
+    pub fn channel() -> (ProgramStopper, ProgramStopReceiver) {
+        let (signal_sender, signal_receiver) = user_signal_channel();
+        let stopper = ProgramStopper {
+            stop_requested: Arc::new(AtomicBool::new(false)),
+            signal_sender,
+        };
+        (
+            stopper.clone(),
+            ProgramStopReceiver {
+                stopper,
+                signal_receiver,
+            },
+        )
+    }
+
+    pub fn is_stop_requested(&self) -> bool {
+        self.stop_requested.load(Ordering::Acquire)
+    }
+
+    /// error to raise in a program that is being stopped
+    pub fn stop_error(vm: &VirtualMachine) -> PyBaseExceptionRef {
+        vm.new_runtime_error("program stopped".to_owned())
+    }
+
+    pub fn request_stop(&self) {
+        if self.stop_requested.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        // error means vm is already gone
+        let _ = self
+            .signal_sender
+            .send(Self::stop_signal(self.signal_sender.clone()));
+    }
+
+    /// make vm check its signals again, in case other vm has eaten the trigger
+    pub fn nudge(&self) {
+        if self.is_stop_requested() {
+            let _ = self.signal_sender.send(Box::new(|_| Ok(())));
+        }
+    }
+
+    fn stop_signal(signal_sender: UserSignalSender) -> UserSignal {
+        Box::new(move |vm| {
+            // rearm, so exception is raised again at the very next instruction,
+            // even if this one gets caught
+            let _ = signal_sender.send(Self::stop_signal(signal_sender.clone()));
+            Err(Self::stop_error(vm))
+        })
+    }
+}
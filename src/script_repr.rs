@@ -2,6 +2,12 @@ pub trait ToScriptRepr {
     fn to_script_repr(&self) -> String;
 }
 
+/// script repr of a player as other player programs see it
+pub trait ToPlayerScriptRepr {
+    /// allies are told apart from enemies
+    fn to_player_script_repr(&self, ally: bool) -> String;
+}
+
 pub trait FromScriptRepr
 where
     Self: Sized,
@@ -0,0 +1,207 @@
+use std::convert::Infallible;
+use std::time::Duration;
+
+use rustpython_vm::compiler::codegen::error::CodegenErrorType;
+use rustpython_vm::compiler::parser::ast::{self, fold::Fold, Ranged};
+use rustpython_vm::compiler::parser::source_code::LinearLocator;
+use rustpython_vm::compiler::parser::text_size::{TextRange, TextSize};
+use rustpython_vm::compiler::parser::{lexer, Tok};
+use rustpython_vm::compiler::{self, codegen, CodeObject, CompileError, CompileErrorType};
+
+/// name of the read only attribute of None instrumented programs read on every step.
+/// it lives on the type, not in program globals, so programs have no way to replace it
+pub const STEP_FUNCTION_NAME: &str = "__step__";
+
+/// builtins that run code they get at runtime, step counter never sees that code,
+/// so they are not available to step counted programs
+pub const UNCOUNTED_CODE_BUILTINS: [&str; 3] = ["exec", "eval", "compile"];
+
+/// how much a player program may "think" between two game commands
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ThinkLimit {
+    /// real time a program may take before sending next command.
+    /// result depends on host load, so battles are not reproducible
+    WallClock(Duration),
+    /// deterministic budget of program steps, see StepBudget
+    Steps(StepBudget),
+}
+
+/// a step is a loop iteration, a function or lambda call or a comprehension item
+/// of the player program. builtins are not charged.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StepBudget {
+    /// steps a program may take between two commands without any penalty
+    pub free_steps: u64,
+    /// every started chunk of this many steps above free_steps
+    /// is one penalty unit, battle logic decides what a unit costs
+    pub steps_per_penalty: u64,
+    /// program is stopped once it takes this many steps without issuing a command
+    pub max_steps: u64,
+    /// last resort real time limit for code step counter cannot see into (like long running builtins).
+    /// hitting it makes battle not reproducible
+    pub hard_timeout: Option<Duration>,
+}
+
+impl StepBudget {
+    /// returns number of penalty units for given amount of steps taken
+    pub fn penalty_units(&self, steps: u64) -> u64 {
+        if steps <= self.free_steps {
+            return 0;
+        }
+        (steps - self.free_steps).div_ceil(self.steps_per_penalty.max(1))
+    }
+}
+
+///
+/// compile program the same way vm.compile does,
+/// but with None.STEP_FUNCTION_NAME read injected at the start of
+/// every loop body, every function and lambda body and every comprehension iteration.
+/// programs that mention STEP_FUNCTION_NAME themselves are rejected,
+/// the name is reserved for the counter
+///
+pub fn compile_with_steps(source: &str, source_path: String) -> Result<CodeObject, CompileError> {
+    let mut locator = LinearLocator::new(source);
+    let ast = match compiler::parser::parse(source, compiler::parser::Mode::Module, &source_path) {
+        Ok(x) => x,
+        Err(e) => return Err(locator.locate_error(e)),
+    };
+    if let Some(offset) = step_function_name_use(source) {
+        return Err(CompileError {
+            error: CompileErrorType::Codegen(CodegenErrorType::SyntaxError(format!(
+                "name {} is reserved for the think step counter",
+                STEP_FUNCTION_NAME
+            ))),
+            location: Some(locator.locate(offset)),
+            source_path,
+        });
+    }
+    let ast = StepInjector {}.fold_mod(ast).unwrap_or_else(|e| match e {});
+    let ast = locator.fold_mod(ast).unwrap_or_else(|e| match e {});
+    codegen::compile::compile_top(&ast, source_path, compiler::Mode::Exec, Default::default())
+        .map_err(|e| e.into())
+}
+
+/// returns position of the first name token equal to STEP_FUNCTION_NAME.
+/// names are checked on token level, so attributes, imports, globals and
+/// del statements are all covered
+fn step_function_name_use(source: &str) -> Option<TextSize> {
+    lexer::lex(source, compiler::parser::Mode::Module)
+        .flatten()
+        .find_map(|(tok, range)| match tok {
+            Tok::Name { name } if name == STEP_FUNCTION_NAME => Some(range.start()),
+            _ => None,
+        })
+}
+
+struct StepInjector {}
+
+impl StepInjector {
+    fn step_expr(range: TextRange) -> ast::Expr {
+        ast::Expr::Attribute(ast::ExprAttribute {
+            range,
+            value: Box::new(ast::Expr::Constant(ast::ExprConstant {
+                range,
+                value: ast::Constant::None,
+                kind: None,
+            })),
+            attr: ast::Identifier::new(STEP_FUNCTION_NAME),
+            ctx: ast::ExprContext::Load,
+        })
+    }
+
+    /// injected nodes must not go back in source position,
+    /// otherwise source locator is not happy
+    fn body_start(body: &[ast::Stmt]) -> TextRange {
+        TextRange::empty(body[0].start())
+    }
+
+    fn step_stmt(range: TextRange) -> ast::Stmt {
+        ast::Stmt::Expr(ast::StmtExpr {
+            range,
+            value: Box::new(Self::step_expr(range)),
+        })
+    }
+}
+
+impl Fold<TextRange> for StepInjector {
+    type TargetU = TextRange;
+    type Error = Infallible;
+    type UserContext = ();
+
+    fn will_map_user(&mut self, _user: &TextRange) -> Self::UserContext {}
+
+    fn map_user(&mut self, user: TextRange, _context: ()) -> Result<TextRange, Infallible> {
+        Ok(user)
+    }
+
+    fn fold_stmt_while(
+        &mut self,
+        node: ast::StmtWhile<TextRange>,
+    ) -> Result<ast::StmtWhile<TextRange>, Infallible> {
+        let mut node = ast::fold::fold_stmt_while(self, node)?;
+        node.body.insert(0, Self::step_stmt(Self::body_start(&node.body)));
+        Ok(node)
+    }
+
+    fn fold_stmt_for(
+        &mut self,
+        node: ast::StmtFor<TextRange>,
+    ) -> Result<ast::StmtFor<TextRange>, Infallible> {
+        let mut node = ast::fold::fold_stmt_for(self, node)?;
+        node.body.insert(0, Self::step_stmt(Self::body_start(&node.body)));
+        Ok(node)
+    }
+
+    fn fold_stmt_async_for(
+        &mut self,
+        node: ast::StmtAsyncFor<TextRange>,
+    ) -> Result<ast::StmtAsyncFor<TextRange>, Infallible> {
+        let mut node = ast::fold::fold_stmt_async_for(self, node)?;
+        node.body.insert(0, Self::step_stmt(Self::body_start(&node.body)));
+        Ok(node)
+    }
+
+    fn fold_stmt_function_def(
+        &mut self,
+        node: ast::StmtFunctionDef<TextRange>,
+    ) -> Result<ast::StmtFunctionDef<TextRange>, Infallible> {
+        let mut node = ast::fold::fold_stmt_function_def(self, node)?;
+        node.body.insert(0, Self::step_stmt(Self::body_start(&node.body)));
+        Ok(node)
+    }
+
+    fn fold_stmt_async_function_def(
+        &mut self,
+        node: ast::StmtAsyncFunctionDef<TextRange>,
+    ) -> Result<ast::StmtAsyncFunctionDef<TextRange>, Infallible> {
+        let mut node = ast::fold::fold_stmt_async_function_def(self, node)?;
+        node.body.insert(0, Self::step_stmt(Self::body_start(&node.body)));
+        Ok(node)
+    }
+
+    fn fold_expr_lambda(
+        &mut self,
+        node: ast::ExprLambda<TextRange>,
+    ) -> Result<ast::ExprLambda<TextRange>, Infallible> {
+        let mut node = ast::fold::fold_expr_lambda(self, node)?;
+        // step is True, so lambda still returns its body
+        let range = node.body.range();
+        node.body = Box::new(ast::Expr::BoolOp(ast::ExprBoolOp {
+            range,
+            op: ast::BoolOp::And,
+            values: vec![Self::step_expr(TextRange::empty(range.start())), *node.body],
+        }));
+        Ok(node)
+    }
+
+    fn fold_comprehension(
+        &mut self,
+        node: ast::Comprehension<TextRange>,
+    ) -> Result<ast::Comprehension<TextRange>, Infallible> {
+        let mut node = ast::fold::fold_comprehension(self, node)?;
+        // step is True, so it does not filter anything out
+        let range = TextRange::empty(node.iter.end());
+        node.ifs.insert(0, Self::step_expr(range));
+        Ok(node)
+    }
+}
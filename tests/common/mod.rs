@@ -1,3 +1,5 @@
+#![allow(dead_code)] // not every test uses every helper
+
 use std::collections::HashMap;
 use std::hash::Hash;
 use std::marker::PhantomData;
@@ -8,7 +10,7 @@ use battle_sim::maptile_logic::MaptileLogic;
 use battle_sim::r#impl::simple_battle_logic::CommandTimer;
 use battle_sim::script_repr::ToScriptRepr;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 pub enum SimpleTileType {
     Nothin,
     Wall,
@@ -24,30 +26,30 @@ impl ToScriptRepr for SimpleTileType {
     }
 }
 
-pub struct VecLogWriter<LO, LA>
-where
-    LO: LogRepresentable,
-    LA: LogRepresentable,
-{
-    pub log_datas: Vec<(LO, LA, GameTime, GameTime)>,
+impl LogRepresentable for SimpleTileType {
+    fn log_repr(&self) -> String {
+        self.to_script_repr()
+    }
 }
 
-impl<LO, LA> LogWriter<LO, LA> for VecLogWriter<LO, LA>
+/// keeps log lines in their text log form
+pub struct VecLogWriter {
+    pub log_datas: Vec<(String, String, GameTime, GameTime)>,
+}
+
+impl<LO, LA> LogWriter<LO, LA> for VecLogWriter
 where
     LO: LogRepresentable,
     LA: LogRepresentable,
 {
     fn add_log_data(&mut self, object: LO, action: LA, time: GameTime, duration: GameTime) {
-        self.log_datas.push((object, action, time, duration));
+        self.log_datas
+            .push((object.to_log_repr(), action.to_log_repr(), time, duration));
     }
 }
 
-impl<LO, LA> VecLogWriter<LO, LA>
-where
-    LO: LogRepresentable,
-    LA: LogRepresentable,
-{
-    pub fn new() -> VecLogWriter<LO, LA> {
+impl VecLogWriter {
+    pub fn new() -> VecLogWriter {
         VecLogWriter {
             log_datas: Vec::new(),
         }
@@ -55,13 +57,7 @@ where
 
     pub fn print(&self) {
         for (lobject, laction, time, duration) in &self.log_datas {
-            println!(
-                "{}\t{}\t{}\t{}",
-                time,
-                duration,
-                lobject.log_repr(),
-                laction.log_repr()
-            );
+            println!("{}\t{}\t{}\t{}", time, duration, lobject, laction);
         }
     }
 }
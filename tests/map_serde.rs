@@ -3,7 +3,8 @@ use std::path::PathBuf;
 use std::str::FromStr;
 
 use battle_sim::r#impl::grid_map::GridBattleMap;
-use battle_sim::map::MapWriteAccess;
+use battle_sim::r#impl::grid_orientation::GridOrientation;
+use battle_sim::map::{MapWriteAccess, ObjectPlacementTile};
 use battle_sim::serialization::{FromFile, ToFile};
 use serde::de::{self, Visitor, DeserializeOwned};
 use serde::{Deserialize, Serialize};
@@ -32,6 +33,12 @@ impl Serialize for SimpleTile {
     }
 }
 
+impl ObjectPlacementTile for SimpleTile {
+    fn can_hold_objects(&self) -> bool {
+        *self != SimpleTile::Wall
+    }
+}
+
 struct TileVisitor;
 
 impl<'de> Visitor<'de> for TileVisitor {
@@ -104,3 +111,83 @@ fn test_simple_de() {
         }
     }
 }
+
+#[test]
+fn test_spawns_and_objects_de() {
+    let json = r#"{
+        "rows": [[0, 0, 0], [0, 1, 0]],
+        "outer_value": 1,
+        "name": "tiny",
+        "author": "someone",
+        "spawns": [
+            {"x": 2, "y": 1, "orientation": "north"},
+            {"x": 0, "y": 0, "orientation": "south"}
+        ],
+        "objects": [{"kind": "ammocrate", "x": 1, "y": 0}]
+    }"#;
+    let map: GridBattleMap<SimpleTile> = GridBattleMap::load_from_reader(json.as_bytes()).unwrap();
+
+    assert_eq!(Some("tiny"), map.map_data().name());
+    assert_eq!(Some("someone"), map.map_data().author());
+    assert_eq!(None, map.map_data().recommended_players());
+    assert_eq!(1, map.map_data().objects().len());
+    assert_eq!(
+        vec![(2, 1, GridOrientation::North), (0, 0, GridOrientation::South)],
+        map.get_spawn_locations(2).unwrap()
+    );
+    assert_eq!(
+        vec![(2, 1, GridOrientation::North)],
+        map.get_spawn_locations(1).unwrap()
+    );
+    // map defines spawns for 2 players only
+    assert!(map.get_spawn_locations(3).is_err());
+}
+
+#[test]
+fn test_old_format_de() {
+    let json = r#"{"rows": [[0, 0, 0], [0, 1, 0]], "outer_value": 1}"#;
+    let map: GridBattleMap<SimpleTile> = GridBattleMap::load_from_reader(json.as_bytes()).unwrap();
+
+    assert_eq!(None, map.map_data().name());
+    assert!(map.map_data().spawns().is_empty());
+    assert_eq!(
+        vec![(0, 0, GridOrientation::East), (2, 1, GridOrientation::West)],
+        map.get_spawn_locations(2).unwrap()
+    );
+
+    let mut buf = Vec::new();
+    map.save_to_writer(&mut buf).unwrap();
+    assert_eq!(
+        r#"{"rows":[[0,0,0],[0,1,0]],"outer_value":1}"#,
+        String::from_utf8(buf).unwrap()
+    );
+}
+
+#[test]
+fn test_bad_spawns_de() {
+    for spawn in [
+        r#"{"x": 3, "y": 0, "orientation": "north"}"#,
+        r#"{"x": 0, "y": 0, "orientation": "up"}"#,
+        r#"{"x": 1, "y": 0, "orientation": "north"}"#,
+        r#"{"x": 0, "y": 0, "orientation": "east"}, {"x": 0, "y": 0, "orientation": "west"}"#,
+    ] {
+        let json = format!(r#"{{"rows": [[0, 1, 0]], "outer_value": 1, "spawns": [{spawn}]}}"#);
+        assert!(GridBattleMap::<SimpleTile>::load_from_reader(json.as_bytes()).is_err());
+    }
+    let json = r#"{"rows": [[0, 1, 2]], "outer_value": 1, "spawns": [{"x": 0, "y": 0, "orientation": "east"}, {"x": 2, "y": 0, "orientation": "west"}]}"#;
+    assert!(GridBattleMap::<SimpleTile>::load_from_reader(json.as_bytes()).is_ok());
+}
+
+#[test]
+fn test_bad_objects_de() {
+    for object in [
+        r#"{"kind": "ammocrate", "x": 3, "y": 0}"#,
+        r#"{"kind": "bomb", "x": 0, "y": 0}"#,
+        r#"{"kind": "healthcrate", "x": 1, "y": 0}"#,
+    ] {
+        let json = format!(r#"{{"rows": [[0, 1, 0]], "outer_value": 1, "objects": [{object}]}}"#);
+        assert!(GridBattleMap::<SimpleTile>::load_from_reader(json.as_bytes()).is_err());
+    }
+    let json = r#"{"rows": [[0, 1, 0]], "outer_value": 1, "objects": [{"kind": "healthcrate", "x": 2, "y": 0}]}"#;
+    assert!(GridBattleMap::<SimpleTile>::load_from_reader(json.as_bytes()).is_ok());
+}
@@ -0,0 +1,154 @@
+use std::path::Path;
+
+use battle_sim::map::MapReadAccess;
+use battle_sim::r#impl::grid_map::GridBattleMap;
+use battle_sim::r#impl::gridmap_text_file::{is_text_map_path, TextGridBattleMap};
+use battle_sim::r#impl::tile_types::TileType;
+use battle_sim::serialization::{FromFile, ToFile};
+
+const DUEL: &str = "\
+; two players, one ammo crate
+name: duel
+author: someone
+players: 2
+
+1..#...
+.~~%a,.
+..#:_=2
+";
+
+fn load(text: &str) -> std::io::Result<GridBattleMap<TileType>> {
+    TextGridBattleMap::load_from_reader(text.as_bytes()).map(|x| x.0)
+}
+
+fn save(map: &GridBattleMap<TileType>) -> std::io::Result<String> {
+    let mut buf = Vec::new();
+    TextGridBattleMap(map.clone()).save_to_writer(&mut buf)?;
+    Ok(String::from_utf8(buf).unwrap())
+}
+
+#[test]
+fn test_text_load() {
+    let map = load(DUEL).unwrap();
+    let data = map.map_data();
+    assert_eq!(3, data.row_count());
+    assert_eq!(7, data.row(0).len());
+    assert_eq!(Some("duel"), data.name());
+    assert_eq!(Some("someone"), data.author());
+    assert_eq!(Some(2), data.recommended_players());
+    assert_eq!(TileType::Wall, data.outer_value());
+    assert_eq!(TileType::Wall, map.get_tile_at(3, 0));
+    assert_eq!(TileType::Mud, map.get_tile_at(1, 1));
+    assert_eq!(TileType::BrickWall(3), map.get_tile_at(3, 1));
+    assert_eq!(TileType::Grass, map.get_tile_at(5, 1));
+    assert_eq!(TileType::Tracks, map.get_tile_at(3, 2));
+    assert_eq!(TileType::ThinIce, map.get_tile_at(4, 2));
+    assert_eq!(TileType::Water, map.get_tile_at(5, 2));
+    // spawns and objects stand on ground
+    assert_eq!(TileType::Ground, map.get_tile_at(0, 0));
+    assert_eq!(TileType::Ground, map.get_tile_at(4, 1));
+    assert_eq!(TileType::Ground, map.get_tile_at(6, 2));
+    // without facing header spawns look at the map center
+    let spawns: Vec<_> = data
+        .spawns()
+        .iter()
+        .map(|s| (s.x, s.y, s.orientation.as_str()))
+        .collect();
+    assert_eq!(vec![(0, 0, "east"), (6, 2, "west")], spawns);
+    let objects: Vec<_> = data
+        .objects()
+        .iter()
+        .map(|o| (o.kind.as_str(), o.x, o.y))
+        .collect();
+    assert_eq!(vec![("ammocrate", 4, 1)], objects);
+}
+
+#[test]
+fn test_text_roundtrip() {
+    let text = "\
+name: ring
+outer: ~
+facing: south north
+
+2.h+-
+.%#%.
+....1
+";
+    let map = load(text).unwrap();
+    assert_eq!(TileType::Mud, map.map_data().outer_value());
+    assert_eq!(TileType::BrickWall(2), map.get_tile_at(3, 0));
+    assert_eq!(TileType::BrickWall(1), map.get_tile_at(4, 0));
+    assert_eq!(
+        (4, 2),
+        (map.map_data().spawns()[0].x, map.map_data().spawns()[0].y)
+    );
+    assert_eq!("south", map.map_data().spawns()[0].orientation);
+    assert_eq!(text, save(&map).unwrap());
+}
+
+#[test]
+fn test_text_json_conversion() {
+    let map = load(DUEL).unwrap();
+    let mut json = Vec::new();
+    map.save_to_writer(&mut json).unwrap();
+    let from_json: GridBattleMap<TileType> =
+        GridBattleMap::load_from_reader(json.as_slice()).unwrap();
+    let text = save(&from_json).unwrap();
+    let back = load(&text).unwrap();
+    assert_eq!(map.map_data().spawns(), back.map_data().spawns());
+    assert_eq!(map.map_data().objects(), back.map_data().objects());
+    for y in 0..3 {
+        assert_eq!(map.map_data().row(y), back.map_data().row(y));
+    }
+}
+
+#[test]
+fn test_text_bad_maps() {
+    // rows of different length
+    assert!(load("...\n..\n").is_err());
+    // unknown character
+    assert!(load("..x\n...\n").is_err());
+    // spawn 2 without spawn 1
+    assert!(load("..2\n...\n").is_err());
+    // same spawn twice
+    assert!(load("1.1\n...\n").is_err());
+    // facing does not match spawn count
+    assert!(load("facing: east\n1.2\n...\n").is_err());
+    // header after rows
+    assert!(load("...\nname: late\n...\n").is_err());
+    // no rows at all
+    assert!(load("name: empty\n").is_err());
+}
+
+#[test]
+fn test_text_unrepresentable() {
+    let from_json = |json: &str| -> GridBattleMap<TileType> {
+        GridBattleMap::load_from_reader(json.as_bytes()).unwrap()
+    };
+    // unknown object kinds do not even load
+    let json = r#"{"rows":[[0,0],[0,0]],"outer_value":1,"objects":[{"kind":"mine","x":1,"y":1}]}"#;
+    assert!(GridBattleMap::<TileType>::load_from_reader(json.as_bytes()).is_err());
+    // object not on ground
+    let map = from_json(
+        r#"{"rows":[[0,0],[0,2]],"outer_value":1,"objects":[{"kind":"ammocrate","x":1,"y":1}]}"#,
+    );
+    assert!(save(&map).is_err());
+    // spawn not on ground
+    let map = from_json(
+        r#"{"rows":[[0,2],[0,0]],"outer_value":1,"spawns":[{"x":1,"y":0,"orientation":"east"}]}"#,
+    );
+    assert!(save(&map).is_err());
+    // object on top of a spawn
+    let map = from_json(
+        r#"{"rows":[[0,0],[0,0]],"outer_value":1,"spawns":[{"x":1,"y":0,"orientation":"east"}],"objects":[{"kind":"ammocrate","x":1,"y":0}]}"#,
+    );
+    assert!(save(&map).is_err());
+}
+
+#[test]
+fn test_text_map_path() {
+    assert!(is_text_map_path(Path::new("maps/duel.txt")));
+    assert!(is_text_map_path(Path::new("duel.map")));
+    assert!(!is_text_map_path(Path::new("duel.json")));
+    assert!(!is_text_map_path(Path::new("duel")));
+}
@@ -0,0 +1,76 @@
+use battle_sim::map::MapWriteAccess;
+use battle_sim::r#impl::ascii_render::{player_label, render_ascii, render_status};
+use battle_sim::r#impl::buf_battle_log_reader::read_log_events;
+use battle_sim::r#impl::grid_map::GridBattleMap;
+use battle_sim::r#impl::log_replay::LogReplay;
+use battle_sim::r#impl::tile_types::TileType;
+
+const LOG: &str = "\
+player[p1](0)\tspawn[0,1,east]\t0\t0
+player[p2](1)\tspawn[4,1,west]\t0\t0
+ammocrate(2)\tspawn[2,0,north]\t0\t0
+player[p1](0)\t-shoot(0)\t0\t5
+player[p1](0)\tshoot[0,1,4,1]\t3\t0
+player[p2](1)\tdying\t3\t0
+player[p2](1)\tdie\t10\t0
+player[p1](0)\twin\t10\t0
+";
+
+#[test]
+fn test_render_ascii() {
+    let mut map = GridBattleMap::new(5, 3, TileType::Ground, TileType::Wall);
+    map.set_tile_at(1, 2, TileType::Wall);
+    map.set_tile_at(3, 2, TileType::Mud);
+    let replay = LogReplay::new(read_log_events(LOG.as_bytes()).unwrap());
+
+    let state = replay.state_at(3);
+    assert_eq!(
+        "\
++----------+
+|. . a . . |
+|1>------2*|
+|. ##. ~~. |
++----------+
+",
+        render_ascii(&map, &state)
+    );
+    assert_eq!(
+        "\
+1 p1(0) at 0,1 east doing shoot till 5
+2 p2(1) at 4,1 west DYING
+",
+        render_status(&state)
+    );
+
+    let state = replay.state_at(10);
+    assert!(render_ascii(&map, &state).contains("|1>. . . 2x|"));
+    assert!(render_status(&state).ends_with("WINNERS: 0\n"));
+}
+
+#[test]
+fn test_render_ascii_tile_changes() {
+    let mut map = GridBattleMap::new(4, 1, TileType::Ground, TileType::Wall);
+    map.set_tile_at(2, 0, TileType::BrickWall(3));
+    map.set_tile_at(3, 0, TileType::BrickWall(3));
+    let log = "\
+player[p1](0)\tspawn[0,0,east]\t0\t0
+map\ttile-change[2,0,4]\t3\t0
+map\ttile-change[2,0,0]\t13\t0
+";
+    let replay = LogReplay::new(read_log_events(log.as_bytes()).unwrap());
+
+    assert!(render_ascii(&map, &replay.state_at(0)).contains("|1>. [][]|"));
+    assert!(render_ascii(&map, &replay.state_at(3)).contains("|1>. [][]|"));
+    assert!(render_ascii(&map, &replay.state_at(13)).contains("|1>. . []|"));
+}
+
+#[test]
+fn test_player_labels() {
+    assert_eq!('1', player_label(0));
+    assert_eq!('9', player_label(8));
+    assert_eq!('a', player_label(9));
+    assert_eq!('z', player_label(34));
+    // labels never wrap around to look like another player
+    assert_eq!('@', player_label(35));
+    assert_eq!('@', player_label(100));
+}
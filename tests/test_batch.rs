@@ -0,0 +1,131 @@
+use battle_sim::r#impl::battle_batch::{run_batch, run_batch_battle, run_in_pool, BatchBattle};
+use battle_sim::r#impl::buf_battle_log_reader::read_log_records;
+use battle_sim::r#impl::buf_battle_logger::BufferLogWriter;
+use battle_sim::r#impl::grid_map::GridBattleMap;
+use battle_sim::r#impl::standard_battle::{
+    new_standard_battle, step_think_limit, DEFAULT_OBJECTS_SEED,
+};
+use battle_sim::r#impl::tile_types::TileType;
+
+#[test]
+fn test_run_in_pool_keeps_order() {
+    let jobs: Vec<_> = (0..20_u64)
+        .map(|i| {
+            move || {
+                std::thread::sleep(std::time::Duration::from_millis(20 - i));
+                i * 2
+            }
+        })
+        .collect();
+    assert_eq!(
+        (0..20).map(|x| x * 2).collect::<Vec<_>>(),
+        run_in_pool(jobs, 3)
+    );
+}
+
+fn batch_battle(seed: Option<u64>) -> BatchBattle {
+    BatchBattle {
+        map: GridBattleMap::new(10, 10, TileType::Ground, TileType::Wall),
+        players: vec![
+            (
+                "roller".to_owned(),
+                "\
+while True:
+    if rand() < 0.5:
+        turn_cw()
+    else:
+        move_forward()
+    shoot()
+"
+                .to_owned(),
+            ),
+            (
+                "sitter".to_owned(),
+                "\
+while True:
+    turn_ccw()
+    shoot()
+"
+                .to_owned(),
+            ),
+        ],
+        seed,
+        time_limit: Some(500),
+        think_limit: Some(step_think_limit(1000)),
+        keep_log: true,
+    }
+}
+
+fn log_actions(log: &[u8]) -> Vec<String> {
+    read_log_records(log)
+        .unwrap()
+        .into_iter()
+        .map(|x| format!("{}\t{}\t{}", x.action, x.time, x.duration))
+        .collect()
+}
+
+#[test]
+fn test_run_batch() {
+    let battles: Vec<BatchBattle> = (0..6).map(|i| batch_battle(Some(i % 3))).collect();
+    let results = run_batch(battles, 3);
+    assert_eq!(6, results.len());
+
+    // same seed - same battle, no matter which worker ran it
+    for i in 0..3 {
+        let (a, b) = (
+            results[i].as_ref().unwrap(),
+            results[i + 3].as_ref().unwrap(),
+        );
+        assert_eq!(a.winners, b.winners);
+        assert_eq!(a.end_time, b.end_time);
+        assert_eq!(
+            log_actions(a.log.as_ref().unwrap()),
+            log_actions(b.log.as_ref().unwrap())
+        );
+    }
+    // and same as running it alone
+    let alone = run_batch_battle(batch_battle(Some(1))).unwrap();
+    assert_eq!(
+        log_actions(alone.log.as_ref().unwrap()),
+        log_actions(results[1].as_ref().unwrap().log.as_ref().unwrap())
+    );
+
+    // different seeds place objects differently
+    assert_ne!(
+        log_actions(results[0].as_ref().unwrap().log.as_ref().unwrap()),
+        log_actions(results[1].as_ref().unwrap().log.as_ref().unwrap())
+    );
+}
+
+#[test]
+fn test_run_batch_bad_battle() {
+    let mut battle = batch_battle(Some(0));
+    battle.players.clear();
+    battle.keep_log = false;
+    let results = run_batch(vec![battle, batch_battle(Some(0))], 2);
+    assert!(results[0].is_none());
+    assert!(results[1].as_ref().unwrap().log.is_some());
+}
+
+#[test]
+fn test_run_batch_without_seed() {
+    // no seed plays the same as a standard battle without set_seed
+    let battle = batch_battle(None);
+    let mut log = Vec::new();
+    {
+        let mut b = new_standard_battle(
+            battle.map.clone(),
+            battle.players.clone(),
+            DEFAULT_OBJECTS_SEED,
+            BufferLogWriter::new(&mut log),
+        )
+        .unwrap();
+        b.set_think_limit(step_think_limit(1000));
+        b.run_simulation_with_time_limit(battle.time_limit);
+    }
+    let result = run_batch_battle(battle).unwrap();
+    assert_eq!(log_actions(&log), log_actions(result.log.as_ref().unwrap()));
+    // while setting the seed also changes rand()
+    let seeded = run_batch_battle(batch_battle(Some(DEFAULT_OBJECTS_SEED))).unwrap();
+    assert_ne!(log_actions(&log), log_actions(seeded.log.as_ref().unwrap()));
+}
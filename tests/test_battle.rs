@@ -1,14 +1,24 @@
-use battle_sim::battle::DEFAULT_COMMAND_DURATION;
+use battle_sim::battle::{ProgramExit, DEFAULT_COMMAND_DURATION};
+use battle_sim::r#impl::battle_maptile_logic::ConfigurableMaptileLogic;
 use battle_sim::map_object::MapObject;
 use battle_sim::object_layer::ObjectLayer;
-use battle_sim::r#impl::grid_battle::{new_player, GridBattle};
+use battle_sim::r#impl::grid_battle::{new_player, new_team_player, GridBattle};
 use battle_sim::r#impl::grid_map::GridBattleMap;
 use battle_sim::r#impl::grid_map_prober::GridMapProber;
 use battle_sim::r#impl::grid_orientation::GridOrientation;
 use battle_sim::r#impl::simple_battle_logic::{
-    PlayerCommand, SimpleBattleLogic, MAX_LOG_LINE_LENGTH,
+    PlayerCommand, RadioSettings, SimpleBattleLogic, AMMO_RES, HEALTH_RES, MAX_LOG_LINE_LENGTH,
 };
 use battle_sim::r#impl::simple_battle_object_layer::SimpleBattleObjectLayer;
+use battle_sim::r#impl::simple_object::{ObjectCacheType, PickupEffect, SimpleObject};
+use battle_sim::r#impl::standard_battle::{new_standard_battle, DEFAULT_OBJECTS_SEED};
+use battle_sim::r#impl::tile_types::{TileType, BRICK_WALL_HIT_POINTS};
+use battle_sim::r#impl::tile_types_logic::TileTypeLogic;
+use battle_sim::map::MapWriteAccess;
+use battle_sim::player_state::PlayerControl;
+use battle_sim::maptile_logic::MaptileLogic;
+use battle_sim::serialization::FromFile;
+use battle_sim::think_budget::{StepBudget, ThinkLimit};
 use std::collections::HashMap;
 
 mod common;
@@ -597,6 +607,133 @@ fn test2players_bad_inf_loop() {
     println!("BATTLE LOG:");
     b.log_writer().print();
     assert_eq!(20, b.time());
+    assert_eq!(
+        &[Some(ProgramExit::Finished), Some(ProgramExit::ThinkTimeout)],
+        b.program_exits()
+    );
+}
+
+#[test]
+fn test_stop_program_catching_everything() {
+    let map = GridBattleMap::new(2, 2, SimpleTileType::Nothin, SimpleTileType::Nothin);
+    let logger = VecLogWriter::new();
+    let mut b = GridBattle::new(
+        SimpleBattleLogic::new(
+            map,
+            TestTrivialLogic {},
+            GridMapProber {},
+            SimpleBattleObjectLayer::new(),
+            FnCommandTimer::new(|com| match com {
+                PlayerCommand::Print(_) => 0,
+                _ => 10,
+            }),
+            0,
+            0,
+        ),
+        vec![
+            (
+                new_player(0, 0, GridOrientation::South, 0, 1, "player1"),
+                "turn_cw()\n".to_owned(),
+            ),
+            (
+                new_player(1, 1, GridOrientation::North, 0, 1, "player2"),
+                "\
+def stubborn():
+    while True:
+        try:
+            try:
+                turn_cw()
+            finally:
+                stubborn()
+        except BaseException:
+            pass
+stubborn()
+                "
+                .to_owned(),
+            ),
+        ],
+        logger,
+    );
+    b.set_think_limit(ThinkLimit::Steps(StepBudget {
+        free_steps: 100,
+        steps_per_penalty: 100,
+        max_steps: 1000,
+        hard_timeout: None,
+    }));
+    b.run_simulation_with_time_limit(Some(50));
+    assert_eq!(50, b.time());
+    assert_eq!(
+        &[Some(ProgramExit::Finished), Some(ProgramExit::Stopped)],
+        b.program_exits()
+    );
+}
+
+#[test]
+fn test_program_exit_reasons() {
+    let map = GridBattleMap::new(7, 7, TileType::Ground, TileType::Wall);
+    let programs = [
+        "check_ammo()\n",
+        "check_ammo()\nraise ValueError('bad\\tthing')\n",
+        "check_ammo()\nif True\n",
+        "check_ammo()\nwhile True:\n    pass\n",
+        "while True:\n    wait()\n",
+    ];
+    let mut b = new_standard_battle(
+        map,
+        programs
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (format!("p{}", i), x.to_string()))
+            .collect(),
+        DEFAULT_OBJECTS_SEED,
+        VecLogWriter::new(),
+    )
+    .unwrap();
+    b.set_think_limit(ThinkLimit::Steps(StepBudget {
+        free_steps: 100,
+        steps_per_penalty: 100,
+        max_steps: 1000,
+        hard_timeout: None,
+    }));
+    let result = b.run_simulation_with_time_limit(Some(50));
+    assert_eq!(Some(Vec::new()), result.winners);
+    assert_eq!(b.program_exits(), result.program_exits.as_slice());
+    assert_eq!(Some(ProgramExit::Finished), result.program_exits[0]);
+    assert!(matches!(
+        &result.program_exits[1],
+        Some(ProgramExit::Errored(e)) if e.contains("ValueError")
+    ));
+    assert!(matches!(result.program_exits[2], Some(ProgramExit::CompileError(_))));
+    assert_eq!(Some(ProgramExit::ThinkTimeout), result.program_exits[3]);
+    assert_eq!(Some(ProgramExit::Stopped), result.program_exits[4]);
+
+    // exit lines are logged when players are done, so they come in time order
+    let log_datas = &b.log_writer().log_datas;
+    let exit_of = |player: &str| -> (&str, u64) {
+        log_datas
+            .iter()
+            .find(|(obj, act, _, _)| obj.starts_with(player) && act.starts_with("exit["))
+            .map(|(_, act, time, _)| (act.as_str(), *time))
+            .unwrap()
+    };
+    // programs that ended on their own are done right after their last command
+    assert_eq!(("exit[finished]", 2), exit_of("player[p0]"));
+    // last line of the error, control characters replaced
+    assert_eq!(
+        ("exit[error,ValueError: bad_thing]", 2),
+        exit_of("player[p1]")
+    );
+    assert!(exit_of("player[p2]").0.starts_with("exit[compile-error,<embedded>: "));
+    assert_eq!(("exit[think-timeout]", 2), exit_of("player[p3]"));
+    assert_eq!(("exit[stopped]", 50), exit_of("player[p4]"));
+    assert_eq!(
+        5,
+        log_datas
+            .iter()
+            .filter(|(_, act, _, _)| act.starts_with("exit["))
+            .count()
+    );
+    assert!(log_datas.windows(2).all(|x| x[0].2 <= x[1].2));
 }
 
 #[test]
@@ -658,7 +795,8 @@ fn test_print_limit() {
         b.log_writer().log_datas[2].1.len()
     ); // check truncation
     assert_eq!("log[---next print will be muted and penalized with game time unless a valid game comand called---]", &b.log_writer().log_datas[6].1);
-    assert_eq!(32, b.log_writer().log_datas.len());
+    assert_eq!(33, b.log_writer().log_datas.len());
+    assert_eq!("exit[finished]", &b.log_writer().log_datas[32].1);
 }
 
 #[test]
@@ -814,7 +952,7 @@ while True:\n
     assert!(!b.is_player_dead(1));
 
     let log_lines = &b.log_writer().log_datas;
-    assert!(8 == log_lines.len());
+    assert!(10 == log_lines.len());
     assert!(log_lines[0].1.starts_with("spawn"));
     assert!(log_lines[1].1.starts_with("spawn"));
     assert!(log_lines[2].1.starts_with("-move-forward"));
@@ -822,7 +960,509 @@ while True:\n
     assert!(log_lines[4].1.starts_with("shoot"));
     assert!(log_lines[5].1.starts_with("die"));
     assert!(log_lines[6].1.starts_with("win"));
-    assert!(log_lines[7].1.starts_with("+shoot"));
+    // dead player is stopped at its death, winner once its last command is done
+    assert_eq!(
+        (log_lines[5].0.as_str(), "exit[stopped]", log_lines[5].2),
+        (log_lines[7].0.as_str(), log_lines[7].1.as_str(), log_lines[7].2)
+    );
+    assert!(log_lines[8].1.starts_with("+shoot"));
+    assert_eq!("exit[stopped]", log_lines[9].1);
+}
+
+#[test]
+fn test_thinking_players_stopped_at_game_end() {
+    // winner goes on thinking after the game is decided, battle must not wait for it
+    let map = GridBattleMap::new(5, 1, TileType::Ground, TileType::Wall);
+    let mut b = GridBattle::new(
+        SimpleBattleLogic::new(
+            map,
+            TileTypeLogic::new(),
+            GridMapProber {},
+            SimpleBattleObjectLayer::new(),
+            FnCommandTimer::new(|_| 10),
+            1,
+            0,
+        ),
+        vec![
+            (
+                new_player(0, 0, GridOrientation::East, 3, 1, "shooter"),
+                "shoot()\nwhile True:\n    pass\n".to_owned(),
+            ),
+            (
+                new_player(3, 0, GridOrientation::West, 3, 1, "target"),
+                "while True:\n    wait()\n".to_owned(),
+            ),
+        ],
+        VecLogWriter::new(),
+    );
+    b.set_think_limit(ThinkLimit::Steps(StepBudget {
+        free_steps: 100,
+        steps_per_penalty: 100,
+        max_steps: u64::MAX,
+        hard_timeout: None,
+    }));
+    let result = b.run_simulation();
+    println!("BATTLE LOG:");
+    b.log_writer().print();
+
+    assert_eq!(Some(vec![0]), result.winners);
+    assert_eq!(Some(ProgramExit::Stopped), result.program_exits[0]);
+    assert_eq!(Some(ProgramExit::Stopped), result.program_exits[1]);
+    let win_time = b
+        .log_writer()
+        .log_datas
+        .iter()
+        .find(|(_, act, _, _)| act == "win")
+        .unwrap()
+        .2;
+    assert!(b
+        .log_writer()
+        .log_datas
+        .iter()
+        .filter(|(_, act, _, _)| act.starts_with("exit["))
+        .all(|(_, _, time, _)| *time <= win_time));
+}
+
+#[test]
+fn test_teams_last_team_standing() {
+    let map = GridBattleMap::new(6, 6, SimpleTileType::Nothin, SimpleTileType::Nothin);
+    let logger = VecLogWriter::new();
+    let mut b = GridBattle::new(
+        SimpleBattleLogic::new(
+            map,
+            TestTrivialLogic {},
+            GridMapProber {},
+            SimpleBattleObjectLayer::new(),
+            FnCommandTimer::new(|com| match com {
+                PlayerCommand::Shoot => 5,
+                PlayerCommand::AfterShootCooldown => 30,
+                PlayerCommand::Print(_) => 0,
+                _ => 10,
+            }),
+            1,
+            0,
+        ),
+        vec![
+            (
+                new_team_player(5, 1, GridOrientation::West, 1, 1, "shooter", 0),
+                "while True:\n    shoot()\n".to_owned(),
+            ),
+            (
+                new_team_player(1, 1, GridOrientation::North, 1, 1, "target", 1),
+                "while True:\n    wait()\n".to_owned(),
+            ),
+            (
+                new_team_player(5, 4, GridOrientation::North, 1, 1, "idler", 0),
+                "while True:\n    wait()\n".to_owned(),
+            ),
+        ],
+        logger,
+    );
+    let winners = b.run_simulation_with_time_limit(Some(100)).winners;
+    println!("BATTLE LOG:");
+    b.log_writer().print();
+
+    assert!(b.is_player_dead(1));
+    assert_eq!(Some(vec![0, 2]), winners);
+    let log_lines = &b.log_writer().log_datas;
+    assert_eq!("team[0]", log_lines[1].1);
+    assert_eq!("team[1]", log_lines[3].1);
+}
+
+#[test]
+fn test_teams_friendly_fire() {
+    for friendly_fire in [false, true] {
+        let map = GridBattleMap::new(6, 6, SimpleTileType::Nothin, SimpleTileType::Nothin);
+        let logger = VecLogWriter::new();
+        let mut logic = SimpleBattleLogic::new(
+            map,
+            TestTrivialLogic {},
+            GridMapProber {},
+            SimpleBattleObjectLayer::new(),
+            FnCommandTimer::new(|com| match com {
+                PlayerCommand::Shoot => 5,
+                PlayerCommand::AfterShootCooldown => 30,
+                PlayerCommand::Print(_) => 0,
+                _ => 10,
+            }),
+            1,
+            0,
+        );
+        logic.set_friendly_fire(friendly_fire);
+        let mut b = GridBattle::new(
+            logic,
+            vec![
+                (
+                    new_team_player(5, 1, GridOrientation::West, 1, 1, "shooter", 0),
+                    // let others look around first
+                    "wait()\nwhile True:\n    shoot()\n".to_owned(),
+                ),
+                (
+                    new_team_player(3, 1, GridOrientation::West, 1, 1, "ally", 0),
+                    "print(look('back'))\nwhile True:\n    wait()\n".to_owned(),
+                ),
+                (
+                    new_team_player(1, 1, GridOrientation::North, 1, 1, "enemy", 1),
+                    "print(look('right'))\nwhile True:\n    wait()\n".to_owned(),
+                ),
+            ],
+            logger,
+        );
+        b.run_simulation_with_time_limit(Some(100));
+        println!("BATTLE LOG:");
+        b.log_writer().print();
+
+        assert_eq!(friendly_fire, b.is_player_dead(1));
+        assert!(!b.is_player_dead(2));
+        let log_lines = &b.log_writer().log_datas;
+        assert!(log_lines
+            .iter()
+            .any(|(obj, act, _, _)| obj.starts_with("player[ally]")
+                && act.contains("ally[shooter][front]")));
+        assert!(log_lines
+            .iter()
+            .any(|(obj, act, _, _)| obj.starts_with("player[enemy]")
+                && act.contains("player[ally][front]")));
+    }
+}
+
+#[test]
+fn test_radio_messages() {
+    let map = GridBattleMap::new(6, 6, SimpleTileType::Nothin, SimpleTileType::Nothin);
+    let logger = VecLogWriter::new();
+    let mut logic = SimpleBattleLogic::new(
+        map,
+        TestTrivialLogic {},
+        GridMapProber {},
+        SimpleBattleObjectLayer::new(),
+        FnCommandTimer::new(|com| match com {
+            PlayerCommand::Print(_) => 0,
+            _ => 10,
+        }),
+        1,
+        0,
+    );
+    logic.set_radio_settings(RadioSettings {
+        delivery_delay: 5,
+        range: Some(3),
+        max_message_length: 5,
+        max_inbox_size: 2,
+    });
+    let listener_program = "\
+for _ in range(6):
+    wait()
+print(receive_messages())
+";
+    let mut b = GridBattle::new(
+        logic,
+        vec![
+            (
+                new_team_player(0, 0, GridOrientation::North, 0, 1, "A", 0),
+                "send_message('one')\nsend_message('two')\nsend_message('three-four')\n"
+                    .to_owned(),
+            ),
+            (
+                new_team_player(1, 0, GridOrientation::North, 0, 1, "B", 0),
+                listener_program.to_owned(),
+            ),
+            (
+                new_team_player(5, 5, GridOrientation::North, 0, 1, "C", 0),
+                listener_program.to_owned(),
+            ),
+            (
+                new_team_player(0, 1, GridOrientation::North, 0, 1, "D", 1),
+                listener_program.to_owned(),
+            ),
+        ],
+        logger,
+    );
+    b.run_simulation();
+    println!("BATTLE LOG:");
+    b.log_writer().print();
+
+    let log_lines = &b.log_writer().log_datas;
+    let action_of = |name: &str, prefix: &str| {
+        log_lines
+            .iter()
+            .find(|(obj, act, _, _)| obj.starts_with(name) && act.starts_with(prefix))
+            .map(|(_, act, _, _)| act.clone())
+    };
+    assert_eq!(Some("radio[one]".to_owned()), action_of("player[A]", "radio"));
+    assert_eq!(
+        Some("log[[('player[A]', 'two'), ('player[A]', 'three')]]".to_owned()),
+        action_of("player[B]", "log")
+    );
+    assert_eq!(Some("log[[]]".to_owned()), action_of("player[C]", "log"));
+    assert_eq!(Some("log[[]]".to_owned()), action_of("player[D]", "log"));
+}
+
+#[test]
+fn test_pickups() {
+    let map = GridBattleMap::new(6, 6, SimpleTileType::Nothin, SimpleTileType::Nothin);
+    let mut object_layer = SimpleBattleObjectLayer::new();
+    object_layer.add(SimpleObject::new(
+        2,
+        1,
+        GridOrientation::North,
+        ObjectCacheType::Pickup(PickupEffect::new(HEALTH_RES, 2)),
+        false,
+        true,
+        false,
+    ));
+    object_layer.add(SimpleObject::new(
+        3,
+        4,
+        GridOrientation::North,
+        ObjectCacheType::Pickup(PickupEffect::new(HEALTH_RES, 2)),
+        false,
+        true,
+        true,
+    ));
+    let logger = VecLogWriter::new();
+    let mut picker = new_player(1, 1, GridOrientation::East, 0, 3, "picker");
+    picker.set_resource(HEALTH_RES, 1);
+    let mut b = GridBattle::new(
+        SimpleBattleLogic::new(
+            map,
+            TestTrivialLogic {},
+            GridMapProber {},
+            object_layer,
+            FnCommandTimer::new(|com| match com {
+                PlayerCommand::Print(_) => 0,
+                _ => 10,
+            }),
+            0,
+            0,
+        ),
+        vec![
+            (
+                picker,
+                "move_forward()\nprint(check_health())\n".to_owned(),
+            ),
+            (
+                new_player(0, 4, GridOrientation::East, 1, 1, "shooter"),
+                "shoot()\n".to_owned(),
+            ),
+        ],
+        logger,
+    );
+    b.run_simulation();
+    println!("BATTLE LOG:");
+    b.log_writer().print();
+
+    let log_lines = &b.log_writer().log_datas;
+    let has_line = |obj_prefix: &str, action: &str| {
+        log_lines
+            .iter()
+            .any(|(obj, act, _, _)| obj.starts_with(obj_prefix) && act == action)
+    };
+    assert!(has_line("healthcrate", "spawn[2,1,north]"));
+    assert!(has_line("healthcrate", "picked"));
+    assert!(has_line("healthcrate", "break"));
+    assert!(has_line("player[picker]", "log[3]"));
+}
+
+#[test]
+fn test_health_pickup_limits() {
+    let map = GridBattleMap::new(6, 6, SimpleTileType::Nothin, SimpleTileType::Nothin);
+    let mut object_layer = SimpleBattleObjectLayer::new();
+    for y in [1, 3] {
+        object_layer.add(SimpleObject::new(
+            2,
+            y,
+            GridOrientation::North,
+            ObjectCacheType::Pickup(PickupEffect::new(HEALTH_RES, 2)),
+            false,
+            true,
+            false,
+        ));
+    }
+    let logger = VecLogWriter::new();
+    let mut b = GridBattle::new(
+        SimpleBattleLogic::new(
+            map,
+            TestTrivialLogic {},
+            GridMapProber {},
+            object_layer,
+            FnCommandTimer::new(|com| match com {
+                PlayerCommand::Print(_) => 0,
+                _ => 10,
+            }),
+            0,
+            100,
+        ),
+        vec![
+            (
+                new_player(1, 1, GridOrientation::East, 0, 1, "victim"),
+                "\
+for _ in range(10):
+    if check_health() == 0:
+        break
+    wait()
+move_forward()
+print(check_health())
+for _ in range(20):
+    wait()
+"
+                .to_owned(),
+            ),
+            (
+                new_player(1, 5, GridOrientation::North, 1, 1, "shooter"),
+                "shoot()\n".to_owned(),
+            ),
+            (
+                new_player(3, 3, GridOrientation::West, 0, 2, "healthy"),
+                "wait()\nmove_forward()\nprint(check_health())\n".to_owned(),
+            ),
+        ],
+        logger,
+    );
+    b.run_simulation();
+    println!("BATTLE LOG:");
+    b.log_writer().print();
+
+    let log_lines = &b.log_writer().log_datas;
+    let has_line = |obj_prefix: &str, action: &str| {
+        log_lines
+            .iter()
+            .any(|(obj, act, _, _)| obj.starts_with(obj_prefix) && act == action)
+    };
+    // health does not grow above the initial one
+    assert!(has_line("player[healthy]", "log[2]"));
+    // dying tank picks the crate, but is not brought back to life
+    assert!(has_line("player[victim]", "dying"));
+    assert!(has_line("player[victim]", "log[0]"));
+    assert!(has_line("player[victim]", "die"));
+    assert_eq!(
+        2,
+        log_lines
+            .iter()
+            .filter(|(obj, act, _, _)| obj.starts_with("healthcrate") && act == "picked")
+            .count()
+    );
+}
+
+#[test]
+fn test_pickup_respawn() {
+    let map = GridBattleMap::new(6, 6, SimpleTileType::Nothin, SimpleTileType::Nothin);
+    let mut object_layer = SimpleBattleObjectLayer::new();
+    for y in [1, 3] {
+        object_layer.add(SimpleObject::new(
+            2,
+            y,
+            GridOrientation::North,
+            ObjectCacheType::Pickup(PickupEffect::new(AMMO_RES, 3)),
+            false,
+            true,
+            false,
+        ));
+    }
+    let logger = VecLogWriter::new();
+    let mut logic = SimpleBattleLogic::new(
+        map,
+        TestTrivialLogic {},
+        GridMapProber {},
+        object_layer,
+        FnCommandTimer::new(|com| match com {
+            PlayerCommand::Print(_) => 0,
+            _ => 10,
+        }),
+        0,
+        0,
+    );
+    logic.set_pickup_respawn_delay(Some(30));
+    let mut b = GridBattle::new(
+        logic,
+        vec![
+            (
+                new_player(1, 1, GridOrientation::East, 0, 1, "leaver"),
+                "move_forward()\nmove_back()\nfor _ in range(5):\n    wait()\n".to_owned(),
+            ),
+            (
+                new_player(1, 3, GridOrientation::East, 0, 1, "camper"),
+                "move_forward()\nfor _ in range(5):\n    wait()\nmove_back()\nwait()\n"
+                    .to_owned(),
+            ),
+        ],
+        logger,
+    );
+    b.run_simulation();
+    println!("BATTLE LOG:");
+    b.log_writer().print();
+
+    let log_lines = &b.log_writer().log_datas;
+    let spawn_times = |action: &str| -> Vec<u64> {
+        log_lines
+            .iter()
+            .filter(|(obj, act, _, _)| obj.starts_with("ammocrate") && act == action)
+            .map(|(_, _, time, _)| *time)
+            .collect()
+    };
+    let picks: Vec<u64> = log_lines
+        .iter()
+        .filter(|(obj, act, _, _)| obj.starts_with("ammocrate") && act == "picked")
+        .map(|(_, _, time, _)| *time)
+        .collect();
+    assert_eq!(2, picks.len());
+    // crate left alone comes back right after the delay
+    assert_eq!(vec![0, picks[0] + 30], spawn_times("spawn[2,1,north]"));
+    // crate with a tank sitting on its place waits till the place is free
+    let camper_spawns = spawn_times("spawn[2,3,north]");
+    assert_eq!(2, camper_spawns.len());
+    assert!(camper_spawns[1] > picks[1] + 30);
+}
+
+#[test]
+fn test_pickup_not_respawned_on_impassable_tile() {
+    let mut map = GridBattleMap::new(4, 1, TileType::Ground, TileType::Wall);
+    map.set_tile_at(1, 0, TileType::ThinIce);
+    let mut object_layer = SimpleBattleObjectLayer::new();
+    object_layer.add(SimpleObject::new(
+        1,
+        0,
+        GridOrientation::North,
+        ObjectCacheType::Pickup(PickupEffect::new(AMMO_RES, 3)),
+        false,
+        true,
+        false,
+    ));
+    let logger = VecLogWriter::new();
+    let mut logic = SimpleBattleLogic::new(
+        map,
+        TileTypeLogic::new(),
+        GridMapProber {},
+        object_layer,
+        FnCommandTimer::new(|com| match com {
+            PlayerCommand::Print(_) => 0,
+            _ => 10,
+        }),
+        0,
+        0,
+    );
+    logic.set_pickup_respawn_delay(Some(30));
+    let mut b = GridBattle::new(
+        logic,
+        vec![(
+            new_player(0, 0, GridOrientation::East, 0, 1, "skater"),
+            "move_forward()\nmove_forward()\nfor _ in range(10):\n    wait()\n".to_owned(),
+        )],
+        logger,
+    );
+    b.run_simulation();
+    println!("BATTLE LOG:");
+    b.log_writer().print();
+
+    let log_lines = &b.log_writer().log_datas;
+    let count = |obj_prefix: &str, action: &str| {
+        log_lines
+            .iter()
+            .filter(|(obj, act, _, _)| obj.starts_with(obj_prefix) && act == action)
+            .count()
+    };
+    assert_eq!(1, count("ammocrate", "picked"));
+    assert_eq!(1, count("map", "tile-change[1,0,9]"));
+    // ice under the crate melted into water, crate is gone for good
+    assert_eq!(1, count("ammocrate", "spawn[1,0,north]"));
 }
 
 #[test]
@@ -954,3 +1594,427 @@ if all == 51179:\n
     assert_eq!(0, b.player_state(0).position().0);
     assert_eq!(2, b.player_state(0).position().1);
 }
+
+#[test]
+fn test_think_steps_inf_loop() {
+    let map = GridBattleMap::new(2, 2, SimpleTileType::Nothin, SimpleTileType::Nothin);
+    let logger = VecLogWriter::new();
+    let mut b = GridBattle::new(
+        SimpleBattleLogic::new(
+            map,
+            TestTrivialLogic {},
+            GridMapProber {},
+            SimpleBattleObjectLayer::new(),
+            FnCommandTimer::new(|com| match com {
+                PlayerCommand::Print(_) => 0,
+                _ => 10,
+            }),
+            0,
+            0,
+        ),
+        vec![
+            (
+                new_player(0, 0, GridOrientation::South, 0, 1, "player1"),
+                "\
+                turn_cw()\n\
+                turn_cw()\n\
+                "
+                .to_owned(),
+            ),
+            (
+                new_player(2, 2, GridOrientation::North, 0, 1, "player2"),
+                "\
+                print('second loops')\n\
+                while True:
+                    try:
+                        while True: pass
+                    except:
+                        continue
+                "
+                .to_owned(),
+            ),
+        ],
+        logger,
+    );
+    // no wall clock timeout at all - only step budget can stop the loop
+    b.set_think_limit(ThinkLimit::Steps(StepBudget {
+        free_steps: 100,
+        steps_per_penalty: 100,
+        max_steps: 10000,
+        hard_timeout: None,
+    }));
+    b.run_simulation();
+    println!("BATTLE LOG:");
+    b.log_writer().print();
+    assert_eq!(20, b.time());
+    assert_eq!(GridOrientation::North, b.player_state(0).orientation);
+}
+
+#[test]
+fn test_think_steps_penalty() {
+    let map = GridBattleMap::new(2, 2, SimpleTileType::Nothin, SimpleTileType::Nothin);
+    let logger = VecLogWriter::new();
+    let mut b = GridBattle::new(
+        SimpleBattleLogic::new(
+            map,
+            TestTrivialLogic {},
+            GridMapProber {},
+            SimpleBattleObjectLayer::new(),
+            FnCommandTimer::new(|com| match com {
+                PlayerCommand::Print(_) => 0,
+                PlayerCommand::Time => 0,
+                _ => 10,
+            }),
+            0,
+            0,
+        ),
+        vec![(
+            new_player(0, 0, GridOrientation::South, 0, 1, "player1"),
+            "\
+def foo(x):
+    return x + 1
+for i in range(50):
+    foo(i)
+turn_cw()  # 100 steps - free
+x = [foo(i) for i in range(60)]
+time()  # free command, steps are not paid yet
+for i in range(30):
+    pass
+turn_cw()  # 150 steps - 1 penalty unit
+            "
+            .to_owned(),
+        )],
+        logger,
+    );
+    b.set_think_limit(ThinkLimit::Steps(StepBudget {
+        free_steps: 100,
+        steps_per_penalty: 100,
+        max_steps: 10000,
+        hard_timeout: None,
+    }));
+    b.run_simulation();
+    println!("BATTLE LOG:");
+    b.log_writer().print();
+    assert_eq!(10 + 10 + 10, b.time());
+    assert_eq!(GridOrientation::North, b.player_state(0).orientation);
+
+    let log_lines = &b.log_writer().log_datas;
+    assert_eq!(
+        1,
+        log_lines
+            .iter()
+            .filter(|x| x.1.starts_with("think-overrun"))
+            .count()
+    );
+    let overrun_i = log_lines
+        .iter()
+        .position(|x| x.1 == "think-overrun[150]")
+        .unwrap();
+    assert!(log_lines[overrun_i + 1].1.starts_with("-wait"));
+}
+
+#[test]
+fn test_think_steps_counter_cannot_be_replaced() {
+    let map = GridBattleMap::new(7, 7, TileType::Ground, TileType::Wall);
+    let programs = [
+        "__step__ = lambda: True\nfor i in range(100000):\n    pass\nturn_cw()\n",
+        "del __step__\nfor i in range(100000):\n    pass\nturn_cw()\n",
+        "def f():\n    global __step__\nturn_cw()\n",
+        "g = globals()\ng['__st' + 'ep__'] = lambda: True\nwhile True:\n    pass\n",
+        "setattr(type(None), '__st' + 'ep__', True)\nwhile True:\n    pass\n",
+        "list(map(lambda x: x, range(100000)))\nturn_cw()\n",
+        "exec('while True:\\n    pass\\n')\n",
+        "eval('1')\n",
+        "compile('1', 'x', 'eval')\n",
+    ];
+    let mut b = new_standard_battle(
+        map,
+        programs
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (format!("p{}", i), x.to_string()))
+            .collect(),
+        DEFAULT_OBJECTS_SEED,
+        VecLogWriter::new(),
+    )
+    .unwrap();
+    b.set_think_limit(ThinkLimit::Steps(StepBudget {
+        free_steps: 100,
+        steps_per_penalty: 100,
+        max_steps: 1000,
+        hard_timeout: None,
+    }));
+    let result = b.run_simulation_with_time_limit(Some(50));
+    println!("BATTLE LOG:");
+    b.log_writer().print();
+    for i in 0..3 {
+        assert!(matches!(
+            &result.program_exits[i],
+            Some(ProgramExit::CompileError(e)) if e.contains("reserved for the think step counter")
+        ));
+    }
+    // counter is an attribute of None, global with the same name changes nothing
+    assert_eq!(Some(ProgramExit::ThinkTimeout), result.program_exits[3]);
+    // and builtin types can't be changed
+    assert!(matches!(
+        &result.program_exits[4],
+        Some(ProgramExit::Errored(e)) if e.contains("TypeError")
+    ));
+    // lambdas are counted too
+    assert_eq!(Some(ProgramExit::ThinkTimeout), result.program_exits[5]);
+    // code made at runtime would not be counted
+    for i in 6..9 {
+        assert!(matches!(
+            &result.program_exits[i],
+            Some(ProgramExit::Errored(e)) if e.contains("NameError")
+        ));
+    }
+    // nobody got to turn
+    assert_eq!(0, b.time());
+}
+
+#[test]
+fn test_shooting_brick_wall() {
+    let mut map = GridBattleMap::new(5, 1, TileType::Ground, TileType::Wall);
+    map.set_tile_at(3, 0, TileType::BrickWall(2));
+    let logger = VecLogWriter::new();
+    let mut b = GridBattle::new(
+        SimpleBattleLogic::new(
+            map,
+            TileTypeLogic::new(),
+            GridMapProber {},
+            SimpleBattleObjectLayer::new(),
+            FnCommandTimer::new(|_| 10),
+            0,
+            0,
+        ),
+        vec![(
+            new_player(0, 0, GridOrientation::East, 3, 1, "shooter"),
+            "shoot()\nshoot()\nshoot()\n".to_owned(),
+        )],
+        logger,
+    );
+    b.run_simulation();
+    println!("BATTLE LOG:");
+    b.log_writer().print();
+
+    let log_lines = &b.log_writer().log_datas;
+    let tile_changes: Vec<&str> = log_lines
+        .iter()
+        .filter(|(obj, _, _, _)| obj == "map")
+        .map(|(_, act, _, _)| act.as_str())
+        .collect();
+    assert_eq!(vec!["tile-change[3,0,5]", "tile-change[3,0,0]"], tile_changes);
+    let shots: Vec<&str> = log_lines
+        .iter()
+        .filter(|(_, act, _, _)| act.starts_with("shoot["))
+        .map(|(_, act, _, _)| act.as_str())
+        .collect();
+    // third shot flies through the broken wall to the map border
+    assert_eq!(
+        vec!["shoot[0,0,3,0]", "shoot[0,0,3,0]", "shoot[0,0,5,0]"],
+        shots
+    );
+}
+
+#[test]
+fn test_configurable_logic_breaks_brick_walls() {
+    let logic = ConfigurableMaptileLogic {};
+    let mut tile = TileType::BrickWall(BRICK_WALL_HIT_POINTS);
+    for _ in 0..BRICK_WALL_HIT_POINTS {
+        assert!(!logic.passable(tile));
+        tile = logic.shoot(tile);
+    }
+    assert_eq!(TileType::Ground, tile);
+}
+
+#[test]
+fn test_configurable_logic_changes_terrain_under_tanks() {
+    let logic = ConfigurableMaptileLogic {};
+    assert_eq!(TileType::Tracks, logic.move_from(TileType::Grass));
+    assert_eq!(TileType::Water, logic.move_from(TileType::ThinIce));
+    assert_eq!(TileType::Ground, logic.move_from(TileType::Ground));
+    assert_eq!(TileType::Grass, logic.move_onto(TileType::Grass));
+    assert_eq!(TileType::ThinIce, logic.move_onto(TileType::ThinIce));
+}
+
+#[test]
+fn test_tile_codes() {
+    // codes are stored in map files and logs, so they must never move
+    let tiles = [
+        (0, TileType::Ground),
+        (1, TileType::Wall),
+        (2, TileType::Mud),
+        (3, TileType::BrickWall(3)),
+        (4, TileType::BrickWall(2)),
+        (5, TileType::BrickWall(1)),
+        (6, TileType::Grass),
+        (7, TileType::Tracks),
+        (8, TileType::ThinIce),
+        (9, TileType::Water),
+    ];
+    for (code, tile) in tiles {
+        assert_eq!(code, tile.code());
+        assert_eq!(Some(tile), TileType::from_code(code));
+    }
+    assert_eq!(None, TileType::from_code(10));
+}
+
+#[test]
+fn test_terrain_changing_under_tanks() {
+    let mut map = GridBattleMap::new(4, 1, TileType::Grass, TileType::Wall);
+    map.set_tile_at(1, 0, TileType::ThinIce);
+    map.set_tile_at(3, 0, TileType::Ground);
+    let logger = VecLogWriter::new();
+    let mut b = GridBattle::new(
+        SimpleBattleLogic::new(
+            map,
+            TileTypeLogic::new(),
+            GridMapProber {},
+            SimpleBattleObjectLayer::new(),
+            FnCommandTimer::new(|_| 10),
+            0,
+            0,
+        ),
+        vec![(
+            new_player(0, 0, GridOrientation::East, 0, 1, "driver"),
+            "for _ in range(3):\n    move_forward()\nfor _ in range(2):\n    move_backward()\n"
+                .to_owned(),
+        )],
+        logger,
+    );
+    b.run_simulation();
+    println!("BATTLE LOG:");
+    b.log_writer().print();
+
+    let log_lines = &b.log_writer().log_datas;
+    let tile_changes: Vec<&str> = log_lines
+        .iter()
+        .filter(|(obj, _, _, _)| obj == "map")
+        .map(|(_, act, _, _)| act.as_str())
+        .collect();
+    assert_eq!(
+        vec![
+            "tile-change[0,0,7]",
+            "tile-change[1,0,9]",
+            "tile-change[2,0,7]"
+        ],
+        tile_changes
+    );
+    let moves: Vec<&str> = log_lines
+        .iter()
+        .filter(|(_, act, _, _)| act.starts_with("move["))
+        .map(|(_, act, _, _)| act.as_str())
+        .collect();
+    // ice behind the tank has broken, so it can't back up onto it
+    assert_eq!(vec!["move[1,0]", "move[2,0]", "move[3,0]", "move[2,0]"], moves);
+}
+
+#[test]
+fn test_standard_battle_map_spawns_and_objects() {
+    let json = r#"{
+        "rows": [[0, 0, 0, 0], [0, 0, 0, 0]],
+        "outer_value": 1,
+        "spawns": [
+            {"x": 1, "y": 1, "orientation": "north"},
+            {"x": 3, "y": 0, "orientation": "west"}
+        ],
+        "objects": [
+            {"kind": "healthcrate", "x": 2, "y": 1},
+            {"kind": "ammocrate", "x": 0, "y": 0}
+        ]
+    }"#;
+    let map: GridBattleMap<TileType> = GridBattleMap::load_from_reader(json.as_bytes()).unwrap();
+    let mut b = new_standard_battle(
+        map,
+        vec![
+            ("p1".to_owned(), "wait()\n".to_owned()),
+            ("p2".to_owned(), "wait()\n".to_owned()),
+        ],
+        DEFAULT_OBJECTS_SEED,
+        VecLogWriter::new(),
+    )
+    .unwrap();
+    b.run_simulation();
+
+    let spawns: Vec<(&str, &str)> = b
+        .log_writer()
+        .log_datas
+        .iter()
+        .filter(|(_, act, _, _)| act.starts_with("spawn["))
+        .map(|(obj, act, _, _)| (obj.split('(').next().unwrap(), act.as_str()))
+        .collect();
+    // only crates listed in the map, nothing scattered
+    assert_eq!(
+        vec![
+            ("player[p1]", "spawn[1,1,north]"),
+            ("player[p2]", "spawn[3,0,west]"),
+            ("healthcrate", "spawn[2,1,north]"),
+            ("ammocrate", "spawn[0,0,north]"),
+        ],
+        spawns
+    );
+}
+
+#[test]
+fn test_standard_battle_pickups_avoid_spawns() {
+    for seed in 0..20 {
+        let map = GridBattleMap::new(3, 1, TileType::Ground, TileType::Wall);
+        let players = vec![
+            ("p1".to_owned(), "pass\n".to_owned()),
+            ("p2".to_owned(), "pass\n".to_owned()),
+        ];
+        let mut b = new_standard_battle(map, players, seed, VecLogWriter::new()).unwrap();
+        b.run_simulation();
+
+        // players take both ends, so the middle is the only place for a crate
+        let crate_spawns: Vec<&str> = b
+            .log_writer()
+            .log_datas
+            .iter()
+            .filter(|(obj, act, _, _)| !obj.starts_with("player[") && act.starts_with("spawn["))
+            .map(|(_, act, _, _)| act.as_str())
+            .collect();
+        assert_eq!(vec!["spawn[1,0,north]"], crate_spawns);
+    }
+}
+
+#[test]
+fn test_standard_battle_16players() {
+    let map = GridBattleMap::new(16, 16, TileType::Ground, TileType::Wall);
+    let players = (0..16)
+        .map(|i| {
+            (
+                format!("player{}", i + 1),
+                "for _ in range(4):\n    shoot()\n    listen()\n    turn_cw()\n".to_owned(),
+            )
+        })
+        .collect();
+    let mut b = new_standard_battle(map, players, DEFAULT_OBJECTS_SEED, VecLogWriter::new()).unwrap();
+    b.run_simulation();
+
+    let log_lines = &b.log_writer().log_datas;
+    let mut spawns: Vec<&str> = log_lines
+        .iter()
+        .filter(|(obj, act, _, _)| obj.starts_with("player[") && act.starts_with("spawn["))
+        .map(|(_, act, _, _)| act.split(']').next().unwrap())
+        .collect();
+    assert_eq!(16, spawns.len());
+    spawns.sort_by_key(|x| x.rsplit_once(',').unwrap().0);
+    spawns.dedup_by_key(|x| x.rsplit_once(',').unwrap().0);
+    assert_eq!(16, spawns.len(), "players must not share spawn cells");
+
+    // first commands are logged in player order
+    let first_commands: Vec<&str> = log_lines
+        .iter()
+        .filter(|(_, act, _, _)| act.starts_with("-shoot("))
+        .take(16)
+        .map(|(obj, _, _, _)| obj.split('(').next().unwrap())
+        .collect();
+    let expected: Vec<String> = (0..16).map(|i| format!("player[player{}]", i + 1)).collect();
+    assert_eq!(expected, first_commands);
+    assert!(b
+        .program_exits()
+        .iter()
+        .all(|x| matches!(x, Some(ProgramExit::Finished) | Some(ProgramExit::Stopped))));
+}
@@ -0,0 +1,114 @@
+use battle_sim::battle::ProgramExit;
+use battle_sim::r#impl::dry_run::{command_echo_line, empty_dry_run_map, DUMMY_PROGRAM};
+use battle_sim::r#impl::grid_orientation::GridOrientation;
+use battle_sim::r#impl::simple_battle_logic::{PlayerCommand, PlayerCommandReply};
+use battle_sim::r#impl::standard_battle::{new_standard_battle, DEFAULT_OBJECTS_SEED};
+use std::sync::{Arc, Mutex};
+
+mod common;
+use common::VecLogWriter;
+
+#[test]
+fn test_command_echo_line() {
+    let look: PlayerCommand<GridOrientation> = PlayerCommand::Look(GridOrientation::West);
+    let reply = PlayerCommandReply::LookResult(vec![
+        ("ground".to_owned(), Some("player[front]".to_owned())),
+        ("wall".to_owned(), None),
+    ]);
+    assert_eq!(
+        "[    12] look('left') -> [('ground', 'player[front]'), ('wall', None)]  cost 4",
+        command_echo_line(&look, &reply, 12, 16)
+    );
+
+    let print: PlayerCommand<GridOrientation> = PlayerCommand::Print("it's".to_owned());
+    assert_eq!(
+        "[     3] print('it\\'s') -> None  cost 0",
+        command_echo_line(&print, &PlayerCommandReply::Ok, 3, 3)
+    );
+    assert_eq!(
+        "[     0] move_forward() -> None (failed)  cost 10",
+        command_echo_line(
+            &PlayerCommand::<GridOrientation>::MoveFwd,
+            &PlayerCommandReply::Failed,
+            0,
+            10
+        )
+    );
+}
+
+#[test]
+fn test_dry_run_echo_and_errors() {
+    let mut b = new_standard_battle(
+        empty_dry_run_map(),
+        vec![
+            (
+                "bot".to_owned(),
+                "turn_cw()\nx = check_ammo()\n\ndef f():\n    return x / 0\nf()\n".to_owned(),
+            ),
+            ("dummy".to_owned(), DUMMY_PROGRAM.to_owned()),
+        ],
+        DEFAULT_OBJECTS_SEED,
+        VecLogWriter::new(),
+    )
+    .unwrap();
+    b.set_program_names(vec!["bots/bot.py".to_owned()]);
+    let echoed = Arc::new(Mutex::new(Vec::new()));
+    b.set_command_echo(Box::new({
+        let echoed = echoed.clone();
+        move |player_i, command, reply, issued_at, replied_at| {
+            if player_i == 0 {
+                echoed
+                    .lock()
+                    .unwrap()
+                    .push(command_echo_line(command, reply, issued_at, replied_at));
+            }
+        }
+    }));
+    b.run_simulation_with_time_limit(Some(100));
+
+    assert_eq!(
+        vec![
+            "[     0] turn_cw() -> None  cost 16",
+            "[    16] check_ammo() -> 5  cost 2",
+        ],
+        *echoed.lock().unwrap()
+    );
+    match &b.program_exits()[0] {
+        Some(ProgramExit::Errored(e)) => {
+            assert!(e.contains("File \"bots/bot.py\", line 5, in f"), "{}", e);
+            assert!(e.contains("ZeroDivisionError"), "{}", e);
+        }
+        x => panic!("unexpected bot exit: {:?}", x),
+    }
+    // dummy just waits until the time limit
+    assert_eq!(Some(ProgramExit::Stopped), b.program_exits()[1]);
+}
+
+#[test]
+fn test_syntax_error_names_program() {
+    let mut b = new_standard_battle(
+        empty_dry_run_map(),
+        vec![
+            ("bot".to_owned(), "wait()\nif True\n".to_owned()),
+            ("unnamed".to_owned(), "wait()\nif True\n".to_owned()),
+        ],
+        DEFAULT_OBJECTS_SEED,
+        VecLogWriter::new(),
+    )
+    .unwrap();
+    b.set_program_names(vec!["bot.py".to_owned()]);
+    b.run_simulation_with_time_limit(Some(10));
+    match &b.program_exits()[0] {
+        Some(ProgramExit::CompileError(e)) => {
+            assert!(e.starts_with("bot.py: ") && e.contains("row 2"), "{}", e)
+        }
+        x => panic!("unexpected bot exit: {:?}", x),
+    }
+    // player without a name keeps the default one
+    match &b.program_exits()[1] {
+        Some(ProgramExit::CompileError(e)) => {
+            assert!(e.starts_with("<embedded>: ") && e.contains("row 2"), "{}", e)
+        }
+        x => panic!("unexpected unnamed player exit: {:?}", x),
+    }
+}
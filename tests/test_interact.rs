@@ -31,7 +31,7 @@ fn test_base<F>(
                 SimpleBattleObjectLayer<SimpleObject<GridOrientation>>,
                 HashmapCommandTimer<PlayerCommand<GridOrientation>>,
             >,
-            VecLogWriter<String, String>,
+            VecLogWriter,
         >,
         Option<Vec<usize>>,
     ),
@@ -82,7 +82,7 @@ fn test_base<F>(
         player_programs,
         logger,
     );
-    let winners = b.run_simulation();
+    let winners = b.run_simulation().winners;
     println!("BATTLE LOG:");
     b.log_writer().print();
     checks(b, winners);
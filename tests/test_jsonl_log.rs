@@ -0,0 +1,144 @@
+use battle_sim::r#impl::grid_map::GridBattleMap;
+use battle_sim::r#impl::jsonl_battle_logger::JsonLinesLogWriter;
+use battle_sim::r#impl::standard_battle::{
+    new_standard_team_battle, step_think_limit, TeamSetup, DEFAULT_OBJECTS_SEED,
+};
+use battle_sim::r#impl::tile_types::TileType;
+use battle_sim::serialization::FromFile;
+
+use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+/// lets the test read what the battle's log writer has written
+#[derive(Clone)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_jsonl_battle_log() {
+    let json = r#"{
+        "rows": [[0, 0, 3, 0, 0, 0], [6, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0]],
+        "outer_value": 1,
+        "spawns": [
+            {"x": 0, "y": 0, "orientation": "east"},
+            {"x": 0, "y": 1, "orientation": "south"},
+            {"x": 5, "y": 2, "orientation": "west"},
+            {"x": 5, "y": 0, "orientation": "west"}
+        ],
+        "objects": [
+            {"kind": "ammocrate", "x": 1, "y": 0}
+        ]
+    }"#;
+    let map: GridBattleMap<TileType> = GridBattleMap::load_from_reader(json.as_bytes()).unwrap();
+    let buffer = SharedBuffer(Rc::new(RefCell::new(Vec::new())));
+    let mut b = new_standard_team_battle(
+        map,
+        vec![
+            (
+                "p0".to_owned(),
+                "print('hello')\nsend_message('go')\nmove_forward()\nwhile True:\n    shoot()\n"
+                    .to_owned(),
+            ),
+            (
+                "p1".to_owned(),
+                "move_forward()\nfor i in range(250):\n    pass\nwait()\n".to_owned(),
+            ),
+            ("p2".to_owned(), "while True:\n    wait()\n".to_owned()),
+            ("p3".to_owned(), "while True:\n    wait()\n".to_owned()),
+        ],
+        TeamSetup {
+            teams: vec![0, 0, 1, 1],
+            friendly_fire: false,
+        },
+        DEFAULT_OBJECTS_SEED,
+        JsonLinesLogWriter::new(buffer.clone()),
+    )
+    .unwrap();
+    b.set_think_limit(step_think_limit(100));
+    b.run_simulation_with_time_limit(Some(500));
+
+    let text = String::from_utf8(buffer.0.borrow().clone()).unwrap();
+    println!("BATTLE LOG:\n{}", text);
+    let events: Vec<Value> = text
+        .lines()
+        .map(|x| serde_json::from_str(x).unwrap())
+        .collect();
+    assert!(!events.is_empty());
+    for event in events.iter() {
+        assert_ne!("unknown", event["action"], "{}", event);
+        assert!(event["time"].is_u64(), "{}", event);
+        assert!(event["object_kind"].is_string(), "{}", event);
+    }
+
+    let actions: HashSet<&str> = events
+        .iter()
+        .map(|x| x["action"].as_str().unwrap())
+        .collect();
+    for action in [
+        "spawn",
+        "team",
+        "move",
+        "shoot",
+        "dying",
+        "die",
+        "picked",
+        "log",
+        "radio",
+        "tile-change",
+        "think-overrun",
+        "exit",
+        "command-start",
+        "command-finish",
+    ] {
+        assert!(actions.contains(action), "no {} in the log", action);
+    }
+
+    let find = |action: &str, name: &str| -> &Value {
+        events
+            .iter()
+            .find(|x| x["action"] == action && x["name"] == name)
+            .unwrap()
+    };
+    let spawn = find("spawn", "p0");
+    assert_eq!("player", spawn["object_kind"]);
+    assert!(spawn["uid"].is_u64());
+    assert_eq!(
+        (0, 0),
+        (spawn["x"].as_i64().unwrap(), spawn["y"].as_i64().unwrap())
+    );
+    assert_eq!("east", spawn["orientation"]);
+    assert_eq!(1, find("team", "p2")["team"]);
+    assert_eq!("hello", find("log", "p0")["text"]);
+    assert_eq!("go", find("radio", "p0")["text"]);
+    let command = find("command-start", "p0");
+    assert!(command["command_id"].is_u64());
+    assert!(command["duration"].as_u64().unwrap() > 0);
+    assert!(command["command"].is_string());
+    assert_eq!(true, find("command-finish", "p0")["success"]);
+    assert!(find("think-overrun", "p1")["steps"].as_u64().unwrap() > 100);
+    assert!(events
+        .iter()
+        .any(|x| x["action"] == "picked" && x["object_kind"] == "ammocrate" && x["uid"].is_u64()));
+    // brick wall shot down, grass driven over
+    assert!(events.iter().any(|x| x["action"] == "tile-change"
+        && x["object_kind"] == "map"
+        && (x["x"].as_i64(), x["y"].as_i64(), x["tile"].as_str())
+            == (Some(2), Some(0), Some("0"))));
+    assert!(events.iter().any(|x| x["action"] == "tile-change"
+        && (x["x"].as_i64(), x["y"].as_i64()) == (Some(0), Some(1))));
+    assert_eq!("stopped", find("exit", "p2")["reason"]);
+    let shot = find("shoot", "p0");
+    assert!(shot["hit_x"].is_i64() && shot["hit_y"].is_i64());
+}
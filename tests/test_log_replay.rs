@@ -0,0 +1,131 @@
+use battle_sim::object_layer::ObjectLayer;
+use battle_sim::r#impl::buf_battle_log_reader::read_log_events;
+use battle_sim::r#impl::buf_battle_logger::BufferLogWriter;
+use battle_sim::r#impl::grid_battle::{new_player, GridBattle};
+use battle_sim::r#impl::grid_map::GridBattleMap;
+use battle_sim::r#impl::grid_map_prober::GridMapProber;
+use battle_sim::r#impl::grid_orientation::GridOrientation;
+use battle_sim::r#impl::log_events::LogAction;
+use battle_sim::r#impl::log_replay::{LogReplay, ReplayShot};
+use battle_sim::r#impl::simple_battle_logic::SimpleBattleLogic;
+use battle_sim::r#impl::simple_battle_object_layer::SimpleBattleObjectLayer;
+
+mod common;
+use common::{FnCommandTimer, SimpleTileType, TestTrivialLogic};
+
+const LOG: &str = "\
+player[p1](0)\tspawn[0,0,east]\t0\t0
+player[p2](1)\tspawn[4,0,west]\t0\t0
+ammocrate(2)\tspawn[2,1,north]\t0\t0
+player[p1](0)\t-move-forward(0)\t0\t10
+player[p2](1)\t-turn-cw(1)\t0\t16
+player[p1](0)\tmove[1,0]\t5\t0
+player[p1](0)\t+move-forward(0)\t10\t0
+player[p2](1)\tturn[north]\t8\t0
+player[p1](0)\t-shoot(2)\t10\t5
+player[p1](0)\tshoot[1,0,4,0]\t10\t0
+player[p2](1)\tdying\t10\t0
+ammocrate(2)\tpicked\t12\t0
+player[p1](0)\tlog[hello]\t12\t0
+player[p2](1)\tdie\t15\t0
+player[p1](0)\twin\t15\t0
+";
+
+#[test]
+fn test_log_replay_states() {
+    let events = read_log_events(LOG.as_bytes()).unwrap();
+    assert_eq!(15, events.len());
+    assert_eq!(LogAction::Move { x: 1, y: 0 }, events[5].action);
+    let replay = LogReplay::new(events);
+    assert_eq!(15, replay.end_time());
+    // out of order line is sorted into place
+    assert_eq!(8, replay.events()[6].time);
+
+    let state = replay.state_at(4);
+    assert_eq!(3, state.objects.len());
+    assert_eq!((0, 0), (state.objects[&0].x, state.objects[&0].y));
+    assert_eq!(
+        "move-forward",
+        state.objects[&0].command.as_ref().unwrap().command
+    );
+
+    let state = replay.state_at(10);
+    assert_eq!((1, 0), (state.objects[&0].x, state.objects[&0].y));
+    assert_eq!("north", state.objects[&1].orientation);
+    assert!(state.objects[&1].dying);
+    assert_eq!(
+        vec![ReplayShot {
+            shooter: 0,
+            x: 1,
+            y: 0,
+            hit_x: 4,
+            hit_y: 0
+        }],
+        state.shots
+    );
+
+    let state = replay.state_at(12);
+    assert!(state.shots.is_empty());
+    assert!(!state.objects.contains_key(&2));
+    assert_eq!(vec![(0, "hello".to_owned())], state.messages);
+
+    let state = replay.state_at(100);
+    assert!(state.objects[&1].dead);
+    assert_eq!(vec![0], state.winners);
+    assert!(state.messages.is_empty());
+
+    let ticks: Vec<_> = replay.ticks().collect();
+    assert_eq!(16, ticks.len());
+    for (time, state) in ticks.iter().enumerate() {
+        assert_eq!(&replay.state_at(time as u64), state);
+    }
+}
+
+#[test]
+fn test_log_replay_of_battle() {
+    let map = GridBattleMap::new(5, 1, SimpleTileType::Nothin, SimpleTileType::Wall);
+    let mut buf = Vec::new();
+    let mut b = GridBattle::new(
+        SimpleBattleLogic::new(
+            map,
+            TestTrivialLogic {},
+            GridMapProber {},
+            SimpleBattleObjectLayer::new(),
+            FnCommandTimer::new(|_| 10),
+            1,
+            0,
+        ),
+        vec![
+            (
+                new_player(0, 0, GridOrientation::East, 2, 1, "player1"),
+                "move_forward()\nmove_forward()\n".to_owned(),
+            ),
+            (
+                new_player(4, 0, GridOrientation::West, 2, 1, "player2"),
+                "turn_cw()\n".to_owned(),
+            ),
+        ],
+        BufferLogWriter::new(&mut buf),
+    );
+    b.run_simulation();
+    drop(b);
+
+    let events = read_log_events(buf.as_slice()).unwrap();
+    assert!(events
+        .iter()
+        .all(|x| !matches!(x.action, LogAction::Unknown { .. })));
+    let replay = LogReplay::new(events);
+    let state = replay.state_at(replay.end_time());
+    let player1 = state
+        .objects
+        .values()
+        .find(|x| x.name.as_deref() == Some("player1"))
+        .unwrap();
+    assert_eq!((2, 0), (player1.x, player1.y));
+    let player2 = state
+        .objects
+        .values()
+        .find(|x| x.name.as_deref() == Some("player2"))
+        .unwrap();
+    assert_eq!("north", player2.orientation);
+}
@@ -0,0 +1,103 @@
+use battle_sim::map::MapWriteAccess;
+use battle_sim::r#impl::grid_map::GridBattleMap;
+use battle_sim::r#impl::grid_orientation::GridOrientation;
+use battle_sim::r#impl::map_analysis::{analyze_map, UnreachableRegion};
+use battle_sim::r#impl::standard_battle::{analyze_standard_map, DEFAULT_OBJECTS_SEED};
+use battle_sim::r#impl::tile_types::TileType;
+use battle_sim::r#impl::tile_types_logic::TileTypeLogic;
+
+#[test]
+fn test_symmetric_map_is_fair() {
+    let mut map = GridBattleMap::new(5, 5, TileType::Ground, TileType::Wall);
+    map.set_tile_at(2, 2, TileType::Wall);
+    let spawns = [(0, 0, GridOrientation::East), (4, 4, GridOrientation::West)];
+    let analysis = analyze_map(&map, &TileTypeLogic::new(), &spawns, &[(0, 4), (4, 0)]);
+
+    assert!(analysis.is_valid());
+    assert_eq!(1.0, analysis.symmetry);
+    assert_eq!(1.0, analysis.fairness);
+    assert_eq!(vec![24], analysis.region_sizes);
+    assert!(analysis.unreachable_regions.is_empty());
+    for spawn in analysis.spawns.iter() {
+        assert_eq!(8, spawn.exposure);
+        assert_eq!(Some(4), spawn.nearest_pickup);
+        assert_eq!(Some(8), spawn.nearest_enemy);
+    }
+}
+
+#[test]
+fn test_walled_off_spawn() {
+    // wall across the map cuts the right column off, and a closed pocket at the bottom left
+    let mut map = GridBattleMap::new(5, 5, TileType::Ground, TileType::Wall);
+    for y in 0..5 {
+        map.set_tile_at(3, y, TileType::Wall);
+    }
+    map.set_tile_at(0, 3, TileType::Wall);
+    map.set_tile_at(1, 4, TileType::Wall);
+    let spawns = [
+        (0, 0, GridOrientation::East),
+        (4, 4, GridOrientation::West),
+        (2, 2, GridOrientation::North),
+    ];
+    let analysis = analyze_map(&map, &TileTypeLogic::new(), &spawns, &[(1, 1)]);
+
+    assert!(!analysis.is_valid());
+    assert_eq!(
+        vec!["spawn 1 at 4,4 can not be reached from spawn 0 at 0,0".to_owned()],
+        analysis.problems()
+    );
+    assert_eq!(
+        vec![UnreachableRegion {
+            size: 1,
+            cell: (0, 4)
+        }],
+        analysis.unreachable_regions
+    );
+    assert_eq!(None, analysis.spawns[1].nearest_pickup);
+    assert_eq!(None, analysis.spawns[1].nearest_enemy);
+    assert_eq!(Some(4), analysis.spawns[0].nearest_enemy);
+}
+
+#[test]
+fn test_blocked_corner_in_standard_map() {
+    let mut map = GridBattleMap::new(6, 6, TileType::Ground, TileType::Wall);
+    map.set_tile_at(0, 0, TileType::Wall);
+    // corner in a wall, players are spread over free cells instead
+    let analysis = analyze_standard_map(&map, 2, DEFAULT_OBJECTS_SEED).unwrap();
+
+    assert!(analysis.problems().is_empty(), "{:?}", analysis.problems());
+    assert_eq!(2, analysis.spawns.len());
+    assert!(analysis.spawns.iter().all(|x| x.passable && (x.x, x.y) != (0, 0)));
+    assert!(analyze_standard_map(&map, 40, DEFAULT_OBJECTS_SEED).is_none());
+
+    // free corners are kept
+    let map = GridBattleMap::new(6, 6, TileType::Ground, TileType::Wall);
+    let analysis = analyze_standard_map(&map, 2, DEFAULT_OBJECTS_SEED).unwrap();
+    assert_eq!(
+        vec![(0, 0), (5, 5)],
+        analysis.spawns.iter().map(|x| (x.x, x.y)).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_walled_off_spawns_behind_blocked_spawn_0() {
+    // spawn 0 is in a wall, spawns 1 and 2 are on different sides of the wall column
+    let mut map = GridBattleMap::new(5, 5, TileType::Ground, TileType::Wall);
+    for y in 0..5 {
+        map.set_tile_at(2, y, TileType::Wall);
+    }
+    let spawns = [
+        (2, 2, GridOrientation::East),
+        (0, 0, GridOrientation::East),
+        (4, 4, GridOrientation::West),
+    ];
+    let analysis = analyze_map(&map, &TileTypeLogic::new(), &spawns, &[]);
+
+    assert_eq!(
+        vec![
+            "spawn 0 at 2,2 is on an impassable tile".to_owned(),
+            "spawn 2 at 4,4 can not be reached from spawn 1 at 0,0".to_owned()
+        ],
+        analysis.problems()
+    );
+}
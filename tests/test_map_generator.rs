@@ -0,0 +1,101 @@
+use battle_sim::map::MapReadAccess;
+use battle_sim::r#impl::map_generator::{MapGenMode, MapGenerator, MapSymmetry};
+use battle_sim::r#impl::standard_battle::{analyze_standard_map, DEFAULT_OBJECTS_SEED};
+use battle_sim::r#impl::tile_types::TileType;
+
+const MODES: [MapGenMode; 4] = [
+    MapGenMode::Random,
+    MapGenMode::Caves,
+    MapGenMode::Maze,
+    MapGenMode::Rooms,
+];
+const SYMMETRIES: [MapSymmetry; 5] = [
+    MapSymmetry::None,
+    MapSymmetry::Mirror,
+    MapSymmetry::Rotational,
+    MapSymmetry::Mirror4,
+    MapSymmetry::Rotational4,
+];
+
+fn generator(
+    size: (usize, usize),
+    seed: u64,
+    mode: MapGenMode,
+    symmetry: MapSymmetry,
+) -> MapGenerator {
+    let mut generator = MapGenerator::new(size.0, size.1, seed);
+    generator.set_mode(mode);
+    generator.set_symmetry(symmetry);
+    generator.set_mud_share(0.1);
+    generator
+}
+
+#[test]
+fn test_generated_maps_are_connected_and_symmetric() {
+    for size in [(16, 16), (15, 11), (1, 1)] {
+        for mode in MODES {
+            for symmetry in SYMMETRIES {
+                for seed in 1..4 {
+                    let map = generator(size, seed, mode, symmetry).generate();
+                    let (w, h) = (size.0 as i64, size.1 as i64);
+                    let what = format!("{:?} {:?} {:?} seed {}", size, mode, symmetry, seed);
+
+                    let analysis = analyze_standard_map(&map, 1, DEFAULT_OBJECTS_SEED).unwrap();
+                    assert!(analysis.unreachable_regions.is_empty(), "{}", what);
+                    assert_eq!(1, analysis.region_sizes.len(), "{}", what);
+                    if size != (1, 1) {
+                        let analysis = analyze_standard_map(&map, 4, DEFAULT_OBJECTS_SEED).unwrap();
+                        assert!(analysis.is_valid(), "{}: {:?}", what, analysis.problems());
+                    }
+
+                    for y in 0..h {
+                        for x in 0..w {
+                            let tile = map.get_tile_at(x, y);
+                            let images = match symmetry {
+                                MapSymmetry::None => vec![],
+                                MapSymmetry::Mirror => vec![(w - 1 - x, y)],
+                                MapSymmetry::Rotational => vec![(w - 1 - x, h - 1 - y)],
+                                MapSymmetry::Mirror4 => {
+                                    vec![(w - 1 - x, y), (x, h - 1 - y), (w - 1 - x, h - 1 - y)]
+                                }
+                                MapSymmetry::Rotational4 if w == h => vec![(w - 1 - y, x)],
+                                MapSymmetry::Rotational4 => vec![(w - 1 - x, h - 1 - y)],
+                            };
+                            for (ix, iy) in images {
+                                assert_eq!(
+                                    tile,
+                                    map.get_tile_at(ix, iy),
+                                    "{} at {},{}",
+                                    what,
+                                    x,
+                                    y
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_generation_is_reproducible() {
+    for mode in MODES {
+        let map1 = generator((20, 12), 42, mode, MapSymmetry::None).generate();
+        let map2 = generator((20, 12), 42, mode, MapSymmetry::None).generate();
+        let map3 = generator((20, 12), 43, mode, MapSymmetry::None).generate();
+        let rows = |map: &battle_sim::r#impl::grid_map::GridBattleMap<TileType>| {
+            (0..12)
+                .map(|y| map.map_data().row(y).to_vec())
+                .collect::<Vec<_>>()
+        };
+        assert!(rows(&map1) == rows(&map2), "{:?}", mode);
+        assert!(rows(&map1) != rows(&map3), "{:?}", mode);
+        assert!(
+            rows(&map1).iter().flatten().any(|x| *x == TileType::Mud),
+            "{:?} has no mud",
+            mode
+        );
+    }
+}
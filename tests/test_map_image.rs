@@ -0,0 +1,105 @@
+use battle_sim::map::MapWriteAccess;
+use battle_sim::r#impl::buf_battle_log_reader::read_log_events;
+use battle_sim::r#impl::grid_map::GridBattleMap;
+use battle_sim::r#impl::gridmap_text_file::TextGridBattleMap;
+use battle_sim::r#impl::log_replay::LogReplay;
+use battle_sim::r#impl::map_image::{
+    position_heatmap, tile_color, MapImage, MapRenderer, PLAYER_COLORS,
+};
+use battle_sim::r#impl::tile_types::TileType;
+use battle_sim::serialization::FromFile;
+
+const LOG: &str = "\
+player[p1](0)\tspawn[0,1,east]\t0\t0
+player[p2](1)\tspawn[3,1,west]\t0\t0
+ammocrate(2)\tspawn[2,0,north]\t0\t0
+map\ttile-change[1,0,0]\t2\t0
+player[p1](0)\tmove[1,1]\t2\t0
+player[p2](1)\tdying\t3\t0
+player[p2](1)\tdie\t4\t0
+";
+
+fn pixel(image: &MapImage, x: u32, y: u32) -> [u8; 3] {
+    let rgb = image.to_rgb();
+    let i = ((y * image.width() + x) * 3) as usize;
+    [rgb[i], rgb[i + 1], rgb[i + 2]]
+}
+
+#[test]
+fn test_render_map() {
+    let map = TextGridBattleMap::load_from_reader("1.a~\n#..2\n".as_bytes())
+        .unwrap()
+        .0;
+    let mut renderer = MapRenderer::new(&map);
+    renderer.set_cell_size(10);
+    let image = renderer.render();
+    assert_eq!((40, 20), (image.width(), image.height()));
+
+    // tile colors at cell corners, objects and spawns in cell centers
+    assert_eq!(tile_color(TileType::Ground), pixel(&image, 11, 1));
+    assert_eq!(tile_color(TileType::Mud), pixel(&image, 35, 5));
+    assert_eq!(tile_color(TileType::Wall), pixel(&image, 1, 11));
+    assert_ne!(tile_color(TileType::Ground), pixel(&image, 25, 5));
+    assert_eq!(tile_color(TileType::Ground), pixel(&image, 21, 1));
+    assert_eq!(PLAYER_COLORS[0], pixel(&image, 3, 2));
+    assert_eq!(PLAYER_COLORS[1], pixel(&image, 36, 12));
+
+    let svg = image.to_svg();
+    assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="40" height="20""#));
+    assert!(svg.ends_with("</svg>\n"));
+    // 8 tiles, 1 crate, 2 spawns with facing triangles
+    assert_eq!(9, svg.matches("<rect").count());
+    assert_eq!(2, svg.matches("<circle").count());
+    assert_eq!(2, svg.matches("<polygon").count());
+}
+
+#[test]
+fn test_render_png() {
+    let map = GridBattleMap::new(3, 2, TileType::Water, TileType::Wall);
+    let mut renderer = MapRenderer::new(&map);
+    renderer.set_cell_size(4);
+    let image = renderer.render();
+    let mut png = Vec::new();
+    image.write_png(&mut png).unwrap();
+
+    let decoder = png::Decoder::new(png.as_slice());
+    let mut reader = decoder.read_info().unwrap();
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).unwrap();
+    assert_eq!((12, 8), (info.width, info.height));
+    assert_eq!(png::ColorType::Rgb, info.color_type);
+    assert_eq!(image.to_rgb(), buf[..info.buffer_size()]);
+    assert_eq!(tile_color(TileType::Water).to_vec(), buf[..3]);
+}
+
+#[test]
+fn test_render_final_state_and_heatmap() {
+    let mut map = GridBattleMap::new(4, 2, TileType::Ground, TileType::Wall);
+    map.set_tile_at(1, 0, TileType::BrickWall(1));
+    let replay = LogReplay::new(read_log_events(LOG.as_bytes()).unwrap());
+    let state = replay.state_at(replay.end_time());
+
+    let heatmap = position_heatmap(&replay);
+    // p1 stands on 0,1 for ticks 0 and 1, then on 1,1; p2 is alive on 3,1 for ticks 0..3
+    assert_eq!(Some(&2), heatmap.get(&(0, 1)));
+    assert_eq!(Some(&3), heatmap.get(&(1, 1)));
+    assert_eq!(Some(&4), heatmap.get(&(3, 1)));
+
+    let mut renderer = MapRenderer::new(&map);
+    renderer.set_cell_size(10);
+    renderer.set_state(Some(&state));
+    let image = renderer.render();
+    // broken brick wall is ground now, tanks are drawn where they ended
+    assert_eq!(tile_color(TileType::Ground), pixel(&image, 15, 5));
+    assert_eq!(tile_color(TileType::Ground), pixel(&image, 3, 12));
+    assert_eq!(PLAYER_COLORS[0], pixel(&image, 13, 12));
+    assert_ne!(PLAYER_COLORS[1], pixel(&image, 36, 12));
+    assert_ne!(tile_color(TileType::Ground), pixel(&image, 36, 12));
+
+    renderer.set_heatmap(Some(&heatmap));
+    let image = renderer.render();
+    let heated = pixel(&image, 1, 11);
+    let hottest = pixel(&image, 31, 11);
+    assert!(heated[0] > heated[1] && hottest[1] < heated[1]);
+    assert_eq!(tile_color(TileType::Ground), pixel(&image, 21, 11));
+}
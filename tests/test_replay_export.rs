@@ -0,0 +1,99 @@
+use battle_sim::r#impl::buf_battle_log_reader::read_log_events;
+use battle_sim::r#impl::grid_map::GridBattleMap;
+use battle_sim::r#impl::log_replay::LogReplay;
+use battle_sim::r#impl::map_image::{MapRenderer, PLAYER_COLORS};
+use battle_sim::r#impl::replay_export::ReplayExporter;
+use battle_sim::r#impl::tile_types::TileType;
+
+const LOG: &str = "\
+player[p1](0)\tspawn[0,0,east]\t0\t0
+player[p2](1)\tspawn[4,0,west]\t0\t0
+player[p1](0)\tshoot[0,0,4,0]\t3\t0
+player[p2](1)\tdying\t3\t0
+player[p2](1)\tdie\t7\t0
+player[p1](0)\twin\t7\t0
+";
+
+fn setup() -> (GridBattleMap<TileType>, LogReplay) {
+    let map = GridBattleMap::new(5, 1, TileType::Ground, TileType::Wall);
+    let replay = LogReplay::new(read_log_events(LOG.as_bytes()).unwrap());
+    (map, replay)
+}
+
+#[test]
+fn test_frame_times() {
+    let (map, replay) = setup();
+    let mut exporter = ReplayExporter::new(&map, &replay);
+    assert_eq!((0..=7).collect::<Vec<_>>(), exporter.frame_times());
+    exporter.set_ticks_per_frame(3);
+    // end of the battle is always shown
+    assert_eq!(vec![0, 3, 6, 7], exporter.frame_times());
+    exporter.set_ticks_per_frame(7);
+    assert_eq!(vec![0, 7], exporter.frame_times());
+    assert_eq!(2, exporter.frames().len());
+}
+
+#[test]
+fn test_frames_keep_shots_between_frames() {
+    let (map, replay) = setup();
+    let mut exporter = ReplayExporter::new(&map, &replay);
+    exporter.set_cell_size(10);
+    exporter.set_ticks_per_frame(5);
+    let frames = exporter.frames();
+    assert_eq!(3, frames.len());
+
+    // shot at tick 3 is only in the frame at tick 5
+    let svgs: Vec<String> = frames.iter().map(|x| x.to_svg()).collect();
+    let tracer = r##"fill="#ffeb5a""##;
+    assert!(!svgs[0].contains(tracer));
+    assert!(svgs[1].contains(tracer));
+    assert!(!svgs[2].contains(tracer));
+
+    // frame is the same picture as rendering the state at frame time with the shot
+    let mut state = replay.state_at(5);
+    state.shots = replay.state_at(3).shots;
+    let mut renderer = MapRenderer::new(&map);
+    renderer.set_cell_size(10);
+    renderer.set_state(Some(&state));
+    assert_eq!(renderer.render(), frames[1]);
+
+    // dead tank loses its color in the last frame
+    let rgb = frames[2].to_rgb();
+    let i = (5 * 50 + 45) * 3;
+    assert_ne!(PLAYER_COLORS[1], [rgb[i], rgb[i + 1], rgb[i + 2]]);
+}
+
+#[test]
+fn test_gif() {
+    let (map, replay) = setup();
+    let mut exporter = ReplayExporter::new(&map, &replay);
+    exporter.set_cell_size(8);
+    exporter.set_ticks_per_frame(2);
+    exporter.set_frame_delay_ms(50);
+    let mut gif_data = Vec::new();
+    exporter.write_gif(&mut gif_data).unwrap();
+
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::RGBA);
+    let mut decoder = options.read_info(gif_data.as_slice()).unwrap();
+    assert_eq!((40, 8), (decoder.width(), decoder.height()));
+    let mut delays = Vec::new();
+    while let Some(frame) = decoder.read_next_frame().unwrap() {
+        delays.push(frame.delay);
+    }
+    assert_eq!(vec![5, 5, 5, 5, 205], delays);
+}
+
+#[test]
+fn test_png_frames() {
+    let (map, replay) = setup();
+    let mut exporter = ReplayExporter::new(&map, &replay);
+    exporter.set_ticks_per_frame(4);
+    let dir = std::env::temp_dir().join(format!("replay_frames_{}", std::process::id()));
+    assert_eq!(3, exporter.save(&dir).unwrap());
+    for i in 0..3 {
+        assert!(dir.join(format!("frame_{:05}.png", i)).is_file());
+    }
+    assert!(!dir.join("frame_00003.png").exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
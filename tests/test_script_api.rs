@@ -0,0 +1,89 @@
+use battle_sim::r#impl::grid_map::GridBattleMap;
+use battle_sim::r#impl::script_api::{api_to_json, api_to_markdown, api_to_pyi};
+use battle_sim::r#impl::standard_battle::{
+    describe_standard_api, new_standard_battle, DEFAULT_OBJECTS_SEED,
+};
+use battle_sim::r#impl::tile_types::TileType;
+
+mod common;
+use common::VecLogWriter;
+
+fn described_names() -> Vec<&'static str> {
+    let mut names: Vec<&str> = describe_standard_api()
+        .iter()
+        .flat_map(|f| std::iter::once(f.name).chain(f.aliases.iter().copied()))
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_api_matches_program_scope() {
+    // program compares functions it can see with the description and prints differences
+    let program = format!(
+        "\
+described = set({:?})
+available = set(k for k in globals() if not k.startswith('_') and callable(globals()[k]))
+print('missing', sorted(described - available))
+print('undescribed', sorted(available - described))
+",
+        described_names()
+    );
+    let map = GridBattleMap::new(3, 1, TileType::Ground, TileType::Wall);
+    let mut b = new_standard_battle(
+        map,
+        vec![
+            ("p1".to_owned(), program),
+            ("p2".to_owned(), "wait()\n".to_owned()),
+        ],
+        DEFAULT_OBJECTS_SEED,
+        VecLogWriter::new(),
+    )
+    .unwrap();
+    b.run_simulation();
+
+    let logs: Vec<&str> = b
+        .log_writer()
+        .log_datas
+        .iter()
+        .filter(|(obj, act, _, _)| obj.starts_with("player[p1]") && act.starts_with("log["))
+        .map(|(_, act, _, _)| act.as_str())
+        .collect();
+    assert_eq!(vec!["log[missing []]", "log[undescribed []]"], logs);
+}
+
+#[test]
+fn test_api_description() {
+    let api = describe_standard_api();
+    let names = described_names();
+    let mut deduped = names.clone();
+    deduped.dedup();
+    assert_eq!(names, deduped);
+
+    let shoot = api.iter().find(|f| f.name == "shoot").unwrap();
+    assert_eq!(&["fire"], shoot.aliases);
+    assert_eq!((Some(10), Some(5)), (shoot.time, shoot.effect_time));
+    let rand = api.iter().find(|f| f.name == "rand").unwrap();
+    assert_eq!((None, None), (rand.time, rand.effect_time));
+
+    let pyi = api_to_pyi(&api);
+    assert!(pyi.contains("\ndef look(direction: str) -> list[tuple[str, str | None]]:\n"));
+    assert!(pyi.contains("\ndef move_back() -> None:\n"));
+    let md = api_to_markdown(&api);
+    assert!(md.contains("| `send_message(text: str) -> None` |  | 3 | 3 |"));
+}
+
+#[test]
+fn test_committed_docs_are_up_to_date() {
+    // regenerate with battle-api -f json docs/api.json, -f pyi docs/__builtins__.pyi, -f markdown docs/api.md
+    let api = describe_standard_api();
+    let docs = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("docs");
+    for (file, expected) in [
+        ("api.json", api_to_json(&api)),
+        ("__builtins__.pyi", api_to_pyi(&api)),
+        ("api.md", api_to_markdown(&api)),
+    ] {
+        let committed = std::fs::read_to_string(docs.join(file)).unwrap();
+        assert_eq!(expected, committed, "docs/{} is out of date", file);
+    }
+}
@@ -0,0 +1,89 @@
+use battle_sim::r#impl::tournament::{
+    round_robin_pairings, GameOutcome, Standings, INITIAL_RATING,
+};
+
+fn names(count: usize) -> Vec<String> {
+    (0..count).map(|x| format!("bot{}", x)).collect()
+}
+
+#[test]
+fn test_round_robin_pairings() {
+    assert_eq!(
+        vec![(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)],
+        round_robin_pairings(4)
+    );
+    assert!(round_robin_pairings(1).is_empty());
+}
+
+#[test]
+fn test_standings_ratings() {
+    let mut standings = Standings::new(names(3));
+    standings.record_game(0, 1, GameOutcome::FirstWon);
+    standings.record_game(2, 0, GameOutcome::Draw);
+
+    let s = standings.standings();
+    assert_eq!((1, 1, 0), (s[0].wins, s[0].draws, s[0].losses));
+    assert_eq!((0, 0, 1), (s[1].wins, s[1].draws, s[1].losses));
+    assert_eq!((0, 1, 0), (s[2].wins, s[2].draws, s[2].losses));
+    // equal players exchange half of K on a decisive game
+    assert_eq!(INITIAL_RATING - 16.0, s[1].rating);
+    // rating is only moved around, never created
+    let total: f64 = s.iter().map(|x| x.rating).sum();
+    assert!((total - 3.0 * INITIAL_RATING).abs() < 1e-9);
+    // draw against a stronger player gains rating
+    assert!(s[2].rating > INITIAL_RATING);
+
+    assert_eq!(vec![0, 2, 1], standings.ranking());
+    assert!(standings.to_text().lines().nth(1).unwrap().contains("bot0"));
+}
+
+#[test]
+fn test_swiss_pairings() {
+    let mut standings = Standings::new(names(5));
+    let first_round = standings.swiss_pairings();
+    assert_eq!(2, first_round.len());
+    for &(a, b) in first_round.iter() {
+        standings.record_game(a, b, GameOutcome::FirstWon);
+    }
+
+    let second_round = standings.swiss_pairings();
+    assert_eq!(2, second_round.len());
+    for &(a, b) in second_round.iter() {
+        assert!(!standings.have_played(a, b));
+    }
+    // winners of the first round meet each other
+    let winners: Vec<usize> = first_round.iter().map(|x| x.0).collect();
+    assert!(winners.contains(&second_round[0].0) && winners.contains(&second_round[0].1));
+}
+
+#[test]
+fn test_swiss_byes() {
+    assert_eq!(None, Standings::new(names(4)).swiss_bye());
+
+    // everyone sits out once before anyone does twice
+    let mut standings = Standings::new(names(3));
+    for _ in 0..3 {
+        let pairings = standings.swiss_pairings();
+        let bye = standings.swiss_bye().unwrap();
+        assert_eq!(1, pairings.len());
+        assert!(pairings[0].0 != bye && pairings[0].1 != bye);
+        standings.record_bye(bye);
+        for &(a, b) in pairings.iter() {
+            standings.record_game(a, b, GameOutcome::FirstWon);
+        }
+    }
+    for standing in standings.standings() {
+        assert_eq!(1, standing.byes);
+        assert_eq!(2, standing.games());
+        // a bye scores as a win
+        assert_eq!((standing.wins + 1) as f64, standing.points());
+    }
+
+    // but does not change the rating
+    let mut standings = Standings::new(names(3));
+    standings.record_bye(2);
+    assert_eq!(1.0, standings.standings()[2].points());
+    assert_eq!(INITIAL_RATING, standings.standings()[2].rating);
+    assert_eq!(2, standings.ranking()[0]);
+    assert!(standings.to_text().lines().next().unwrap().contains("bye"));
+}
@@ -0,0 +1,142 @@
+use battle_sim::r#impl::buf_battle_log_reader::{read_log_records, LogRecord};
+use battle_sim::r#impl::buf_battle_logger::BufferLogWriter;
+use battle_sim::r#impl::grid_battle::{new_player, GridBattle, GridPlayerState};
+use battle_sim::r#impl::grid_map::GridBattleMap;
+use battle_sim::r#impl::grid_map_prober::GridMapProber;
+use battle_sim::r#impl::grid_orientation::GridOrientation;
+use battle_sim::r#impl::log_events::LogAction;
+use battle_sim::r#impl::simple_battle_logic::{PlayerCommand, SimpleBattleLogic};
+use battle_sim::r#impl::simple_battle_object_layer::SimpleBattleObjectLayer;
+use battle_sim::r#impl::verify_log_writer::VerifyLogWriter;
+use battle_sim::log_data::LogWriter;
+use battle_sim::object_layer::ObjectLayer;
+use battle_sim::think_budget::{StepBudget, ThinkLimit};
+
+mod common;
+use common::{FnCommandTimer, SimpleTileType, TestTrivialLogic};
+
+fn players() -> Vec<(GridPlayerState, String)> {
+    vec![
+        (
+            new_player(1, 1, GridOrientation::East, 2, 1, "player1"),
+            "\
+for i in range(3):
+    turn_cw()
+    print(i)
+shoot()
+            "
+            .to_owned(),
+        ),
+        (
+            new_player(4, 1, GridOrientation::West, 2, 1, "player2"),
+            "\
+move_forward()
+shoot()
+            "
+            .to_owned(),
+        ),
+    ]
+}
+
+fn run_battle<LW, F, T>(logger: LW, check: F) -> T
+where
+    LW: LogWriter<String, LogAction>,
+    F: FnOnce(&LW) -> T,
+{
+    let map = GridBattleMap::new(6, 3, SimpleTileType::Nothin, SimpleTileType::Wall);
+    let mut b = GridBattle::new(
+        SimpleBattleLogic::new(
+            map,
+            TestTrivialLogic {},
+            GridMapProber {},
+            SimpleBattleObjectLayer::new(),
+            FnCommandTimer::new(|com| match com {
+                PlayerCommand::Print(_) => 0,
+                _ => 10,
+            }),
+            1,
+            0,
+        ),
+        players(),
+        logger,
+    );
+    b.set_think_limit(ThinkLimit::Steps(StepBudget {
+        free_steps: 1000,
+        steps_per_penalty: 1000,
+        max_steps: 100000,
+        hard_timeout: None,
+    }));
+    b.run_simulation();
+    check(b.log_writer())
+}
+
+fn record_battle() -> Vec<LogRecord> {
+    let mut buf = Vec::new();
+    run_battle(BufferLogWriter::new(&mut buf), |_| ());
+    read_log_records(buf.as_slice()).unwrap()
+}
+
+#[test]
+fn test_verify_same_battle() {
+    let recorded = record_battle();
+    assert!(recorded.len() > 4);
+
+    // object uids of the second battle differ, but it must still verify
+    let divergence = run_battle(VerifyLogWriter::new(recorded), |x| x.divergence());
+    assert_eq!(None, divergence);
+}
+
+#[test]
+fn test_verify_program_exits() {
+    let recorded = record_battle();
+    let exits: Vec<(&str, &str)> = recorded
+        .iter()
+        .filter(|x| x.action.starts_with("exit["))
+        .map(|x| (&x.object[..x.object.find('(').unwrap()], x.action.as_str()))
+        .collect();
+    // player2 is stopped at its shot cooldown, after its shot killed player1
+    assert_eq!(
+        vec![
+            ("player[player1]", "exit[stopped]"),
+            ("player[player2]", "exit[stopped]")
+        ],
+        exits
+    );
+
+    // programs ending in the same tick the game ends must end the same way every run
+    for _ in 0..10 {
+        let divergence = run_battle(VerifyLogWriter::new(recorded.clone()), |x| x.divergence());
+        assert_eq!(None, divergence);
+    }
+}
+
+#[test]
+fn test_verify_tampered_battle() {
+    let mut recorded = record_battle();
+    recorded[4].time += 1;
+    let tampered = recorded[4].clone();
+
+    let divergence = run_battle(VerifyLogWriter::new(recorded), |x| x.divergence()).unwrap();
+    assert_eq!(5, divergence.record);
+    assert!(divergence.to_string().starts_with("record 5: "));
+    assert_eq!(Some(tampered.clone()), divergence.expected);
+    assert_eq!(tampered.time - 1, divergence.actual.unwrap().time);
+}
+
+#[test]
+fn test_verify_truncated_battle() {
+    let mut recorded = record_battle();
+    let last = recorded.pop().unwrap();
+    let divergence =
+        run_battle(VerifyLogWriter::new(recorded.clone()), |x| x.divergence()).unwrap();
+    assert_eq!(recorded.len() + 1, divergence.record);
+    assert_eq!(None, divergence.expected);
+    assert_eq!(Some(last.action.clone()), divergence.actual.map(|x| x.action));
+
+    recorded.push(last.clone());
+    recorded.push(last);
+    let divergence =
+        run_battle(VerifyLogWriter::new(recorded.clone()), |x| x.divergence()).unwrap();
+    assert_eq!(recorded.len(), divergence.record);
+    assert_eq!(None, divergence.actual);
+}